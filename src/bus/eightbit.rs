@@ -1,9 +1,10 @@
-use embedded_hal::digital::OutputPin;
-use embedded_hal::{delay::DelayNs, digital};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{ErrorKind, OutputPin};
 
 use crate::{
 	bus::DataBus,
 	error::{Error, Port, Result},
+	instruction::DataLength,
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -37,17 +38,16 @@ pub struct EightBitBus<
 }
 
 impl<
-		RS: OutputPin<Error = E>,
-		EN: OutputPin<Error = E>,
-		D0: OutputPin<Error = E>,
-		D1: OutputPin<Error = E>,
-		D2: OutputPin<Error = E>,
-		D3: OutputPin<Error = E>,
-		D4: OutputPin<Error = E>,
-		D5: OutputPin<Error = E>,
-		D6: OutputPin<Error = E>,
-		D7: OutputPin<Error = E>,
-		E,
+		RS: OutputPin,
+		EN: OutputPin,
+		D0: OutputPin,
+		D1: OutputPin,
+		D2: OutputPin,
+		D3: OutputPin,
+		D4: OutputPin,
+		D5: OutputPin,
+		D6: OutputPin,
+		D7: OutputPin,
 	> EightBitBus<RS, EN, D0, D1, D2, D3, D4, D5, D6, D7>
 {
 	pub fn from_pins(
@@ -60,7 +60,7 @@ impl<
 		self.pins
 	}
 
-	fn set_bus_bits(&mut self, data: u8) -> Result<(), E> {
+	fn set_bus_bits(&mut self, data: u8) -> Result<(), ErrorKind> {
 		let db0: bool = (0b0000_0001 & data) != 0;
 		let db1: bool = (0b0000_0010 & data) != 0;
 		let db2: bool = (0b0000_0100 & data) != 0;
@@ -70,46 +70,49 @@ impl<
 		let db6: bool = (0b0100_0000 & data) != 0;
 		let db7: bool = (0b1000_0000 & data) != 0;
 
-		self.pins.d0.set_state(db0.into()).map_err(Error::wrap_io(Port::D0))?;
-		self.pins.d1.set_state(db1.into()).map_err(Error::wrap_io(Port::D1))?;
-		self.pins.d2.set_state(db2.into()).map_err(Error::wrap_io(Port::D2))?;
-		self.pins.d3.set_state(db3.into()).map_err(Error::wrap_io(Port::D3))?;
-		self.pins.d4.set_state(db4.into()).map_err(Error::wrap_io(Port::D4))?;
-		self.pins.d5.set_state(db5.into()).map_err(Error::wrap_io(Port::D5))?;
-		self.pins.d6.set_state(db6.into()).map_err(Error::wrap_io(Port::D6))?;
-		self.pins.d7.set_state(db7.into()).map_err(Error::wrap_io(Port::D7))?;
+		self.pins.d0.set_state(db0.into()).map_err(Error::wrap_pin(Port::D0))?;
+		self.pins.d1.set_state(db1.into()).map_err(Error::wrap_pin(Port::D1))?;
+		self.pins.d2.set_state(db2.into()).map_err(Error::wrap_pin(Port::D2))?;
+		self.pins.d3.set_state(db3.into()).map_err(Error::wrap_pin(Port::D3))?;
+		self.pins.d4.set_state(db4.into()).map_err(Error::wrap_pin(Port::D4))?;
+		self.pins.d5.set_state(db5.into()).map_err(Error::wrap_pin(Port::D5))?;
+		self.pins.d6.set_state(db6.into()).map_err(Error::wrap_pin(Port::D6))?;
+		self.pins.d7.set_state(db7.into()).map_err(Error::wrap_pin(Port::D7))?;
 
 		Ok(())
 	}
 }
 
 impl<
-		RS: OutputPin<Error = E>,
-		EN: OutputPin<Error = E>,
-		D0: OutputPin<Error = E>,
-		D1: OutputPin<Error = E>,
-		D2: OutputPin<Error = E>,
-		D3: OutputPin<Error = E>,
-		D4: OutputPin<Error = E>,
-		D5: OutputPin<Error = E>,
-		D6: OutputPin<Error = E>,
-		D7: OutputPin<Error = E>,
-		E: digital::Error,
+		RS: OutputPin,
+		EN: OutputPin,
+		D0: OutputPin,
+		D1: OutputPin,
+		D2: OutputPin,
+		D3: OutputPin,
+		D4: OutputPin,
+		D5: OutputPin,
+		D6: OutputPin,
+		D7: OutputPin,
 	> DataBus for EightBitBus<RS, EN, D0, D1, D2, D3, D4, D5, D6, D7>
 {
-	type Error = E;
+	type Error = ErrorKind;
+
+	fn data_length(&self) -> DataLength {
+		DataLength::EightBit
+	}
 
 	fn write<D: DelayNs>(&mut self, byte: u8, data: bool, delay: &mut D) -> Result<(), Self::Error> {
-		self.pins.rs.set_state(data.into()).map_err(Error::wrap_io(Port::RS))?;
+		self.pins.rs.set_state(data.into()).map_err(Error::wrap_pin(Port::RS))?;
 
 		self.set_bus_bits(byte)?;
 
-		self.pins.en.set_high().map_err(Error::wrap_io(Port::EN))?;
+		self.pins.en.set_high().map_err(Error::wrap_pin(Port::EN))?;
 		delay.delay_ms(2u32);
-		self.pins.en.set_low().map_err(Error::wrap_io(Port::EN))?;
+		self.pins.en.set_low().map_err(Error::wrap_pin(Port::EN))?;
 
 		if data {
-			self.pins.rs.set_low().map_err(Error::wrap_io(Port::RS))?;
+			self.pins.rs.set_low().map_err(Error::wrap_pin(Port::RS))?;
 		}
 
 		Ok(())
@@ -118,56 +121,50 @@ impl<
 
 #[cfg(feature = "async")]
 mod non_blocking {
-	use core::future::Future;
-	use embedded_hal::digital::{self, OutputPin};
+	use embedded_hal::digital::{ErrorKind, OutputPin};
 	use embedded_hal_async::delay::DelayNs;
 
 	use crate::{
 		error::{Error, Port, Result},
+		instruction::DataLength,
 		non_blocking::bus::DataBus,
 	};
 
 	use super::EightBitBus;
 
 	impl<
-			RS: OutputPin<Error = E> + 'static,
-			EN: OutputPin<Error = E> + 'static,
-			D0: OutputPin<Error = E> + 'static,
-			D1: OutputPin<Error = E> + 'static,
-			D2: OutputPin<Error = E> + 'static,
-			D3: OutputPin<Error = E> + 'static,
-			D4: OutputPin<Error = E> + 'static,
-			D5: OutputPin<Error = E> + 'static,
-			D6: OutputPin<Error = E> + 'static,
-			D7: OutputPin<Error = E> + 'static,
-			E: digital::Error,
+			RS: OutputPin,
+			EN: OutputPin,
+			D0: OutputPin,
+			D1: OutputPin,
+			D2: OutputPin,
+			D3: OutputPin,
+			D4: OutputPin,
+			D5: OutputPin,
+			D6: OutputPin,
+			D7: OutputPin,
 		> DataBus for EightBitBus<RS, EN, D0, D1, D2, D3, D4, D5, D6, D7>
 	{
-		type Error = E;
-
-		type WriteFuture<'a, D: 'a + DelayNs> = impl Future<Output = Result<(), Self::Error>> + 'a;
+		type Error = ErrorKind;
 
-		fn write<'a, D: DelayNs + 'a>(
-			&'a mut self,
-			byte: u8,
-			data: bool,
-			delay: &'a mut D,
-		) -> Self::WriteFuture<'a, D> {
-			async move {
-				self.pins.rs.set_state(data.into()).map_err(Error::wrap_io(Port::RS))?;
+		fn data_length(&self) -> DataLength {
+			DataLength::EightBit
+		}
 
-				self.set_bus_bits(byte)?;
+		async fn write<D: DelayNs>(&mut self, byte: u8, data: bool, delay: &mut D) -> Result<(), Self::Error> {
+			self.pins.rs.set_state(data.into()).map_err(Error::wrap_pin(Port::RS))?;
 
-				self.pins.en.set_high().map_err(Error::wrap_io(Port::EN))?;
-				delay.delay_ms(2).await;
-				self.pins.en.set_low().map_err(Error::wrap_io(Port::EN))?;
+			self.set_bus_bits(byte)?;
 
-				if data {
-					self.pins.rs.set_low().map_err(Error::wrap_io(Port::RS))?;
-				}
+			self.pins.en.set_high().map_err(Error::wrap_pin(Port::EN))?;
+			delay.delay_ms(2).await;
+			self.pins.en.set_low().map_err(Error::wrap_pin(Port::EN))?;
 
-				Ok(())
+			if data {
+				self.pins.rs.set_low().map_err(Error::wrap_pin(Port::RS))?;
 			}
+
+			Ok(())
 		}
 	}
 }
@@ -1,5 +1,5 @@
 use embedded_hal::delay::DelayNs;
-use embedded_hal::digital::{self, OutputPin};
+use embedded_hal::digital::{ErrorKind, OutputPin};
 
 use crate::bus::DataBus;
 use crate::error::{Error, Port, Result};
@@ -19,15 +19,8 @@ pub struct FourBitBus<RS: OutputPin, EN: OutputPin, D4: OutputPin, D5: OutputPin
 	pins: FourBitBusPins<RS, EN, D4, D5, D6, D7>,
 }
 
-impl<
-		RS: OutputPin<Error = E>,
-		EN: OutputPin<Error = E>,
-		D4: OutputPin<Error = E>,
-		D5: OutputPin<Error = E>,
-		D6: OutputPin<Error = E>,
-		D7: OutputPin<Error = E>,
-		E,
-	> FourBitBus<RS, EN, D4, D5, D6, D7>
+impl<RS: OutputPin, EN: OutputPin, D4: OutputPin, D5: OutputPin, D6: OutputPin, D7: OutputPin>
+	FourBitBus<RS, EN, D4, D5, D6, D7>
 {
 	pub fn from_pins(pins: FourBitBusPins<RS, EN, D4, D5, D6, D7>) -> FourBitBus<RS, EN, D4, D5, D6, D7> {
 		FourBitBus { pins }
@@ -37,66 +30,59 @@ impl<
 		self.pins
 	}
 
-	fn write_lower_nibble(&mut self, data: u8) -> Result<(), E> {
+	fn write_lower_nibble(&mut self, data: u8) -> Result<(), ErrorKind> {
 		let db0: bool = (0b0000_0001 & data) != 0;
 		let db1: bool = (0b0000_0010 & data) != 0;
 		let db2: bool = (0b0000_0100 & data) != 0;
 		let db3: bool = (0b0000_1000 & data) != 0;
 
-		self.pins.d4.set_state(db0.into()).map_err(Error::wrap_io(Port::D4))?;
-		self.pins.d5.set_state(db1.into()).map_err(Error::wrap_io(Port::D5))?;
-		self.pins.d6.set_state(db2.into()).map_err(Error::wrap_io(Port::D6))?;
-		self.pins.d7.set_state(db3.into()).map_err(Error::wrap_io(Port::D7))?;
+		self.pins.d4.set_state(db0.into()).map_err(Error::wrap_pin(Port::D4))?;
+		self.pins.d5.set_state(db1.into()).map_err(Error::wrap_pin(Port::D5))?;
+		self.pins.d6.set_state(db2.into()).map_err(Error::wrap_pin(Port::D6))?;
+		self.pins.d7.set_state(db3.into()).map_err(Error::wrap_pin(Port::D7))?;
 
 		Ok(())
 	}
 
-	fn write_upper_nibble(&mut self, data: u8) -> Result<(), E> {
+	fn write_upper_nibble(&mut self, data: u8) -> Result<(), ErrorKind> {
 		let db4: bool = (0b0001_0000 & data) != 0;
 		let db5: bool = (0b0010_0000 & data) != 0;
 		let db6: bool = (0b0100_0000 & data) != 0;
 		let db7: bool = (0b1000_0000 & data) != 0;
 
-		self.pins.d4.set_state(db4.into()).map_err(Error::wrap_io(Port::D4))?;
-		self.pins.d5.set_state(db5.into()).map_err(Error::wrap_io(Port::D5))?;
-		self.pins.d6.set_state(db6.into()).map_err(Error::wrap_io(Port::D6))?;
-		self.pins.d7.set_state(db7.into()).map_err(Error::wrap_io(Port::D7))?;
+		self.pins.d4.set_state(db4.into()).map_err(Error::wrap_pin(Port::D4))?;
+		self.pins.d5.set_state(db5.into()).map_err(Error::wrap_pin(Port::D5))?;
+		self.pins.d6.set_state(db6.into()).map_err(Error::wrap_pin(Port::D6))?;
+		self.pins.d7.set_state(db7.into()).map_err(Error::wrap_pin(Port::D7))?;
 
 		Ok(())
 	}
 }
 
-impl<
-		RS: OutputPin<Error = E>,
-		EN: OutputPin<Error = E>,
-		D4: OutputPin<Error = E>,
-		D5: OutputPin<Error = E>,
-		D6: OutputPin<Error = E>,
-		D7: OutputPin<Error = E>,
-		E: digital::Error,
-	> DataBus for FourBitBus<RS, EN, D4, D5, D6, D7>
+impl<RS: OutputPin, EN: OutputPin, D4: OutputPin, D5: OutputPin, D6: OutputPin, D7: OutputPin> DataBus
+	for FourBitBus<RS, EN, D4, D5, D6, D7>
 {
-	type Error = E;
+	type Error = ErrorKind;
 
 	fn write<D: DelayNs>(&mut self, byte: u8, data: bool, delay: &mut D) -> Result<(), Self::Error> {
-		self.pins.rs.set_state(data.into()).map_err(Error::wrap_io(Port::RS))?;
+		self.pins.rs.set_state(data.into()).map_err(Error::wrap_pin(Port::RS))?;
 
 		self.write_upper_nibble(byte)?;
 
 		// Pulse the enable pin to recieve the upper nibble
-		self.pins.en.set_high().map_err(Error::wrap_io(Port::EN))?;
+		self.pins.en.set_high().map_err(Error::wrap_pin(Port::EN))?;
 		delay.delay_ms(2u32);
-		self.pins.en.set_low().map_err(Error::wrap_io(Port::EN))?;
+		self.pins.en.set_low().map_err(Error::wrap_pin(Port::EN))?;
 
 		self.write_lower_nibble(byte)?;
 
 		// Pulse the enable pin to recieve the lower nibble
-		self.pins.en.set_high().map_err(Error::wrap_io(Port::EN))?;
+		self.pins.en.set_high().map_err(Error::wrap_pin(Port::EN))?;
 		delay.delay_ms(2u32);
-		self.pins.en.set_low().map_err(Error::wrap_io(Port::EN))?;
+		self.pins.en.set_low().map_err(Error::wrap_pin(Port::EN))?;
 
 		if data {
-			self.pins.rs.set_low().map_err(Error::wrap_io(Port::RS))?;
+			self.pins.rs.set_low().map_err(Error::wrap_pin(Port::RS))?;
 		}
 
 		Ok(())
@@ -105,8 +91,7 @@ impl<
 
 #[cfg(feature = "async")]
 mod non_blocking {
-	use core::future::Future;
-	use embedded_hal::digital::{self, OutputPin};
+	use embedded_hal::digital::{ErrorKind, OutputPin};
 	use embedded_hal_async::delay::DelayNs;
 
 	use crate::{
@@ -116,48 +101,82 @@ mod non_blocking {
 
 	use super::FourBitBus;
 
-	impl<
-			RS: OutputPin<Error = E> + 'static,
-			EN: OutputPin<Error = E> + 'static,
-			D4: OutputPin<Error = E> + 'static,
-			D5: OutputPin<Error = E> + 'static,
-			D6: OutputPin<Error = E> + 'static,
-			D7: OutputPin<Error = E> + 'static,
-			E: digital::Error,
-		> DataBus for FourBitBus<RS, EN, D4, D5, D6, D7>
+	impl<RS: OutputPin, EN: OutputPin, D4: OutputPin, D5: OutputPin, D6: OutputPin, D7: OutputPin> DataBus
+		for FourBitBus<RS, EN, D4, D5, D6, D7>
 	{
-		type Error = E;
+		type Error = ErrorKind;
 
-		type WriteFuture<'a, D: 'a + DelayNs> = impl Future<Output = Result<(), Self::Error>> + 'a;
+		async fn write<D: DelayNs>(&mut self, byte: u8, data: bool, delay: &mut D) -> Result<(), Self::Error> {
+			self.pins.rs.set_state(data.into()).map_err(Error::wrap_pin(Port::RS))?;
 
-		fn write<'a, D: DelayNs + 'a>(
-			&'a mut self,
-			byte: u8,
-			data: bool,
-			delay: &'a mut D,
-		) -> Self::WriteFuture<'a, D> {
-			async move {
-				self.pins.rs.set_state(data.into()).map_err(Error::wrap_io(Port::RS))?;
+			self.write_upper_nibble(byte)?;
 
-				self.write_upper_nibble(byte)?;
+			// Pulse the enable pin to recieve the upper nibble
+			self.pins.en.set_high().map_err(Error::wrap_pin(Port::EN))?;
+			delay.delay_ms(2).await;
+			self.pins.en.set_low().map_err(Error::wrap_pin(Port::EN))?;
 
-				// Pulse the enable pin to recieve the upper nibble
-				self.pins.en.set_high().map_err(Error::wrap_io(Port::EN))?;
-				delay.delay_ms(2).await;
-				self.pins.en.set_low().map_err(Error::wrap_io(Port::EN))?;
+			self.write_lower_nibble(byte)?;
+			// Pulse the enable pin to recieve the lower nibble
+			self.pins.en.set_high().map_err(Error::wrap_pin(Port::EN))?;
+			delay.delay_ms(2).await;
+			self.pins.en.set_low().map_err(Error::wrap_pin(Port::EN))?;
 
-				self.write_lower_nibble(byte)?;
-				// Pulse the enable pin to recieve the lower nibble
-				self.pins.en.set_high().map_err(Error::wrap_io(Port::EN))?;
-				delay.delay_ms(2).await;
-				self.pins.en.set_low().map_err(Error::wrap_io(Port::EN))?;
+			if data {
+				self.pins.rs.set_low().map_err(Error::wrap_pin(Port::RS))?;
+			}
 
-				if data {
-					self.pins.rs.set_low().map_err(Error::wrap_io(Port::RS))?;
-				}
+			Ok(())
+		}
+	}
+}
 
-				Ok(())
-			}
+#[cfg(test)]
+mod tests {
+	use embedded_hal::digital::{self, ErrorType};
+
+	use super::*;
+	use crate::test_util::{NoDelay, Pin};
+
+	/// Pin of an I/O expander which lost its connection.
+	struct ExpanderPin;
+
+	#[derive(Debug)]
+	struct ExpanderError;
+
+	impl digital::Error for ExpanderError {
+		fn kind(&self) -> ErrorKind {
+			ErrorKind::Other
 		}
 	}
+
+	impl ErrorType for ExpanderPin {
+		type Error = ExpanderError;
+	}
+
+	impl OutputPin for ExpanderPin {
+		fn set_low(&mut self) -> core::result::Result<(), Self::Error> {
+			Err(ExpanderError)
+		}
+
+		fn set_high(&mut self) -> core::result::Result<(), Self::Error> {
+			Err(ExpanderError)
+		}
+	}
+
+	#[test]
+	fn mixed_pin_errors() {
+		let pins = FourBitBusPins {
+			rs: Pin::default(),
+			en: Pin::default(),
+			d4: Pin::default(),
+			d5: ExpanderPin,
+			d6: Pin::default(),
+			d7: Pin::default(),
+		};
+		let mut bus = FourBitBus::from_pins(pins);
+
+		let error = bus.write(b'a', true, &mut NoDelay).unwrap_err();
+		assert!(matches!(error, Error::Io { port: Port::D5, error: ErrorKind::Other }));
+	}
 }
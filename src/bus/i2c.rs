@@ -7,6 +7,8 @@ use crate::{bus::DataBus, error::Result};
 pub struct I2CBus<I2C> {
 	i2c_bus: I2C,
 	address: u8,
+	max_chunk_size: usize,
+	backlight: u8,
 }
 
 const BACKLIGHT: u8 = 0b0000_1000;
@@ -14,9 +16,54 @@ const ENABLE: u8 = 0b0000_0100;
 // const READ_WRITE: u8 = 0b0000_0010; // Not used as no reading of the `HD44780` is done
 const REGISTER_SELECT: u8 = 0b0000_0001;
 
+/// I2C writes per byte on the batched path: set RS, then latch each nibble on the falling
+/// edge of EN.
+const FRAMES_PER_BYTE: usize = 5;
+
+/// Size of the buffer the batched path encodes into, the largest chunk possible.
+const MAX_CHUNK_SIZE: usize = 255;
+
+/// Chunk size used unless configured otherwise, in bytes. Fits 12 characters, 60 bytes, into
+/// each I2C write.
+pub(crate) const DEFAULT_CHUNK_SIZE: usize = 64;
+
+/// Frames sending `byte` as data when written to the PCF8574 one after another.
+const fn data_frames(byte: u8, backlight: u8) -> [u8; FRAMES_PER_BYTE] {
+	let upper = (byte & 0xF0) | REGISTER_SELECT | backlight;
+	let lower = ((byte & 0x0F) << 4) | REGISTER_SELECT | backlight;
+
+	[upper, upper | ENABLE, upper, lower | ENABLE, lower]
+}
+
 impl<I2C> I2CBus<I2C> {
 	pub fn new(i2c_bus: I2C, address: u8) -> I2CBus<I2C> {
-		I2CBus { i2c_bus, address }
+		I2CBus { i2c_bus, address, max_chunk_size: DEFAULT_CHUNK_SIZE, backlight: BACKLIGHT }
+	}
+
+	/// Largest I2C write, in bytes, used to send text. Every character takes 5 bytes on the
+	/// bus, so this is rounded down to a multiple of 5 and clamped to `5..=255`. Defaults to 64.
+	///
+	/// The `HD44780` needs no extra delay between characters of one write up to a bus
+	/// frequency of 400kHz. Faster buses should use a chunk size of 5.
+	pub fn with_max_chunk_size(mut self, max_chunk_size: usize) -> Self {
+		self.max_chunk_size = max_chunk_size;
+		self
+	}
+
+	/// Number of data bytes sent per I2C write.
+	fn bytes_per_chunk(&self) -> usize {
+		self.max_chunk_size.clamp(FRAMES_PER_BYTE, MAX_CHUNK_SIZE) / FRAMES_PER_BYTE
+	}
+
+	/// Encode `bytes` into the frames written in one go
+	fn encode_chunk(&self, bytes: &[u8], buffer: &mut [u8; MAX_CHUNK_SIZE]) -> usize {
+		let (frames, _) = buffer.as_chunks_mut::<FRAMES_PER_BYTE>();
+
+		for (frames, &byte) in frames.iter_mut().zip(bytes) {
+			*frames = data_frames(byte, self.backlight);
+		}
+
+		bytes.len() * FRAMES_PER_BYTE
 	}
 
 	pub fn destroy(self) -> I2C {
@@ -32,7 +79,7 @@ impl<I2C: I2c> I2CBus<I2C> {
 			false => 0u8,
 			true => REGISTER_SELECT,
 		};
-		let byte = nibble | rs | BACKLIGHT;
+		let byte = nibble | rs | self.backlight;
 
 		self.i2c_bus.write(self.address, &[byte, byte | ENABLE]).map_err(Error::wrap_io(Port::I2C))?;
 		delay.delay_ms(2u32);
@@ -52,11 +99,30 @@ impl<I2C: I2c> DataBus for I2CBus<I2C> {
 
 		Ok(())
 	}
+
+	fn write_data<D: DelayNs>(&mut self, bytes: &[u8], delay: &mut D) -> Result<(), Self::Error> {
+		let mut buffer = [0; MAX_CHUNK_SIZE];
+
+		for chunk in bytes.chunks(self.bytes_per_chunk()) {
+			let len = self.encode_chunk(chunk, &mut buffer);
+			self.i2c_bus.write(self.address, &buffer[..len]).map_err(Error::wrap_io(Port::I2C))?;
+
+			// Wait for the last byte to be processed
+			delay.delay_us(100);
+		}
+
+		Ok(())
+	}
+
+	fn set_backlight<D: DelayNs>(&mut self, on: bool, _delay: &mut D) -> Result<(), Self::Error> {
+		self.backlight = if on { BACKLIGHT } else { 0 };
+
+		self.i2c_bus.write(self.address, &[self.backlight]).map_err(Error::wrap_io(Port::I2C))
+	}
 }
 
 #[cfg(feature = "async")]
 mod non_blocking {
-	use core::future::Future;
 	use embedded_hal_async::delay::DelayNs;
 	use embedded_hal_async::i2c::I2c;
 
@@ -65,22 +131,22 @@ mod non_blocking {
 		non_blocking::bus::DataBus,
 	};
 
-	use super::{I2CBus, BACKLIGHT, ENABLE, REGISTER_SELECT};
+	use super::{I2CBus, BACKLIGHT, ENABLE, MAX_CHUNK_SIZE, REGISTER_SELECT};
 
 	impl<I2C: I2c> I2CBus<I2C> {
 		/// Write a nibble to the lcd
 		/// The nibble should be in the upper part of the byte
-		async fn write_nibble_non_blocking<'a, D: DelayNs + 'a>(
+		async fn write_nibble_non_blocking<D: DelayNs>(
 			&mut self,
 			nibble: u8,
 			data: bool,
-			delay: &'a mut D,
+			delay: &mut D,
 		) -> Result<(), I2C::Error> {
 			let rs = match data {
 				false => 0u8,
 				true => REGISTER_SELECT,
 			};
-			let byte = nibble | rs | BACKLIGHT;
+			let byte = nibble | rs | self.backlight;
 
 			self.i2c_bus.write(self.address, &[byte, byte | ENABLE]).await.map_err(Error::wrap_io(Port::I2C))?;
 			delay.delay_ms(2).await;
@@ -88,26 +154,71 @@ mod non_blocking {
 		}
 	}
 
-	impl<I2C: I2c + 'static> DataBus for I2CBus<I2C> {
+	impl<I2C: I2c> DataBus for I2CBus<I2C> {
 		type Error = I2C::Error;
 
-		type WriteFuture<'a, D: 'a + DelayNs> = impl Future<Output = Result<(), Self::Error>> + 'a;
+		async fn write<D: DelayNs>(&mut self, byte: u8, data: bool, delay: &mut D) -> Result<(), Self::Error> {
+			let upper_nibble = byte & 0xF0;
+			self.write_nibble_non_blocking(upper_nibble, data, delay).await?;
 
-		fn write<'a, D: DelayNs + 'a>(
-			&'a mut self,
-			byte: u8,
-			data: bool,
-			delay: &'a mut D,
-		) -> Self::WriteFuture<'a, D> {
-			async move {
-				let upper_nibble = byte & 0xF0;
-				self.write_nibble_non_blocking(upper_nibble, data, delay).await?;
+			let lower_nibble = (byte & 0x0F) << 4;
+			self.write_nibble_non_blocking(lower_nibble, data, delay).await?;
+
+			Ok(())
+		}
 
-				let lower_nibble = (byte & 0x0F) << 4;
-				self.write_nibble_non_blocking(lower_nibble, data, delay).await?;
+		async fn write_data<D: DelayNs>(&mut self, bytes: &[u8], delay: &mut D) -> Result<(), Self::Error> {
+			let mut buffer = [0; MAX_CHUNK_SIZE];
 
-				Ok(())
+			for chunk in bytes.chunks(self.bytes_per_chunk()) {
+				let len = self.encode_chunk(chunk, &mut buffer);
+				self.i2c_bus.write(self.address, &buffer[..len]).await.map_err(Error::wrap_io(Port::I2C))?;
+
+				// Wait for the last byte to be processed
+				delay.delay_us(100).await;
 			}
+
+			Ok(())
+		}
+
+		async fn set_backlight<D: DelayNs>(&mut self, on: bool, _delay: &mut D) -> Result<(), Self::Error> {
+			self.backlight = if on { BACKLIGHT } else { 0 };
+
+			self.i2c_bus.write(self.address, &[self.backlight]).await.map_err(Error::wrap_io(Port::I2C))
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_util::{I2cRecorder, NoDelay};
+
+	#[test]
+	fn encodes_data_frames() {
+		assert_eq!(data_frames(b'A', BACKLIGHT), [0x49, 0x4D, 0x49, 0x1D, 0x19]);
+		assert_eq!(data_frames(b'A', 0), [0x41, 0x45, 0x41, 0x15, 0x11]);
+	}
+
+	#[test]
+	fn batches_data_into_chunks() {
+		let mut bus = I2CBus::new(I2cRecorder::default(), 0x27).with_max_chunk_size(12);
+
+		bus.write_data(b"Hello", &mut NoDelay).unwrap();
+
+		let recorder = bus.destroy();
+		assert!(recorder.writes().map(<[u8]>::len).eq([10, 10, 5]));
+
+		let first = recorder.writes().next().unwrap();
+		assert_eq!(first[..5], data_frames(b'H', BACKLIGHT));
+		assert_eq!(first[5..], data_frames(b'e', BACKLIGHT));
+	}
+
+	#[test]
+	fn clamps_chunk_size() {
+		let bus = I2CBus::new((), 0x27);
+		assert_eq!(bus.bytes_per_chunk(), 12);
+		assert_eq!(bus.with_max_chunk_size(0).bytes_per_chunk(), 1);
+		assert_eq!(I2CBus::new((), 0x27).with_max_chunk_size(usize::MAX).bytes_per_chunk(), 51);
+	}
+}
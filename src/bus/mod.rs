@@ -3,18 +3,55 @@ use embedded_hal::delay::DelayNs;
 mod eightbit;
 mod fourbit;
 mod i2c;
+mod port;
+mod serial;
+mod shared;
 
 pub use self::eightbit::{EightBitBus, EightBitBusPins};
 pub use self::fourbit::{FourBitBus, FourBitBusPins};
 pub use self::i2c::I2CBus;
+pub use self::port::{ParallelPort, PortBus, PortBusPins};
+pub use self::serial::{I2cControl, SerialControlBus, SpiControl, SpiError, DEFAULT_I2C_ADDRESS};
+#[cfg(feature = "critical-section")]
+pub use self::shared::CriticalSectionLines;
+pub use self::shared::{SharedBus, SharedLines, SharedLinesPins};
+
+pub(crate) use self::shared::sealed::Lock;
+
+pub(crate) use self::i2c::DEFAULT_CHUNK_SIZE;
 
 use crate::error::Result;
+use crate::instruction::DataLength;
 
 pub trait DataBus {
 	type Error: core::fmt::Debug;
 
 	fn write<D: DelayNs>(&mut self, byte: u8, data: bool, delay: &mut D) -> Result<(), Self::Error>;
 
+	/// Write several data bytes in a row, waiting for each of them to be processed.
+	///
+	/// Buses which can transfer many bytes at once override this.
+	fn write_data<D: DelayNs>(&mut self, bytes: &[u8], delay: &mut D) -> Result<(), Self::Error> {
+		for &byte in bytes {
+			self.write(byte, true, delay)?;
+
+			// Wait for the data to be processed
+			delay.delay_us(100);
+		}
+
+		Ok(())
+	}
+
+	/// Turn the backlight on or off. Does nothing on buses without control over a backlight.
+	fn set_backlight<D: DelayNs>(&mut self, _on: bool, _delay: &mut D) -> Result<(), Self::Error> {
+		Ok(())
+	}
+
+	/// Width of the data path to the `HD44780`, which function set instructions have to keep.
+	fn data_length(&self) -> DataLength {
+		DataLength::FourBit
+	}
+
 	// TODO
 	// fn read(...)
 }
@@ -0,0 +1,253 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{ErrorKind, ErrorType, OutputPin};
+
+use crate::bus::DataBus;
+use crate::error::{Error, Port, Result};
+use crate::instruction::DataLength;
+
+/// GPIO port which drives several data lines of the `HD44780` with a single register write,
+/// so they all change at the same time.
+///
+/// Bit `n` of `bits` and `mask` is data line `Dn`. Implementations map these to the pins the
+/// display is wired to, e.g. through a set/reset register:
+///
+/// ```rust,ignore
+/// struct PortB;
+///
+/// impl ErrorType for PortB {
+///     type Error = Infallible;
+/// }
+///
+/// impl ParallelPort for PortB {
+///     fn write_masked(&mut self, bits: u8, mask: u8) -> Result<(), Self::Error> {
+///         // D0..D7 on PB8..PB15: set the high bits, reset the low ones
+///         let set = u32::from(bits & mask) << 8;
+///         let reset = u32::from(!bits & mask) << 24;
+///         unsafe { (*GPIOB::ptr()).bsrr.write(|w| w.bits(set | reset)) };
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait ParallelPort: ErrorType {
+	/// Drive the data lines selected by `mask` to the matching bits of `bits`, leaving the
+	/// other lines alone.
+	fn write_masked(&mut self, bits: u8, mask: u8) -> core::result::Result<(), Self::Error>;
+}
+
+impl<P: ParallelPort + ?Sized> ParallelPort for &mut P {
+	fn write_masked(&mut self, bits: u8, mask: u8) -> core::result::Result<(), Self::Error> {
+		P::write_masked(self, bits, mask)
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PortBusPins<P, RS, EN> {
+	/// Data lines `D0..D7`, or `D4..D7` in 4-bit mode.
+	pub port: P,
+	pub rs: RS,
+	pub en: EN,
+}
+
+/// Parallel bus writing the data lines through a [`ParallelPort`], so a byte costs a single
+/// port write in 8-bit mode, or two in 4-bit mode.
+#[derive(Debug)]
+pub struct PortBus<P: ParallelPort, RS: OutputPin, EN: OutputPin> {
+	pins: PortBusPins<P, RS, EN>,
+	data_length: DataLength,
+}
+
+impl<P: ParallelPort, RS: OutputPin, EN: OutputPin> PortBus<P, RS, EN> {
+	/// In 4-bit mode only `D4..D7` are written, using bits 4 to 7 of the port.
+	pub fn from_pins(pins: PortBusPins<P, RS, EN>, data_length: DataLength) -> PortBus<P, RS, EN> {
+		PortBus { pins, data_length }
+	}
+
+	pub fn destroy(self) -> PortBusPins<P, RS, EN> {
+		self.pins
+	}
+
+	/// Number of times EN is pulsed per byte, shifting the next nibble into place each time.
+	fn transfers(&self) -> usize {
+		match self.data_length {
+			DataLength::EightBit => 1,
+			DataLength::FourBit => 2,
+		}
+	}
+
+	fn set_data_lines(&mut self, bits: u8) -> Result<(), ErrorKind> {
+		let mask = match self.data_length {
+			DataLength::EightBit => 0xFF,
+			DataLength::FourBit => 0xF0,
+		};
+
+		self.pins.port.write_masked(bits, mask).map_err(Error::wrap_pin(Port::Data))
+	}
+}
+
+impl<P: ParallelPort, RS: OutputPin, EN: OutputPin> DataBus for PortBus<P, RS, EN> {
+	type Error = ErrorKind;
+
+	fn data_length(&self) -> DataLength {
+		self.data_length
+	}
+
+	fn write<D: DelayNs>(&mut self, byte: u8, data: bool, delay: &mut D) -> Result<(), Self::Error> {
+		self.pins.rs.set_state(data.into()).map_err(Error::wrap_pin(Port::RS))?;
+
+		let mut bits = byte;
+		for _ in 0..self.transfers() {
+			self.set_data_lines(bits)?;
+
+			self.pins.en.set_high().map_err(Error::wrap_pin(Port::EN))?;
+			delay.delay_ms(2u32);
+			self.pins.en.set_low().map_err(Error::wrap_pin(Port::EN))?;
+
+			bits <<= 4;
+		}
+
+		if data {
+			self.pins.rs.set_low().map_err(Error::wrap_pin(Port::RS))?;
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(feature = "async")]
+mod non_blocking {
+	use embedded_hal::digital::{ErrorKind, OutputPin};
+	use embedded_hal_async::delay::DelayNs;
+
+	use crate::{
+		error::{Error, Port, Result},
+		instruction::DataLength,
+		non_blocking::bus::DataBus,
+	};
+
+	use super::{ParallelPort, PortBus};
+
+	impl<P: ParallelPort, RS: OutputPin, EN: OutputPin> DataBus for PortBus<P, RS, EN> {
+		type Error = ErrorKind;
+
+		fn data_length(&self) -> DataLength {
+			self.data_length
+		}
+
+		async fn write<D: DelayNs>(&mut self, byte: u8, data: bool, delay: &mut D) -> Result<(), Self::Error> {
+			self.pins.rs.set_state(data.into()).map_err(Error::wrap_pin(Port::RS))?;
+
+			let mut bits = byte;
+			for _ in 0..self.transfers() {
+				self.set_data_lines(bits)?;
+
+				self.pins.en.set_high().map_err(Error::wrap_pin(Port::EN))?;
+				delay.delay_ms(2).await;
+				self.pins.en.set_low().map_err(Error::wrap_pin(Port::EN))?;
+
+				bits <<= 4;
+			}
+
+			if data {
+				self.pins.rs.set_low().map_err(Error::wrap_pin(Port::RS))?;
+			}
+
+			Ok(())
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use core::cell::RefCell;
+	use core::convert::Infallible;
+
+	use super::*;
+	use crate::test_util::NoDelay;
+
+	/// Records the port writes and how many times EN latched the data lines.
+	#[derive(Default)]
+	struct Recorder {
+		lines: u8,
+		writes: usize,
+		latched: [u8; 4],
+		latches: usize,
+	}
+
+	struct Lines<'a>(&'a RefCell<Recorder>);
+
+	impl ErrorType for Lines<'_> {
+		type Error = Infallible;
+	}
+
+	impl ParallelPort for Lines<'_> {
+		fn write_masked(&mut self, bits: u8, mask: u8) -> core::result::Result<(), Infallible> {
+			let mut recorder = self.0.borrow_mut();
+			recorder.lines = (recorder.lines & !mask) | (bits & mask);
+			recorder.writes += 1;
+			Ok(())
+		}
+	}
+
+	struct Enable<'a>(&'a RefCell<Recorder>);
+
+	impl ErrorType for Enable<'_> {
+		type Error = Infallible;
+	}
+
+	impl OutputPin for Enable<'_> {
+		fn set_low(&mut self) -> core::result::Result<(), Infallible> {
+			let mut recorder = self.0.borrow_mut();
+			let (lines, latches) = (recorder.lines, recorder.latches);
+			recorder.latched[latches] = lines;
+			recorder.latches += 1;
+			Ok(())
+		}
+
+		fn set_high(&mut self) -> core::result::Result<(), Infallible> {
+			Ok(())
+		}
+	}
+
+	struct Rs;
+
+	impl ErrorType for Rs {
+		type Error = Infallible;
+	}
+
+	impl OutputPin for Rs {
+		fn set_low(&mut self) -> core::result::Result<(), Infallible> {
+			Ok(())
+		}
+
+		fn set_high(&mut self) -> core::result::Result<(), Infallible> {
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn eight_bit_writes_once() {
+		let recorder = RefCell::new(Recorder::default());
+		let pins = PortBusPins { port: Lines(&recorder), rs: Rs, en: Enable(&recorder) };
+		let mut bus = PortBus::from_pins(pins, DataLength::EightBit);
+
+		bus.write(0xA5, true, &mut NoDelay).unwrap();
+
+		let recorder = recorder.borrow();
+		assert_eq!(recorder.writes, 1);
+		assert_eq!(recorder.latched[..recorder.latches], [0xA5]);
+	}
+
+	#[test]
+	fn four_bit_writes_nibbles() {
+		let recorder = RefCell::new(Recorder { lines: 0x0F, ..Default::default() });
+		let pins = PortBusPins { port: Lines(&recorder), rs: Rs, en: Enable(&recorder) };
+		let mut bus = PortBus::from_pins(pins, DataLength::FourBit);
+
+		bus.write(0xA5, true, &mut NoDelay).unwrap();
+
+		// D0..D3 are left alone
+		let recorder = recorder.borrow();
+		assert_eq!(recorder.writes, 2);
+		assert_eq!(recorder.latched[..recorder.latches], [0xAF, 0x5F]);
+	}
+}
@@ -0,0 +1,287 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+use embedded_hal::i2c::I2c;
+use embedded_hal::spi::SpiDevice;
+
+use crate::bus::DataBus;
+use crate::error::{Error, Port, Result};
+use crate::instruction::DataLength;
+
+/// Control byte announcing a single command.
+const COMMAND: u8 = 0b0000_0000;
+/// Control byte announcing that all following bytes of the write are data.
+const DATA: u8 = 0b0100_0000;
+
+/// Most data bytes sent per I2C write, following the control byte.
+const MAX_CHUNK_SIZE: usize = 32;
+
+/// Address of the `ST7032i` and `AiP31068`.
+pub const DEFAULT_I2C_ADDRESS: u8 = 0x3E;
+
+/// Controller speaking I2C itself, selecting command or data with a control byte in front of
+/// the payload.
+#[derive(Debug)]
+pub struct I2cControl<I2C> {
+	i2c_bus: I2C,
+	address: u8,
+	max_chunk_size: usize,
+}
+
+/// Controller on a 4-wire SPI bus, selecting command or data with the `RS` pin.
+#[derive(Debug)]
+pub struct SpiControl<SPI, RS> {
+	spi: SPI,
+	rs: RS,
+}
+
+/// Error of a [`SerialControlBus`] on SPI, [`Port::SPI`] or [`Port::RS`] tells which one.
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub enum SpiError<S, P> {
+	Spi(S),
+	Pin(P),
+}
+
+/// Bus of controllers with a native serial interface, like the `ST7032i`, `AiP31068` (I2C) and
+/// `ST7036` (SPI), so they work without a PCF8574 backpack. Both always use an 8-bit data
+/// path.
+#[derive(Debug)]
+pub struct SerialControlBus<T> {
+	transport: T,
+}
+
+impl<I2C> SerialControlBus<I2cControl<I2C>> {
+	pub fn i2c(i2c_bus: I2C, address: u8) -> Self {
+		Self { transport: I2cControl { i2c_bus, address, max_chunk_size: 1 } }
+	}
+
+	/// Data bytes sent after a single control byte, clamped to `1..=32`. Defaults to 1, each
+	/// character is a write of its own, followed by the wait for it to be processed.
+	///
+	/// Within one write the bytes follow each other every 9 clock cycles. The `ST7032` needs
+	/// 26.3µs to write data to RAM, so more than one byte per write is only safe up to a bus
+	/// frequency of about 340kHz.
+	pub fn with_max_chunk_size(mut self, max_chunk_size: usize) -> Self {
+		self.transport.max_chunk_size = max_chunk_size.clamp(1, MAX_CHUNK_SIZE);
+		self
+	}
+
+	pub fn destroy(self) -> I2C {
+		self.transport.i2c_bus
+	}
+}
+
+impl<SPI, RS> SerialControlBus<SpiControl<SPI, RS>> {
+	pub fn spi(spi: SPI, rs: RS) -> Self {
+		Self { transport: SpiControl { spi, rs } }
+	}
+
+	pub fn destroy(self) -> (SPI, RS) {
+		(self.transport.spi, self.transport.rs)
+	}
+}
+
+impl<I2C: I2c> DataBus for SerialControlBus<I2cControl<I2C>> {
+	type Error = I2C::Error;
+
+	fn write<D: DelayNs>(&mut self, byte: u8, data: bool, _delay: &mut D) -> Result<(), Self::Error> {
+		let control = if data { DATA } else { COMMAND };
+		let I2cControl { i2c_bus, address, .. } = &mut self.transport;
+		i2c_bus.write(*address, &[control, byte]).map_err(Error::wrap_io(Port::I2C))?;
+
+		Ok(())
+	}
+
+	/// Sends up to [`with_max_chunk_size`](SerialControlBus::with_max_chunk_size) bytes with a
+	/// single control byte in front.
+	fn write_data<D: DelayNs>(&mut self, bytes: &[u8], delay: &mut D) -> Result<(), Self::Error> {
+		let mut buffer = [DATA; MAX_CHUNK_SIZE + 1];
+
+		for chunk in bytes.chunks(self.transport.max_chunk_size) {
+			buffer[1..=chunk.len()].copy_from_slice(chunk);
+
+			let I2cControl { i2c_bus, address, .. } = &mut self.transport;
+			i2c_bus.write(*address, &buffer[..=chunk.len()]).map_err(Error::wrap_io(Port::I2C))?;
+
+			// Wait for the last byte to be processed
+			delay.delay_us(100);
+		}
+
+		Ok(())
+	}
+
+	fn data_length(&self) -> DataLength {
+		DataLength::EightBit
+	}
+}
+
+impl<SPI: SpiDevice, RS: OutputPin> DataBus for SerialControlBus<SpiControl<SPI, RS>> {
+	type Error = SpiError<SPI::Error, RS::Error>;
+
+	fn write<D: DelayNs>(&mut self, byte: u8, data: bool, _delay: &mut D) -> Result<(), Self::Error> {
+		let SpiControl { spi, rs } = &mut self.transport;
+		rs.set_state(data.into()).map_err(|error| Error::Io { port: Port::RS, error: SpiError::Pin(error) })?;
+		spi.write(&[byte]).map_err(|error| Error::Io { port: Port::SPI, error: SpiError::Spi(error) })?;
+
+		Ok(())
+	}
+
+	fn data_length(&self) -> DataLength {
+		DataLength::EightBit
+	}
+}
+
+#[cfg(feature = "async")]
+mod non_blocking {
+	use embedded_hal::digital::OutputPin;
+	use embedded_hal_async::{delay::DelayNs, i2c::I2c, spi::SpiDevice};
+
+	use crate::{
+		error::{Error, Port, Result},
+		instruction::DataLength,
+		non_blocking::bus::DataBus,
+	};
+
+	use super::{I2cControl, SerialControlBus, SpiControl, SpiError, COMMAND, DATA, MAX_CHUNK_SIZE};
+
+	impl<I2C: I2c> DataBus for SerialControlBus<I2cControl<I2C>> {
+		type Error = I2C::Error;
+
+		async fn write<D: DelayNs>(&mut self, byte: u8, data: bool, _delay: &mut D) -> Result<(), Self::Error> {
+			let control = if data { DATA } else { COMMAND };
+			let I2cControl { i2c_bus, address, .. } = &mut self.transport;
+			i2c_bus.write(*address, &[control, byte]).await.map_err(Error::wrap_io(Port::I2C))?;
+
+			Ok(())
+		}
+
+		async fn write_data<D: DelayNs>(&mut self, bytes: &[u8], delay: &mut D) -> Result<(), Self::Error> {
+			let mut buffer = [DATA; MAX_CHUNK_SIZE + 1];
+
+			for chunk in bytes.chunks(self.transport.max_chunk_size) {
+				buffer[1..=chunk.len()].copy_from_slice(chunk);
+
+				let I2cControl { i2c_bus, address, .. } = &mut self.transport;
+				i2c_bus.write(*address, &buffer[..=chunk.len()]).await.map_err(Error::wrap_io(Port::I2C))?;
+
+				// Wait for the last byte to be processed
+				delay.delay_us(100).await;
+			}
+
+			Ok(())
+		}
+
+		fn data_length(&self) -> DataLength {
+			DataLength::EightBit
+		}
+	}
+
+	impl<SPI: SpiDevice, RS: OutputPin> DataBus for SerialControlBus<SpiControl<SPI, RS>> {
+		type Error = SpiError<SPI::Error, RS::Error>;
+
+		async fn write<D: DelayNs>(&mut self, byte: u8, data: bool, _delay: &mut D) -> Result<(), Self::Error> {
+			let SpiControl { spi, rs } = &mut self.transport;
+			rs.set_state(data.into()).map_err(|error| Error::Io { port: Port::RS, error: SpiError::Pin(error) })?;
+			spi.write(&[byte]).await.map_err(|error| Error::Io { port: Port::SPI, error: SpiError::Spi(error) })?;
+
+			Ok(())
+		}
+
+		fn data_length(&self) -> DataLength {
+			DataLength::EightBit
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use core::cell::Cell;
+	use core::convert::Infallible;
+
+	use embedded_hal::spi::{self, Operation};
+
+	use super::*;
+	use crate::test_util::{I2cRecorder, NoDelay};
+
+	/// `RS` pin of [`Spi`], which it reads when a byte is sent.
+	struct Rs<'a>(&'a Cell<bool>);
+
+	impl embedded_hal::digital::ErrorType for Rs<'_> {
+		type Error = Infallible;
+	}
+
+	impl OutputPin for Rs<'_> {
+		fn set_low(&mut self) -> core::result::Result<(), Infallible> {
+			self.0.set(false);
+			Ok(())
+		}
+
+		fn set_high(&mut self) -> core::result::Result<(), Infallible> {
+			self.0.set(true);
+			Ok(())
+		}
+	}
+
+	/// Records every byte sent with the level of `RS` at that time.
+	struct Spi<'a> {
+		rs: &'a Cell<bool>,
+		writes: [(u8, bool); 8],
+		len: usize,
+	}
+
+	impl spi::ErrorType for Spi<'_> {
+		type Error = Infallible;
+	}
+
+	impl SpiDevice for Spi<'_> {
+		fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> core::result::Result<(), Infallible> {
+			for operation in operations {
+				if let Operation::Write(bytes) = operation {
+					for &byte in bytes.iter() {
+						self.writes[self.len] = (byte, self.rs.get());
+						self.len += 1;
+					}
+				}
+			}
+
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn control_bytes() {
+		let mut bus = SerialControlBus::i2c(I2cRecorder::default(), DEFAULT_I2C_ADDRESS);
+
+		bus.write(0x01, false, &mut NoDelay).unwrap();
+		bus.write(b'a', true, &mut NoDelay).unwrap();
+		bus.write_data(b"bcd", &mut NoDelay).unwrap();
+
+		let recorder = bus.destroy();
+		assert_eq!(recorder.address, Some(DEFAULT_I2C_ADDRESS));
+		assert!(recorder.writes().eq([&[0x00, 0x01][..], &[0x40, b'a'], &[0x40, b'b'], &[0x40, b'c'], &[0x40, b'd']]));
+	}
+
+	#[test]
+	fn chunked_data() {
+		let mut bus = SerialControlBus::i2c(I2cRecorder::default(), DEFAULT_I2C_ADDRESS).with_max_chunk_size(2);
+
+		bus.write_data(b"abc", &mut NoDelay).unwrap();
+
+		let recorder = bus.destroy();
+		assert!(recorder.writes().eq([&[0x40, b'a', b'b'][..], &[0x40, b'c']]));
+	}
+
+	#[test]
+	fn spi_selects_register_first() {
+		let level = Cell::new(false);
+		let mut bus = SerialControlBus::spi(Spi { rs: &level, writes: [(0, false); 8], len: 0 }, Rs(&level));
+
+		bus.write(0x01, false, &mut NoDelay).unwrap();
+		bus.write_data(b"ab", &mut NoDelay).unwrap();
+		bus.write(0x80, false, &mut NoDelay).unwrap();
+
+		let (spi, _) = bus.destroy();
+		assert_eq!(spi.writes[..spi.len], [(0x01, false), (b'a', true), (b'b', true), (0x80, false)]);
+	}
+}
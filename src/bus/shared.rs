@@ -0,0 +1,262 @@
+use core::cell::RefCell;
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{ErrorKind, OutputPin};
+
+use crate::bus::DataBus;
+use crate::error::{Error, Port, Result};
+
+pub(crate) mod sealed {
+	#[doc(hidden)]
+	pub trait Lock {
+		type Pins;
+
+		fn lock<R>(&self, f: impl FnOnce(&mut Self::Pins) -> R) -> R;
+	}
+
+	impl<L: Lock> Lock for &L {
+		type Pins = L::Pins;
+
+		fn lock<R>(&self, f: impl FnOnce(&mut Self::Pins) -> R) -> R {
+			L::lock(self, f)
+		}
+	}
+}
+
+use sealed::Lock;
+
+/// Register select and data lines of a 4-bit bus which several displays are wired to.
+#[derive(Debug, Clone, Copy)]
+pub struct SharedLinesPins<RS, D4, D5, D6, D7> {
+	pub rs: RS,
+	pub d4: D4,
+	pub d5: D5,
+	pub d6: D6,
+	pub d7: D7,
+}
+
+impl<RS: OutputPin, D4: OutputPin, D5: OutputPin, D6: OutputPin, D7: OutputPin> SharedLinesPins<RS, D4, D5, D6, D7> {
+	fn write_nibble(&mut self, nibble: u8) -> Result<(), ErrorKind> {
+		self.d4.set_state((nibble & 0b0001 != 0).into()).map_err(Error::wrap_pin(Port::D4))?;
+		self.d5.set_state((nibble & 0b0010 != 0).into()).map_err(Error::wrap_pin(Port::D5))?;
+		self.d6.set_state((nibble & 0b0100 != 0).into()).map_err(Error::wrap_pin(Port::D6))?;
+		self.d7.set_state((nibble & 0b1000 != 0).into()).map_err(Error::wrap_pin(Port::D7))?;
+
+		Ok(())
+	}
+
+	/// Write a byte to the display whose enable pin is `en`. Displays with their enable pin
+	/// low ignore the lines, so they are not disturbed.
+	fn write<EN: OutputPin, D: DelayNs>(
+		&mut self,
+		en: &mut EN,
+		byte: u8,
+		data: bool,
+		delay: &mut D,
+	) -> Result<(), ErrorKind> {
+		self.rs.set_state(data.into()).map_err(Error::wrap_pin(Port::RS))?;
+
+		for nibble in [byte >> 4, byte & 0x0F] {
+			self.write_nibble(nibble)?;
+
+			en.set_high().map_err(Error::wrap_pin(Port::EN))?;
+			delay.delay_us(1);
+			en.set_low().map_err(Error::wrap_pin(Port::EN))?;
+		}
+
+		if data {
+			self.rs.set_low().map_err(Error::wrap_pin(Port::RS))?;
+		}
+
+		Ok(())
+	}
+}
+
+/// Lines shared by displays used from a single context, e.g. only from the main loop.
+///
+/// ```rust,ignore
+/// let lines = SharedLines::new(SharedLinesPins { rs, d4, d5, d6, d7 });
+///
+/// let mut top = HD44780::new(DisplayOptionsShared::new(MemoryMap1602::new()).with_bus(lines.bus(en1)), &mut delay)?;
+/// let mut bottom = HD44780::new(DisplayOptionsShared::new(MemoryMap1602::new()).with_bus(lines.bus(en2)), &mut delay)?;
+/// ```
+#[derive(Debug)]
+pub struct SharedLines<RS, D4, D5, D6, D7> {
+	pins: RefCell<SharedLinesPins<RS, D4, D5, D6, D7>>,
+}
+
+impl<RS, D4, D5, D6, D7> SharedLines<RS, D4, D5, D6, D7> {
+	pub const fn new(pins: SharedLinesPins<RS, D4, D5, D6, D7>) -> Self {
+		Self { pins: RefCell::new(pins) }
+	}
+
+	pub fn into_inner(self) -> SharedLinesPins<RS, D4, D5, D6, D7> {
+		self.pins.into_inner()
+	}
+
+	/// Bus of the display whose enable pin is `en`.
+	pub fn bus<EN: OutputPin>(&self, en: EN) -> SharedBus<&Self, EN> {
+		SharedBus { lines: self, en }
+	}
+}
+
+impl<RS, D4, D5, D6, D7> Lock for SharedLines<RS, D4, D5, D6, D7> {
+	type Pins = SharedLinesPins<RS, D4, D5, D6, D7>;
+
+	fn lock<R>(&self, f: impl FnOnce(&mut Self::Pins) -> R) -> R {
+		f(&mut self.pins.borrow_mut())
+	}
+}
+
+/// Lines shared by displays which are written to from several contexts, e.g. a main loop
+/// and interrupt handlers. Each byte is written inside a critical section, so a write to
+/// one display can't be interleaved with a write to another.
+#[cfg(feature = "critical-section")]
+#[derive(Debug)]
+pub struct CriticalSectionLines<RS, D4, D5, D6, D7> {
+	lines: critical_section::Mutex<SharedLines<RS, D4, D5, D6, D7>>,
+}
+
+#[cfg(feature = "critical-section")]
+impl<RS, D4, D5, D6, D7> CriticalSectionLines<RS, D4, D5, D6, D7> {
+	pub const fn new(pins: SharedLinesPins<RS, D4, D5, D6, D7>) -> Self {
+		Self { lines: critical_section::Mutex::new(SharedLines::new(pins)) }
+	}
+
+	pub fn into_inner(self) -> SharedLinesPins<RS, D4, D5, D6, D7> {
+		self.lines.into_inner().into_inner()
+	}
+
+	/// Bus of the display whose enable pin is `en`.
+	pub fn bus<EN: OutputPin>(&self, en: EN) -> SharedBus<&Self, EN> {
+		SharedBus { lines: self, en }
+	}
+}
+
+#[cfg(feature = "critical-section")]
+impl<RS, D4, D5, D6, D7> Lock for CriticalSectionLines<RS, D4, D5, D6, D7> {
+	type Pins = SharedLinesPins<RS, D4, D5, D6, D7>;
+
+	fn lock<R>(&self, f: impl FnOnce(&mut Self::Pins) -> R) -> R {
+		critical_section::with(|cs| self.lines.borrow(cs).lock(f))
+	}
+}
+
+/// 4-bit bus of one display over [`SharedLines`] or [`CriticalSectionLines`], owning only its
+/// enable pin.
+///
+/// The lines are held for a whole byte, with a short enable pulse instead of the
+/// millisecond pulses of [`FourBitBus`](crate::bus::FourBitBus) to keep other displays (and,
+/// with [`CriticalSectionLines`], interrupts) waiting as little as possible.
+///
+/// Only the blocking driver is supported, as the lines can't be held across an `.await`.
+#[derive(Debug)]
+pub struct SharedBus<L, EN> {
+	lines: L,
+	en: EN,
+}
+
+impl<L, EN> SharedBus<L, EN> {
+	/// Returns the enable pin.
+	pub fn destroy(self) -> EN {
+		self.en
+	}
+}
+
+impl<L, RS, D4, D5, D6, D7, EN> DataBus for SharedBus<L, EN>
+where
+	L: Lock<Pins = SharedLinesPins<RS, D4, D5, D6, D7>>,
+	RS: OutputPin,
+	D4: OutputPin,
+	D5: OutputPin,
+	D6: OutputPin,
+	D7: OutputPin,
+	EN: OutputPin,
+{
+	type Error = ErrorKind;
+
+	fn write<D: DelayNs>(&mut self, byte: u8, data: bool, delay: &mut D) -> Result<(), Self::Error> {
+		let en = &mut self.en;
+		self.lines.lock(|pins| pins.write(en, byte, data, delay))?;
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use core::cell::Cell;
+	use core::convert::Infallible;
+
+	use embedded_hal::digital::ErrorType;
+
+	use super::*;
+	use crate::test_util::NoDelay;
+
+	/// Level of a shared line, recorded by the enable pins when they latch.
+	struct Line<'a>(&'a Cell<bool>);
+
+	impl ErrorType for Line<'_> {
+		type Error = Infallible;
+	}
+
+	impl OutputPin for Line<'_> {
+		fn set_low(&mut self) -> core::result::Result<(), Infallible> {
+			self.0.set(false);
+			Ok(())
+		}
+
+		fn set_high(&mut self) -> core::result::Result<(), Infallible> {
+			self.0.set(true);
+			Ok(())
+		}
+	}
+
+	/// Enable pin collecting the nibbles it latched.
+	struct Enable<'a> {
+		lines: &'a [Cell<bool>; 5],
+		nibbles: [u8; 4],
+		len: usize,
+	}
+
+	impl ErrorType for Enable<'_> {
+		type Error = Infallible;
+	}
+
+	impl OutputPin for Enable<'_> {
+		fn set_low(&mut self) -> core::result::Result<(), Infallible> {
+			let nibble = self.lines[1..].iter().rev().fold(0, |nibble, line| nibble << 1 | u8::from(line.get()));
+			self.nibbles[self.len] = nibble;
+			self.len += 1;
+			Ok(())
+		}
+
+		fn set_high(&mut self) -> core::result::Result<(), Infallible> {
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn writes_only_reach_their_display() {
+		let levels: [Cell<bool>; 5] = Default::default();
+		let lines = SharedLines::new(SharedLinesPins {
+			rs: Line(&levels[0]),
+			d4: Line(&levels[1]),
+			d5: Line(&levels[2]),
+			d6: Line(&levels[3]),
+			d7: Line(&levels[4]),
+		});
+
+		let mut first = lines.bus(Enable { lines: &levels, nibbles: [0; 4], len: 0 });
+		let mut second = lines.bus(Enable { lines: &levels, nibbles: [0; 4], len: 0 });
+
+		first.write(0xA5, true, &mut NoDelay).unwrap();
+		second.write(0x3C, false, &mut NoDelay).unwrap();
+		first.write(0x12, true, &mut NoDelay).unwrap();
+
+		let first = first.destroy();
+		assert_eq!(first.nibbles[..first.len], [0xA, 0x5, 0x1, 0x2]);
+		let second = second.destroy();
+		assert_eq!(second.nibbles[..second.len], [0x3, 0xC]);
+	}
+}
@@ -2,10 +2,118 @@ use core::ops::{Deref, DerefMut};
 
 pub trait Charset {
 	fn code_from_utf8(&self, ch: char) -> Option<u8>;
+
+	/// Codes `ch` is displayed with. Unlike [`code_from_utf8`][`Charset::code_from_utf8`]
+	/// this can map characters that take up more than a single cell.
+	fn codes_from_utf8(&self, ch: char) -> Option<CharCodes> {
+		self.code_from_utf8(ch).map(CharCodes::single)
+	}
+
+	/// Character displayed for `code`, if it has a unicode representation.
+	fn char_from_code(&self, _code: u8) -> Option<char> {
+		None
+	}
 }
 
 pub trait CharsetWithFallback {
 	fn code_from_utf8_with_fallback(&self, ch: char) -> u8;
+
+	/// Codes `ch` is displayed with. Unlike
+	/// [`code_from_utf8_with_fallback`][`CharsetWithFallback::code_from_utf8_with_fallback`]
+	/// this can map characters that take up more than a single cell.
+	fn codes_from_utf8_with_fallback(&self, ch: char) -> CharCodes {
+		CharCodes::single(self.code_from_utf8_with_fallback(ch))
+	}
+
+	/// Character displayed for `code`, if it has a unicode representation.
+	/// Same as [`Charset::char_from_code`] for the wrapped charset.
+	fn char_from_code_with_fallback(&self, _code: u8) -> Option<char> {
+		None
+	}
+
+	/// Number of cells `text` takes up on the display.
+	fn text_width(&self, text: &str) -> usize {
+		text.chars().map(|ch| self.codes_from_utf8_with_fallback(ch).len()).sum()
+	}
+
+	/// Called before `ch` is written to the display. Returns a glyph that has to be
+	/// stored in CGRAM first, if the character is displayed using a custom character.
+	fn glyph_for(&mut self, _ch: char) -> Option<GlyphUpload> {
+		None
+	}
+
+	/// Whether [`glyph_for`][`CharsetWithFallback::glyph_for`] would return a glyph for `ch`,
+	/// without storing it in a slot.
+	fn needs_glyph(&self, _ch: char) -> bool {
+		false
+	}
+
+	/// Called after the display has been cleared.
+	fn screen_cleared(&mut self) {}
+
+	/// Called before glyphs the charset didn't ask for are uploaded into the CGRAM codes in the
+	/// bit mask `codes`.
+	fn cgram_overwritten(&mut self, _codes: u8) {}
+}
+
+/// Display codes of a single character, taking up one cell per code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct CharCodes {
+	codes: [u8; CharCodes::CAPACITY],
+	len: u8,
+}
+
+impl CharCodes {
+	/// Maximum number of cells a character can be displayed with.
+	pub const CAPACITY: usize = 4;
+
+	pub const fn single(code: u8) -> Self {
+		Self { codes: [code, 0, 0, 0], len: 1 }
+	}
+
+	/// Panics if `codes` is empty or longer than [`CharCodes::CAPACITY`].
+	pub const fn from_slice(codes: &[u8]) -> Self {
+		assert!(!codes.is_empty() && codes.len() <= Self::CAPACITY, "a character takes 1 to 4 cells");
+
+		let mut result = Self { codes: [0; Self::CAPACITY], len: codes.len() as u8 };
+		let mut i = 0;
+		while i < codes.len() {
+			result.codes[i] = codes[i];
+			i += 1;
+		}
+		result
+	}
+
+	pub fn as_slice(&self) -> &[u8] {
+		&self.codes[..self.len as usize]
+	}
+
+	/// Number of cells the character takes up.
+	#[allow(clippy::len_without_is_empty)]
+	pub const fn len(&self) -> usize {
+		self.len as usize
+	}
+}
+
+impl Deref for CharCodes {
+	type Target = [u8];
+
+	fn deref(&self) -> &Self::Target {
+		self.as_slice()
+	}
+}
+
+/// A 5x8 glyph that has to be stored in CGRAM before a character can be displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct GlyphUpload {
+	/// CGRAM slot (`0..=7`) the glyph is stored in.
+	pub slot: u8,
+	/// Rows of the glyph, top to bottom.
+	pub pattern: [u8; 8],
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -44,32 +152,598 @@ impl<C: Charset, const FB: u8> CharsetWithFallback for Fallback<C, FB> {
 	fn code_from_utf8_with_fallback(&self, ch: char) -> u8 {
 		self.0.code_from_utf8(ch).unwrap_or(FB)
 	}
+
+	fn codes_from_utf8_with_fallback(&self, ch: char) -> CharCodes {
+		self.0.codes_from_utf8(ch).unwrap_or(CharCodes::single(FB))
+	}
+
+	fn char_from_code_with_fallback(&self, code: u8) -> Option<char> {
+		self.0.char_from_code(code)
+	}
+}
+
+/// Fallback that replaces characters missing from the charset with a similar looking
+/// character before resorting to the constant `FB`.
+///
+/// Accented latin letters lose their diacritics (`é` → `e`, `Ł` → `L`) and typographic
+/// punctuation is replaced by its ASCII counterpart (`“` → `"`, `–` → `-`), so text
+/// stays readable on ROMs without these characters. When written with
+/// [`codes_from_utf8_with_fallback`][`CharsetWithFallback::codes_from_utf8_with_fallback`],
+/// some characters are spelled out using several cells (`€` → `EUR`, `…` → `...`).
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct TransliteratingFallback<C: Charset, const FB: u8 = b'?'>(C);
+
+impl<C: Charset, const FB: u8> TransliteratingFallback<C, FB> {
+	pub const fn new(c: C) -> Self {
+		Self(c)
+	}
+
+	pub fn into_inner(self) -> C {
+		self.0
+	}
+}
+
+impl<C: Charset, const FB: u8> Deref for TransliteratingFallback<C, FB> {
+	type Target = C;
+
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl<C: Charset, const FB: u8> DerefMut for TransliteratingFallback<C, FB> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.0
+	}
+}
+
+impl<C: Charset, const FB: u8> CharsetWithFallback for TransliteratingFallback<C, FB> {
+	fn code_from_utf8_with_fallback(&self, ch: char) -> u8 {
+		self.0
+			.code_from_utf8(ch)
+			.or_else(|| transliterate(ch).and_then(|ascii| self.0.code_from_utf8(ascii.chars().next()?)))
+			.unwrap_or(FB)
+	}
+
+	fn codes_from_utf8_with_fallback(&self, ch: char) -> CharCodes {
+		self.0
+			.codes_from_utf8(ch)
+			.or_else(|| {
+				let ascii = transliterate(ch)?;
+				let mut codes = [0; CharCodes::CAPACITY];
+				for (code, ch) in codes.iter_mut().zip(ascii.chars()) {
+					*code = self.0.code_from_utf8(ch)?;
+				}
+				Some(CharCodes::from_slice(&codes[..ascii.len()]))
+			})
+			.unwrap_or(CharCodes::single(FB))
+	}
+
+	fn char_from_code_with_fallback(&self, code: u8) -> Option<char> {
+		self.0.char_from_code(code)
+	}
+}
+
+/// ASCII approximation of a character, using at most [`CharCodes::CAPACITY`] characters.
+/// When only a single cell is available, the first character is used.
+fn transliterate(ch: char) -> Option<&'static str> {
+	let ascii = match ch {
+		// Latin-1 Supplement and Latin Extended-A
+		'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+		'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+		'Æ' => "AE",
+		'æ' => "ae",
+		'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => "C",
+		'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+		'Ð' | 'Ď' | 'Đ' => "D",
+		'ð' | 'ď' | 'đ' => "d",
+		'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => "E",
+		'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+		'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => "G",
+		'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+		'Ĥ' | 'Ħ' => "H",
+		'ĥ' | 'ħ' => "h",
+		'Ì' | 'Í' | 'Î' | 'Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => "I",
+		'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+		'Ĵ' => "J",
+		'ĵ' => "j",
+		'Ķ' => "K",
+		'ķ' | 'ĸ' => "k",
+		'Ĺ' | 'Ļ' | 'Ľ' | 'Ŀ' | 'Ł' => "L",
+		'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+		'Ñ' | 'Ń' | 'Ņ' | 'Ň' | 'Ŋ' => "N",
+		'ñ' | 'ń' | 'ņ' | 'ň' | 'ŉ' | 'ŋ' => "n",
+		'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => "O",
+		'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+		'Œ' => "OE",
+		'œ' => "oe",
+		'Ŕ' | 'Ŗ' | 'Ř' => "R",
+		'ŕ' | 'ŗ' | 'ř' => "r",
+		'Ś' | 'Ŝ' | 'Ş' | 'Š' => "S",
+		'ś' | 'ŝ' | 'ş' | 'š' => "s",
+		'ß' => "ss",
+		'Ţ' | 'Ť' | 'Ŧ' => "T",
+		'ţ' | 'ť' | 'ŧ' => "t",
+		'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => "U",
+		'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+		'Ŵ' => "W",
+		'ŵ' => "w",
+		'Ý' | 'Ŷ' | 'Ÿ' => "Y",
+		'ý' | 'ÿ' | 'ŷ' => "y",
+		'Ź' | 'Ż' | 'Ž' => "Z",
+		'ź' | 'ż' | 'ž' => "z",
+		// Punctuation
+		'\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{2032}' | '\u{00B4}' => "'", // ‘ ’ ‚ ‛ ′ ´
+		'\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{2033}' | '\u{00AB}' | '\u{00BB}' => "\"", // “ ” „ ‟ ″ « »
+		'\u{2039}' => "<",                                                                  // ‹
+		'\u{203A}' => ">",                                                                  // ›
+		'\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2014}' | '\u{2015}' | '\u{2212}' => "-", // ‐ ‑ ‒ – — ― −
+		'\u{2026}' => "...",                                                                // …
+		'\u{00B7}' | '\u{2022}' => ".",                                                     // · •
+		'\u{20AC}' => "EUR",                                                                // €
+		'\u{00D7}' => "x",                                                                  // ×
+		'\u{00A0}' | '\u{2002}'..='\u{200A}' | '\u{202F}' => " ",                           // No-break and narrow spaces
+		_ => return None,
+	};
+
+	Some(ascii)
 }
 
 impl<T: CharsetWithFallback> Charset for T {
 	fn code_from_utf8(&self, ch: char) -> Option<u8> {
 		Some(self.code_from_utf8_with_fallback(ch))
 	}
+
+	fn codes_from_utf8(&self, ch: char) -> Option<CharCodes> {
+		Some(self.codes_from_utf8_with_fallback(ch))
+	}
+
+	fn char_from_code(&self, code: u8) -> Option<char> {
+		self.char_from_code_with_fallback(code)
+	}
 }
 
-/// Symbols common to both A00 and A02 Charset.
-#[derive(Debug, Clone, Copy)]
+/// Charset that loads glyphs for characters missing from the ROM into CGRAM on demand.
+///
+/// Characters found in the glyph table but not in the ROM of the wrapped charset are mapped to
+/// the CGRAM codes `0..=7`, everything else is looked up in the wrapped charset. When all eight slots are taken, the least
+/// recently used glyph that is not on the screen since the last [clear][`crate::HD44780::clear`]
+/// gets replaced. If every slot is in use on the current screen, the character is looked
+/// up in the wrapped charset instead, as replacing a glyph would change text already shown.
+///
+/// Slots written with [`upload_glyph`](crate::HD44780::upload_glyph) or
+/// [`activate_glyphs`](crate::HD44780::activate_glyphs) are reserved for those glyphs, the
+/// charset stops using them until [`reset`](DynamicCharset::reset).
+///
+/// ```rust,ignore
+/// const GLYPHS: &[(char, [u8; 8])] = &[
+///     ('ł', [0b01100, 0b00100, 0b00110, 0b00100, 0b01100, 0b00100, 0b01110, 0b00000]),
+///     ('ż', [0b00100, 0b00000, 0b11111, 0b00010, 0b00100, 0b01000, 0b11111, 0b00000]),
+/// ];
+///
+/// let options = DisplayOptionsI2C::new(MemoryMap1602::new())
+///     .with_charset(DynamicCharset::new(CharsetA00::QUESTION_FALLBACK, GLYPHS));
+/// ```
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
-#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
-pub struct CharsetUniversal;
+pub struct DynamicCharset<'a, C: CharsetWithFallback> {
+	charset: C,
+	glyphs: &'a [(char, [u8; 8])],
+	/// Character currently stored in each CGRAM slot.
+	slots: [Option<char>; 8],
+	/// Slot indices, most recently used first.
+	recency: [u8; 8],
+	/// Bit mask of the slots written since the screen was last cleared.
+	on_screen: u8,
+	/// Bit mask of the slots holding glyphs uploaded outside the charset.
+	reserved: u8,
+}
 
-impl CharsetUniversal {
-	pub const EMPTY_FALLBACK: EmptyFallback<Self> = Fallback(Self);
-	pub const QUESTION_FALLBACK: QuestionFallback<Self> = Fallback(Self);
+impl<'a, C: CharsetWithFallback> DynamicCharset<'a, C> {
+	pub const fn new(charset: C, glyphs: &'a [(char, [u8; 8])]) -> Self {
+		Self { charset, glyphs, slots: [None; 8], recency: [0, 1, 2, 3, 4, 5, 6, 7], on_screen: 0, reserved: 0 }
+	}
+
+	pub fn into_inner(self) -> C {
+		self.charset
+	}
+
+	/// Forget which glyphs are stored in CGRAM, e.g. after the display lost power, and use the
+	/// reserved slots again.
+	pub fn reset(&mut self) {
+		self.slots = [None; 8];
+		self.on_screen = 0;
+		self.reserved = 0;
+	}
+
+	/// Pattern of `ch` if it has to be loaded into CGRAM, as the ROM doesn't have it.
+	fn glyph(&self, ch: char) -> Option<[u8; 8]> {
+		let &(_, pattern) = self.glyphs.iter().find(|(glyph, _)| *glyph == ch)?;
+		let in_rom =
+			self.charset.char_from_code_with_fallback(self.charset.code_from_utf8_with_fallback(ch)) == Some(ch);
+
+		(!in_rom).then_some(pattern)
+	}
+
+	fn slot_of(&self, ch: char) -> Option<u8> {
+		self.slots.iter().position(|&slot| slot == Some(ch)).map(|slot| slot as u8)
+	}
+
+	fn touch(&mut self, slot: u8) {
+		let index = self.recency.iter().position(|&s| s == slot).unwrap_or(self.recency.len() - 1);
+		self.recency.copy_within(0..index, 1);
+		self.recency[0] = slot;
+		self.on_screen |= 1 << slot;
+	}
+
+	fn free_slot(&self) -> Option<u8> {
+		let usable = |slot: u8| self.reserved & (1 << slot) == 0;
+		if let Some(slot) = (0..8).find(|&slot| usable(slot) && self.slots[slot as usize].is_none()) {
+			return Some(slot);
+		}
+
+		self.recency.iter().rev().copied().find(|&slot| usable(slot) && self.on_screen & (1 << slot) == 0)
+	}
 }
 
-impl Charset for CharsetUniversal {
-	fn code_from_utf8(&self, ch: char) -> Option<u8> {
-		match ch {
-			'\\' | '\x10'..='\x1f' => None,
-			'\x00'..='\x7d' => Some(ch as u8),
-			_ => None,
+// ufmt can't format `char`, so the glyphs are shown as code points
+#[cfg(feature = "ufmt")]
+impl<C: CharsetWithFallback + ufmt::uDebug> ufmt::uDebug for DynamicCharset<'_, C> {
+	fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> core::result::Result<(), W::Error>
+	where
+		W: ufmt::uWrite + ?Sized,
+	{
+		let slots = self.slots.map(|slot| slot.map(u32::from));
+
+		f.debug_struct("DynamicCharset")?
+			.field("charset", &self.charset)?
+			.field("glyphs", &self.glyphs.len())?
+			.field("slots", &slots)?
+			.field("recency", &self.recency)?
+			.field("on_screen", &self.on_screen)?
+			.field("reserved", &self.reserved)?
+			.finish()
+	}
+}
+
+impl<C: CharsetWithFallback> Deref for DynamicCharset<'_, C> {
+	type Target = C;
+
+	fn deref(&self) -> &Self::Target {
+		&self.charset
+	}
+}
+
+impl<C: CharsetWithFallback> CharsetWithFallback for DynamicCharset<'_, C> {
+	fn code_from_utf8_with_fallback(&self, ch: char) -> u8 {
+		match self.slot_of(ch) {
+			Some(slot) => slot,
+			None => self.charset.code_from_utf8_with_fallback(ch),
+		}
+	}
+
+	fn codes_from_utf8_with_fallback(&self, ch: char) -> CharCodes {
+		match self.slot_of(ch) {
+			Some(slot) => CharCodes::single(slot),
+			None => self.charset.codes_from_utf8_with_fallback(ch),
+		}
+	}
+
+	fn char_from_code_with_fallback(&self, code: u8) -> Option<char> {
+		match self.slots.get(code as usize) {
+			Some(&slot) => slot,
+			None => self.charset.char_from_code_with_fallback(code),
+		}
+	}
+
+	fn text_width(&self, text: &str) -> usize {
+		text.chars()
+			.map(|ch| match self.glyph(ch) {
+				Some(_) => 1,
+				None => self.charset.codes_from_utf8_with_fallback(ch).len(),
+			})
+			.sum()
+	}
+
+	fn needs_glyph(&self, ch: char) -> bool {
+		self.slot_of(ch).is_none() && self.glyph(ch).is_some() && self.free_slot().is_some()
+	}
+
+	fn glyph_for(&mut self, ch: char) -> Option<GlyphUpload> {
+		if let Some(slot) = self.slot_of(ch) {
+			self.touch(slot);
+			return None;
+		}
+
+		let pattern = self.glyph(ch)?;
+		let slot = self.free_slot()?;
+
+		self.slots[slot as usize] = Some(ch);
+		self.touch(slot);
+
+		Some(GlyphUpload { slot, pattern })
+	}
+
+	fn screen_cleared(&mut self) {
+		self.on_screen = 0;
+	}
+
+	fn cgram_overwritten(&mut self, codes: u8) {
+		for (_, glyph) in self.slots.iter_mut().enumerate().filter(|&(slot, _)| codes & (1 << slot) != 0) {
+			*glyph = None;
 		}
+		self.on_screen &= !codes;
+		self.reserved |= codes;
+	}
+}
+
+/// Characters of a ROM indexed by their code, built from ranges of codes displaying the
+/// character with the same code point and a list of `(code, character)` entries. Only used
+/// by [`CharsetA00`], whose katakana lookups don't fit [`define_charset!`](crate::define_charset).
+///
+/// Every character may only appear once, so each character maps to exactly one code.
+const fn rom_table(identity: &[(u8, u8)], entries: &[(u8, char)]) -> [Option<char>; 256] {
+	let mut rom = [None; 256];
+
+	let mut i = 0;
+	while i < identity.len() {
+		let (start, end) = identity[i];
+		let mut code = start as u32;
+		while code <= end as u32 {
+			rom[code as usize] = char::from_u32(code);
+			code += 1;
+		}
+		i += 1;
+	}
+
+	let mut i = 0;
+	while i < entries.len() {
+		let (code, ch) = entries[i];
+		rom[code as usize] = Some(ch);
+		i += 1;
+	}
+
+	assert_unique(&rom);
+	rom
+}
+
+/// Every character may only appear once in a ROM, so each character maps to exactly one code.
+const fn assert_unique(rom: &[Option<char>; 256]) {
+	let mut code = 0;
+	while code < rom.len() {
+		let mut other = code + 1;
+		while other < rom.len() {
+			if let (Some(a), Some(b)) = (rom[code], rom[other]) {
+				assert!(a != b, "character is mapped to several codes");
+			}
+			other += 1;
+		}
+		code += 1;
+	}
+}
+
+/// Column `0x0` of tables for [`define_charset!`](crate::define_charset): the CGRAM characters, at their own code
+/// points.
+pub const CGRAM_COLUMN: &str = "\u{0}\u{1}\u{2}\u{3}\u{4}\u{5}\u{6}\u{7}\u{8}\u{9}\u{A}\u{B}\u{C}\u{D}\u{E}\u{F}";
+
+/// Column of a table for [`define_charset!`](crate::define_charset) without any characters.
+pub const EMPTY_COLUMN: &str = "����������������";
+
+/// Marks a code without a Unicode character in tables for [`define_charset!`](crate::define_charset).
+pub const NO_CHARACTER: char = '\u{FFFD}';
+
+/// Characters of a ROM from the columns of its datasheet table, see [`define_charset!`](crate::define_charset).
+#[doc(hidden)]
+pub const fn rom_from_columns(columns: &[&str; 16]) -> [Option<char>; 256] {
+	let mut rom = [None; 256];
+
+	let mut column = 0;
+	while column < columns.len() {
+		let bytes = columns[column].as_bytes();
+		let mut i = 0;
+		let mut row = 0;
+		while i < bytes.len() {
+			assert!(row < 16, "column with more than 16 characters");
+
+			// Decode one UTF-8 sequence
+			let (len, mut code_point) = match bytes[i] {
+				byte if byte < 0x80 => (1, byte as u32),
+				byte if byte >> 5 == 0b110 => (2, (byte & 0b1_1111) as u32),
+				byte if byte >> 4 == 0b1110 => (3, (byte & 0b1111) as u32),
+				byte => (4, (byte & 0b111) as u32),
+			};
+			let mut j = 1;
+			while j < len {
+				code_point = code_point << 6 | (bytes[i + j] & 0b11_1111) as u32;
+				j += 1;
+			}
+			i += len;
+
+			let ch = match char::from_u32(code_point) {
+				Some(ch) => ch,
+				None => panic!("invalid character"),
+			};
+			if ch != NO_CHARACTER {
+				rom[column * 16 + row] = Some(ch);
+			}
+			row += 1;
+		}
+		assert!(row == 16, "column with less than 16 characters");
+
+		column += 1;
+	}
+
+	assert_unique(&rom);
+	rom
+}
+
+/// Characters of a ROM sorted for looking up their codes, plus characters displayed with the
+/// code of a look-alike. Built by [`define_charset!`](crate::define_charset).
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct ReverseTable {
+	entries: [(char, u8); 256],
+	len: usize,
+	aliases: &'static [(char, u8)],
+}
+
+impl ReverseTable {
+	pub const fn new(rom: &[Option<char>; 256], aliases: &'static [(char, u8)]) -> Self {
+		let mut entries = [('\0', 0); 256];
+		let mut len = 0;
+
+		// Insertion sort by character
+		let mut code = 0;
+		while code < rom.len() {
+			if let Some(ch) = rom[code] {
+				let mut i = len;
+				while i > 0 && entries[i - 1].0 as u32 > ch as u32 {
+					entries[i] = entries[i - 1];
+					i -= 1;
+				}
+				entries[i] = (ch, code as u8);
+				len += 1;
+			}
+			code += 1;
+		}
+
+		let mut i = 0;
+		while i < aliases.len() {
+			let (ch, code) = aliases[i];
+			assert!(rom[code as usize].is_some(), "alias of a code without a character");
+
+			let mut code = 0;
+			while code < rom.len() {
+				if let Some(rom_ch) = rom[code] {
+					assert!(rom_ch != ch, "alias of a character which is in the ROM");
+				}
+				code += 1;
+			}
+			i += 1;
+		}
+
+		Self { entries, len, aliases }
+	}
+
+	pub fn code(&self, ch: char) -> Option<u8> {
+		let entries = &self.entries[..self.len];
+		match entries.binary_search_by_key(&ch, |&(entry, _)| entry) {
+			Ok(index) => Some(entries[index].1),
+			Err(_) => self.aliases.iter().find(|&&(alias, _)| alias == ch).map(|&(_, code)| code),
+		}
+	}
+}
+
+/// Checks that every code maps back to itself and every mapped character ends up at a code
+/// that displays the canonical character of that code. Used by the tests of
+/// [`define_charset!`](crate::define_charset).
+#[doc(hidden)]
+pub fn assert_round_trip(charset: &impl Charset) {
+	for code in 0..=255 {
+		if let Some(ch) = charset.char_from_code(code) {
+			assert_eq!(charset.code_from_utf8(ch), Some(code), "{ch:?} is not mapped to {code:#04X}");
+		}
+	}
+
+	for ch in (0..0x20000).filter_map(char::from_u32) {
+		if let Some(code) = charset.code_from_utf8(ch) {
+			let Some(rom_ch) = charset.char_from_code(code) else {
+				panic!("{ch:?} is mapped to {code:#04X} without a reverse mapping");
+			};
+			assert_eq!(charset.code_from_utf8(rom_ch), Some(code));
+		}
+	}
+}
+
+/// Defines a [`Charset`] from the code table in a datasheet, given as its 16 columns. Each
+/// column holds the characters of `0xN0..=0xNF` for its `N`, with [`NO_CHARACTER`] (`�`) for
+/// codes without a Unicode character. Characters which look like one in the ROM can be added
+/// as `aliases`.
+///
+/// The table is checked at compile time: each column needs 16 characters, and a character
+/// may only appear once. With `tests`, a module of that name tests the round trip between
+/// characters and codes.
+///
+/// ```rust,ignore
+/// use hd44780_driver::charset::{CGRAM_COLUMN, EMPTY_COLUMN};
+///
+/// hd44780_driver::define_charset! {
+///     /// Vendor ROM of our display.
+///     pub struct CharsetVendor {
+///         columns: [
+///             CGRAM_COLUMN, EMPTY_COLUMN,
+///             " !\"#$%&'()*+,-./", "0123456789:;<=>?", "@ABCDEFGHIJKLMNO", "PQRSTUVWXYZ[\\]^_",
+///             "`abcdefghijklmno", "pqrstuvwxyz{|}~�",
+///             EMPTY_COLUMN, EMPTY_COLUMN, "БГЁЖЗИЙЛПУФЧШЪЫЭ", EMPTY_COLUMN,
+///             EMPTY_COLUMN, EMPTY_COLUMN, EMPTY_COLUMN, EMPTY_COLUMN,
+///         ],
+///         aliases: { 'А' => 0x41, 'В' => 0x42 },
+///         tests: charset_vendor_tests,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! define_charset {
+	(
+		$(#[$meta:meta])*
+		$vis:vis struct $name:ident {
+			columns: [$($column:expr),* $(,)?],
+			$(aliases: { $($alias:literal => $code:literal),* $(,)? },)?
+			$(tests: $tests:ident,)?
+		}
+	) => {
+		$(#[$meta])*
+		#[derive(Debug, Clone, Copy)]
+		$vis struct $name;
+
+		impl $name {
+			pub const EMPTY_FALLBACK: $crate::charset::EmptyFallback<Self> = $crate::charset::Fallback::new(Self);
+			pub const QUESTION_FALLBACK: $crate::charset::QuestionFallback<Self> = $crate::charset::Fallback::new(Self);
+			pub const TRANSLITERATING_FALLBACK: $crate::charset::TransliteratingFallback<Self> =
+				$crate::charset::TransliteratingFallback::new(Self);
+
+			const ROM: [Option<char>; 256] = $crate::charset::rom_from_columns(&[$($column),*]);
+			const REVERSE: $crate::charset::ReverseTable =
+				$crate::charset::ReverseTable::new(&Self::ROM, &[$($(($alias, $code)),*)?]);
+		}
+
+		impl $crate::charset::Charset for $name {
+			fn code_from_utf8(&self, ch: char) -> Option<u8> {
+				Self::REVERSE.code(ch)
+			}
+
+			fn char_from_code(&self, code: u8) -> Option<char> {
+				Self::ROM[code as usize]
+			}
+		}
+
+		$(
+			#[cfg(test)]
+			mod $tests {
+				#[test]
+				fn round_trip() {
+					$crate::charset::assert_round_trip(&super::$name);
+				}
+			}
+		)?
+	};
+}
+
+define_charset! {
+	/// Symbols common to both A00 and A02 Charset.
+	#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+	#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+	pub struct CharsetUniversal {
+		columns: [
+			CGRAM_COLUMN, EMPTY_COLUMN,
+			" !\"#$%&'()*+,-./", "0123456789:;<=>?", "@ABCDEFGHIJKLMNO", "PQRSTUVWXYZ[�]^_",
+			"`abcdefghijklmno", "pqrstuvwxyz{|}��",
+			EMPTY_COLUMN, EMPTY_COLUMN, EMPTY_COLUMN, EMPTY_COLUMN,
+			EMPTY_COLUMN, EMPTY_COLUMN, EMPTY_COLUMN, EMPTY_COLUMN,
+		],
+		tests: charset_universal_tests,
 	}
 }
 
@@ -84,220 +758,506 @@ pub struct CharsetA00;
 impl CharsetA00 {
 	pub const EMPTY_FALLBACK: EmptyFallback<Self> = Fallback(Self);
 	pub const QUESTION_FALLBACK: QuestionFallback<Self> = Fallback(Self);
+	pub const TRANSLITERATING_FALLBACK: TransliteratingFallback<Self> = TransliteratingFallback(Self);
+
+	#[rustfmt::skip]
+	const ROM: [Option<char>; 256] = rom_table(&[(0x00, 0x0F), (0x20, 0x5B), (0x5D, 0x7D)], &[
+		// Lower (ASCII)
+		(0x5C, '\u{00A5}'), // ¥
+		(0x7E, '\u{2192}'), // →
+		(0x7F, '\u{2190}'), // ←
+		// Upper (Japanese)
+		// A0: Empty
+		(0xA1, '\u{3002}'), // 。 Kuten
+		(0xA2, '\u{300c}'), // 「 Quotation Marks
+		(0xA3, '\u{300d}'), // 」
+		(0xA4, '\u{3001}'), // 、 Tōten
+		(0xA5, '\u{30fb}'), // ・ Nakaguro
+		(0xA6, '\u{30f2}'), // ヲ Wo
+		(0xA7, '\u{30a1}'), // ァ A (small)
+		(0xA8, '\u{30a3}'), // ィ I (small)
+		(0xA9, '\u{30a5}'), // ゥ U (small)
+		(0xAA, '\u{30a7}'), // ェ E (small)
+		(0xAB, '\u{30a9}'), // ォ O (small)
+		(0xAC, '\u{30e3}'), // ャ Ya (small)
+		(0xAD, '\u{30e5}'), // ュ Yu (small)
+		(0xAE, '\u{30e7}'), // ョ Yo (small)
+		(0xAF, '\u{30c3}'), // ッ Tu (small)
+		(0xB0, '\u{30fc}'), // ー
+		(0xB1, '\u{30a2}'), // ア A
+		(0xB2, '\u{30a4}'), // イ I
+		(0xB3, '\u{30a6}'), // ウ U
+		(0xB4, '\u{30a8}'), // エ E
+		(0xB5, '\u{30aa}'), // オ O
+		(0xB6, '\u{30ab}'), // カ Ka
+		(0xB7, '\u{30ad}'), // キ Ki
+		(0xB8, '\u{30af}'), // ク Ku
+		(0xB9, '\u{30b1}'), // ケ Ke
+		(0xBA, '\u{30b3}'), // コ Ko
+		(0xBB, '\u{30b5}'), // サ Sa
+		(0xBC, '\u{30b7}'), // シ Si
+		(0xBD, '\u{30b9}'), // ス Su
+		(0xBE, '\u{30bb}'), // セ Se
+		(0xBF, '\u{30bd}'), // ソ So
+		(0xC0, '\u{30bf}'), // タ Ta
+		(0xC1, '\u{30c1}'), // チ Ti
+		(0xC2, '\u{30c4}'), // ツ Tu
+		(0xC3, '\u{30c6}'), // テ Te
+		(0xC4, '\u{30c8}'), // ト To
+		(0xC5, '\u{30ca}'), // ナ Na
+		(0xC6, '\u{30cb}'), // ニ Ni
+		(0xC7, '\u{30cc}'), // ヌ Nu
+		(0xC8, '\u{30cd}'), // ネ Ne
+		(0xC9, '\u{30ce}'), // ノ No
+		(0xCA, '\u{30cf}'), // ハ Ha
+		(0xCB, '\u{30d2}'), // ヒ Hi
+		(0xCC, '\u{30d5}'), // フ Hu
+		(0xCD, '\u{30d8}'), // ヘ He
+		(0xCE, '\u{30db}'), // ホ Ho
+		(0xCF, '\u{30de}'), // マ Ma
+		(0xD0, '\u{30df}'), // ミ Mi
+		(0xD1, '\u{30e0}'), // ム Mu
+		(0xD2, '\u{30e1}'), // メ Me
+		(0xD3, '\u{30e2}'), // モ Mo
+		(0xD4, '\u{30e4}'), // ヤ Ya
+		(0xD5, '\u{30e6}'), // ユ Yu
+		(0xD6, '\u{30e8}'), // ヨ Yo
+		(0xD7, '\u{30e9}'), // ラ Ra
+		(0xD8, '\u{30ea}'), // リ Ri
+		(0xD9, '\u{30eb}'), // ル Ru
+		(0xDA, '\u{30ec}'), // レ Re
+		(0xDB, '\u{30ed}'), // ロ Ro
+		(0xDC, '\u{30ef}'), // ワ Wa
+		(0xDD, '\u{30f3}'), // ン N
+		(0xDE, '\u{309B}'), // ゛ Dakuten
+		(0xDF, '\u{309C}'), // ゜ Handakuten
+		// Upper (5x10 Extra)
+		(0xE0, '\u{03b1}'), // α Small Alpha
+		(0xE1, '\u{00e4}'), // ä Small A with Diaeresis
+		(0xE2, '\u{03b2}'), // β Small Beta
+		(0xE3, '\u{03b5}'), // ε Small Epsilon
+		(0xE4, '\u{00b5}'), // µ Small Mu/Micro
+		(0xE5, '\u{03c3}'), // σ Small Sigma
+		(0xE6, '\u{03c1}'), // ρ Small Rho
+		// E7: Small G (tall version)
+		(0xE8, '\u{221a}'), // √ Square Root
+		// E9: Superscript -1 (has no unicode character)
+		// EA: Small J (tall version)
+		// EB: Superscript Small X (has no unicode character)
+		(0xEC, '\u{00A2}'), // ¢ Cent
+		(0xED, '\u{2c60}'), // Ⱡ Capital L with Double Bar
+		(0xEE, '\u{00f1}'), // ñ Small N with Tilde
+		(0xEF, '\u{00f6}'), // ö Small O with Diaeresis
+		// F0: Small P (tall version)
+		// F1: Small Q (tall version)
+		(0xF2, '\u{03b8}'), // θ Small Theta
+		(0xF3, '\u{221e}'), // ∞ Infinity
+		(0xF4, '\u{03a9}'), // Ω Capital Omega
+		(0xF5, '\u{00fc}'), // ü Small U with Diaeresis
+		(0xF6, '\u{03a3}'), // Σ Capital Sigma
+		(0xF7, '\u{03c0}'), // π Small Pi
+		// F8: Small X-Bar (has no unicode character)
+		// F9: Small Y (tall version)
+		(0xFA, '\u{5343}'), // 千 Sen (1,000)
+		(0xFB, '\u{4E07}'), // 万 Man (10,000)
+		(0xFC, '\u{5186}'), // 円 Yen/¥
+		(0xFD, '\u{00f7}'), // ÷ Division
+		// FE: Empty
+		(0xFF, '\u{2588}'), // █ Full Block
+	]);
+	const REVERSE: ReverseTable = ReverseTable::new(&Self::ROM, &[]);
+
+	/// Replaces characters that look the same on this ROM: hiragana are displayed as
+	/// katakana and full-width ASCII variants as regular ASCII.
+	fn normalize(ch: char) -> char {
+		let normalized = match ch {
+			'\u{3041}'..='\u{3096}' => ch as u32 + 0x60,   // Hiragana
+			'\u{FF01}'..='\u{FF5E}' => ch as u32 - 0xFEE0, // Full-width ASCII
+			_ => return ch,
+		};
+
+		char::from_u32(normalized).unwrap_or(ch)
+	}
 }
 
 impl Charset for CharsetA00 {
 	fn code_from_utf8(&self, ch: char) -> Option<u8> {
-		match ch {
-			// Lower (ASCII)
-			'¥' => Some(0x5C),
-			'\\' => None,
-			'\x10'..='\x1f' => None,
-			'\x00'..='\x7d' => Some(ch as u8),
-			'\u{2192}' => Some(0x7E), // →
-			'\u{2190}' => Some(0x7F), // ←
-			'\u{ff01}' => Some(b'!'), // ！ full-width exclamation mark
-			'\u{ff1f}' => Some(b'?'), // ！ full-width exclamation mark
-			// Upper (Japanese)
-			// A0: Empty
-			'\u{3002}' => Some(0xA1),              // 。 Kuten
-			'\u{300c}' => Some(0xA2),              // 「 Quotation Marks
-			'\u{300d}' => Some(0xA3),              // 」
-			'\u{3001}' => Some(0xA4),              // 、 Tōten
-			'\u{30fb}' => Some(0xA5),              // ・ Nakaguro
-			'\u{30f2}' => Some(0xA6),              // ヲ Wo
-			'\u{30a1}' => Some(0xA7),              // ァ A (small)
-			'\u{30a3}' => Some(0xA8),              // ィ I (small)
-			'\u{30a5}' => Some(0xA9),              // ゥ U (small)
-			'\u{30a7}' => Some(0xAA),              // ェ E (small)
-			'\u{30a9}' => Some(0xAB),              // ォ O (small)
-			'\u{30e3}' => Some(0xAC),              // ャ Ya (small)
-			'\u{30e5}' => Some(0xAD),              // ュ Yu (small)
-			'\u{30e7}' => Some(0xAE),              // ョ Yo (small)
-			'\u{30c3}' => Some(0xAF),              // ッ Tu (small)
-			'\u{30fc}' => Some(0xB0),              // ー
-			'\u{30a2}' => Some(0xB1),              // ア A
-			'\u{30a4}' => Some(0xB2),              // イ I
-			'\u{30a6}' => Some(0xB3),              // ウ U
-			'\u{30a8}' => Some(0xB4),              // エ E
-			'\u{30aa}' => Some(0xB5),              // オ O
-			'\u{30ab}' => Some(0xB6),              // カ Ka
-			'\u{30ad}' => Some(0xB7),              // キ Ki
-			'\u{30af}' => Some(0xB8),              // ク Ku
-			'\u{30b1}' => Some(0xB9),              // ケ Ke
-			'\u{30b3}' => Some(0xBA),              // コ Ko
-			'\u{30b5}' => Some(0xBB),              // サ Sa
-			'\u{30b7}' => Some(0xBC),              // シ Si
-			'\u{30b9}' => Some(0xBD),              // ス Su
-			'\u{30bb}' => Some(0xBE),              // セ Se
-			'\u{30bd}' => Some(0xBF),              // ソ So
-			'\u{30bf}' => Some(0xC0),              // タ Ta
-			'\u{30c1}' => Some(0xC1),              // チ Ti
-			'\u{30c4}' => Some(0xC2),              // ツ Tu
-			'\u{30c6}' => Some(0xC3),              // テ Te
-			'\u{30c8}' => Some(0xC4),              // ト To
-			'\u{30ca}' => Some(0xC5),              // ナ Na
-			'\u{30cb}' => Some(0xC6),              // ニ Ni
-			'\u{30cc}' => Some(0xC7),              // ヌ Nu
-			'\u{30cd}' => Some(0xC8),              // ネ Ne
-			'\u{30ce}' => Some(0xC9),              // ノ No
-			'\u{30cf}' => Some(0xCA),              // ハ Ha
-			'\u{30d2}' => Some(0xCB),              // ヒ Hi
-			'\u{30d5}' => Some(0xCC),              // フ Hu
-			'\u{30d8}' => Some(0xCD),              // ヘ He
-			'\u{30db}' => Some(0xCE),              // ホ Ho
-			'\u{30de}' => Some(0xCF),              // マ Ma
-			'\u{30df}' => Some(0xD0),              // ミ Mi
-			'\u{30e0}' => Some(0xD1),              // ム Mu
-			'\u{30e1}' => Some(0xD2),              // メ Me
-			'\u{30e2}' => Some(0xD3),              // モ Mo
-			'\u{30e4}' => Some(0xD4),              // ヤ Ya
-			'\u{30e6}' => Some(0xD5),              // ユ Yu
-			'\u{30e8}' => Some(0xD6),              // ヨ Yo
-			'\u{30e9}' => Some(0xD7),              // ラ Ra
-			'\u{30ea}' => Some(0xD8),              // リ Ri
-			'\u{30eb}' => Some(0xD9),              // ル Ru
-			'\u{30ec}' => Some(0xDA),              // レ Re
-			'\u{30ed}' => Some(0xDB),              // ロ Ro
-			'\u{30ef}' => Some(0xDC),              // ワ Wa
-			'\u{30f3}' => Some(0xDD),              // ン N
-			'\u{309B}' | '\u{3099}' => Some(0xDE), // ゛ Dakuten
-			'\u{309C}' | '\u{309A}' => Some(0xDF), // ゜ Handakuten
-			// Upper (5x10 Extra)
-			'\u{03b1}' => Some(0xE0), // α Small Alpha
-			'\u{00e4}' => Some(0xE1), // ä Small A with Diaeresis
-			'\u{03b2}' => Some(0xE2), // β Small Beta
-			'\u{03b5}' => Some(0xE3), // ε Small Epsilon
-			'\u{00b5}' => Some(0xE4), // µ Small Mu/Micro
-			'\u{03c3}' => Some(0xE5), // σ Small Sigma
-			'\u{03c1}' => Some(0xE6), // ρ Small Rho
-			// E7: Small G (tall version)
-			'\u{221a}' => Some(0xE8), // √ Square Root
-			// E9: Superscript -1 (has no unicode character)
-			// EA: Small J (tall version)
-			// EB: Superscript Small X (has no unicode character)
-			'\u{00A2}' => Some(0xEC), // ¢ Cent
-			'\u{2c60}' => Some(0xED), // Ⱡ Capital L with Double Bar
-			'\u{00f1}' => Some(0xEE), // ñ Small N with Tilde
-			'\u{00f6}' => Some(0xEF), // ö Small O with Diaeresis
-			// F0: Small P (tall version)
-			// F1: Small Q (tall version)
-			'\u{03b8}' => Some(0xF2), // θ Small Theta
-			'\u{221e}' => Some(0xF3), // ∞ Infinity
-			'\u{03a9}' => Some(0xF4), // Ω Capital Omega
-			'\u{00fc}' => Some(0xF5), // ü Small U with Diaeresis
-			'\u{03a3}' => Some(0xF6), // Σ Capital Sigma
-			'\u{03c0}' => Some(0xF7), // π Small Pi
-			// F8: Small X-Bar (has no unicode character)
-			// F9: Small Y (tall version)
-			'\u{5343}' => Some(0xFA), // 千 Sen (1,000)
-			'\u{4E07}' => Some(0xFB), // 万 Man (10,000)
-			'\u{5186}' => Some(0xFC), // 円 Yen/¥
-			'\u{00f7}' => Some(0xFD), // ÷ Division
-			// FE: Empty
-			'\u{2588}' => Some(0xFF), // █ Full Block
-			// Unmatched
-			ch if ch.is_whitespace() => Some(b' '), // full-width space
+		let ch = Self::normalize(ch);
+
+		Self::REVERSE.code(ch).or(match ch {
+			'\u{FFE5}' => Some(0x5C),                                           // ￥ Full-width Yen
+			'\u{FF61}'..='\u{FF9F}' => Some((ch as u32 - 0xFF61) as u8 + 0xA1), // Half-width katakana (JIS X 0201)
+			'\u{3099}' => Some(0xDE),                                           // Combining Dakuten
+			'\u{309A}' => Some(0xDF),                                           // Combining Handakuten
+			ch if ch.is_whitespace() => Some(b' '),                             // full-width space
 			_ => None,
+		})
+	}
+
+	fn codes_from_utf8(&self, ch: char) -> Option<CharCodes> {
+		if let Some(code) = self.code_from_utf8(ch) {
+			return Some(CharCodes::single(code));
 		}
+
+		let ch = Self::normalize(ch);
+
+		// Voiced katakana are displayed as the unvoiced katakana followed by a (han)dakuten
+		let (unvoiced, mark) = match ch {
+			'\u{30AC}' | '\u{30AE}' | '\u{30B0}' | '\u{30B2}' | '\u{30B4}' // ガ ギ グ ゲ ゴ
+			| '\u{30B6}' | '\u{30B8}' | '\u{30BA}' | '\u{30BC}' | '\u{30BE}' // ザ ジ ズ ゼ ゾ
+			| '\u{30C0}' | '\u{30C2}' | '\u{30C5}' | '\u{30C7}' | '\u{30C9}' // ダ ヂ ヅ デ ド
+			| '\u{30D0}' | '\u{30D3}' | '\u{30D6}' | '\u{30D9}' | '\u{30DC}' // バ ビ ブ ベ ボ
+			=> (ch as u32 - 1, 0xDE),
+			'\u{30D1}' | '\u{30D4}' | '\u{30D7}' | '\u{30DA}' | '\u{30DD}' => (ch as u32 - 2, 0xDF), // パ ピ プ ペ ポ
+			'\u{30F4}' => (0x30A6, 0xDE), // ヴ
+			'\u{30F7}' => (0x30EF, 0xDE), // ヷ
+			'\u{30FA}' => (0x30F2, 0xDE), // ヺ
+			_ => return None,
+		};
+
+		let unvoiced = self.code_from_utf8(char::from_u32(unvoiced)?)?;
+		Some(CharCodes::from_slice(&[unvoiced, mark]))
+	}
+
+	fn char_from_code(&self, code: u8) -> Option<char> {
+		Self::ROM[code as usize]
 	}
 }
 
-/// European Standard Font Character Set.
-//
-/// For reference, see page 18 on [the Hitachi datasheet by Sparkfun](https://www.sparkfun.com/datasheets/LCD/HD44780.pdf).
-#[derive(Debug, Clone, Copy)]
-pub struct CharsetA02;
+define_charset! {
+	/// European Standard Font Character Set.
+	///
+	/// For reference, see page 18 on [the Hitachi datasheet by Sparkfun](https://www.sparkfun.com/datasheets/LCD/HD44780.pdf).
+	pub struct CharsetA02 {
+		columns: [
+			CGRAM_COLUMN, "⏵⏴“”⏫⏬⏺↲↑↓→←≤≥⏶⏷",
+			" !\"#$%&'()*+,-./", "0123456789:;<=>?", "@ABCDEFGHIJKLMNO", "PQRSTUVWXYZ[\\]^_",
+			"`abcdefghijklmno", "pqrstuvwxyz{|}~⌂",
+			"БДЖЗИЙЛПУЦЧШЩЪЫЭ", "α♪ГπΣσ♬τ🔔ϴΩδ∞♥ε∩",
+			// B4: Pt symbol (has no unicode character)
+			"⏸¡¢£¤¥¦§⨍©ª«ЮЯ®‘", "°±²³�µ¶·ω¹º»¼½¾¿",
+			"ÀÁÂÃÄÅÆÇÈÉÊËÌÍÎÏ", "ÐÑÒÓÔÕÖ×ɸÙÚÛÜÝÞß",
+			"àáâãäåæçèéêëìíîï", "ðñòóôõö÷∮ùúûüýþÿ",
+		],
+		aliases: {
+			// Cyrillic letters looking like latin ones
+			'А' => 0x41, 'В' => 0x42, 'Е' => 0x45, 'К' => 0x4B, 'М' => 0x4D, 'Н' => 0x48, 'О' => 0x4F,
+			'Р' => 0x50, 'С' => 0x43, 'Т' => 0x54, 'Х' => 0x58, 'Ь' => 0x62,
+			// Other
+			'’' => 0x27,
+		},
+	}
+}
 
-impl CharsetA02 {
-	pub const EMPTY_FALLBACK: EmptyFallback<Self> = Fallback(Self);
-	pub const QUESTION_FALLBACK: QuestionFallback<Self> = Fallback(Self);
+define_charset! {
+	/// Western European ROM of the `ST7066U-0E` and `KS0066` F05.
+	///
+	/// ASCII, with the upper half following ISO 8859-1.
+	pub struct CharsetWesternEuropean {
+		columns: [
+			CGRAM_COLUMN, EMPTY_COLUMN,
+			" !\"#$%&'()*+,-./", "0123456789:;<=>?", "@ABCDEFGHIJKLMNO", "PQRSTUVWXYZ[\\]^_",
+			"`abcdefghijklmno", "pqrstuvwxyz{|}~�",
+			EMPTY_COLUMN, EMPTY_COLUMN,
+			"�¡¢£¤¥¦§¨©ª«¬\u{AD}®¯", "°±²³´µ¶·¸¹º»¼½¾¿",
+			"ÀÁÂÃÄÅÆÇÈÉÊËÌÍÎÏ", "ÐÑÒÓÔÕÖ×ØÙÚÛÜÝÞß",
+			"àáâãäåæçèéêëìíîï", "ðñòóôõö÷øùúûüýþÿ",
+		],
+	}
 }
 
-impl Charset for CharsetA02 {
-	fn code_from_utf8(&self, ch: char) -> Option<u8> {
-		match ch {
-			'\x00'..='\x0f' | '\x20'..='\x7e' => Some(ch as u8), // CGRAM + ASCII
-			// Latin-1 with exceptions
-			'\u{A8}' | '\u{AC}' | '\u{AD}' | '\u{AF}' | '\u{B4}' | '\u{B8}' | '\u{D8}' | '\u{F8}' => None,
-			'\u{A1}'..='\u{FF}' => Some(ch as u8), // Latin-1 Supplement
-			// Lower
-			'\u{23F5}' => Some(0x00), // ⏵
-			'\u{23F4}' => Some(0x01), // ⏴
-			'\u{201C}' => Some(0x02), // “
-			'\u{201D}' => Some(0x03), // ”
-			'\u{23EB}' => Some(0x04), // ⏫
-			'\u{23EC}' => Some(0x05), // ⏬
-			'\u{23FA}' => Some(0x06), // ⏺
-			'\u{21b2}' => Some(0x07), // ↲
-			'\u{2191}' => Some(0x08), // ↑
-			'\u{2193}' => Some(0x09), // ↓
-			'\u{2192}' => Some(0x0A), // →
-			'\u{2190}' => Some(0x0B), // ←
-			'\u{2264}' => Some(0x0C), // ≤
-			'\u{2265}' => Some(0x0D), // ≥
-			'\u{23F6}' => Some(0x0E), // ⏶
-			'\u{23F7}' => Some(0x0F), // ⏷
-			// Middle
-			'\u{2302}' => Some(0x7F), // ⌂
-			// Cyrillic
-			'\u{0410}' => Some(b'A'), // А
-			'\u{0411}' => Some(0x80), // Б
-			'\u{0412}' => Some(b'B'), // В
-			'\u{0413}' => Some(0x92), // Г
-			'\u{0414}' => Some(0x81), // Д
-			'\u{0415}' => Some(b'E'), // Е
-			'\u{0416}' => Some(0x82), // Ж
-			'\u{0417}' => Some(0x83), // З
-			'\u{0418}' => Some(0x84), // И
-			'\u{0419}' => Some(0x85), // Й
-			'\u{041A}' => Some(b'K'), // К
-			'\u{041B}' => Some(0x86), // Л
-			'\u{041C}' => Some(b'M'), // М
-			'\u{041D}' => Some(b'H'), // Н
-			'\u{041E}' => Some(b'O'), // О
-			'\u{041F}' => Some(0x87), // П
-			'\u{0420}' => Some(b'P'), // Р
-			'\u{0421}' => Some(b'C'), // С
-			'\u{0422}' => Some(b'T'), // Т
-			'\u{0423}' => Some(0x88), // У
-			//'\u{0424}' => None,     // Ф
-			'\u{0425}' => Some(b'X'), // Х
-			'\u{0426}' => Some(0x89), // Ц
-			'\u{0427}' => Some(0x8A), // Ч
-			'\u{0428}' => Some(0x8B), // Ш
-			'\u{0429}' => Some(0x8C), // Щ
-			'\u{042A}' => Some(0x8D), // Ъ
-			'\u{042B}' => Some(0x8E), // Ы
-			'\u{042C}' => Some(b'b'), // Ь
-			'\u{042D}' => Some(0x8F), // Э
-			'\u{042E}' => Some(0xAC), // Ю
-			'\u{042F}' => Some(0xAD), // Я
-			// Other
-			'\u{03B1}' => Some(0x90),  // α Small Alpha
-			'\u{266A}' => Some(0x91),  // ♪ Eighth Note
-			'\u{03C0}' => Some(0x93),  // π Small Pi
-			'\u{03A3}' => Some(0x94),  // Σ Capital Sigma
-			'\u{03C3}' => Some(0x95),  // σ Small Sigma
-			'\u{266C}' => Some(0x96),  // ♬ Beamed Sixteenth Notes
-			'\u{03C4}' => Some(0x97),  // τ Small Tau
-			'\u{1F514}' => Some(0x98), // 🔔 Bell
-			'\u{03F4}' => Some(0x99),  // ϴ Capital Theta
-			'\u{03A9}' => Some(0x9A),  // Ω Capital Omega
-			'\u{03B4}' => Some(0x9B),  // δ Small Delta
-			'\u{221e}' => Some(0x9C),  // ∞ Infinity
-			'\u{2665}' => Some(0x9D),  // ♥ Heart
-			'\u{03B5}' => Some(0x9E),  // ε Small Epsilon
-			'\u{2229}' => Some(0x9F),  // ∩ Intersection
-			'\u{23F8}' => Some(0xA0),  // ⏸ Double Vertical Bar
-			'\u{2A0D}' => Some(0xA8),  // ⨍ Finite Part Integral
-			// B4: Pt Symbol (has no unicode character)
-			'\u{03C9}' => Some(0xB8),  // ω Small Omega
-			'\u{0278}' => Some(0xD8),  // ɸ Small Phi
-			'\u{222E}' => Some(0xF8),  // ∮ Contour Integral
-			'\u{2018}' => Some(0xAF),  // ‘
-			'\u{2019}' => Some(b'\''), // ’
-			_ => None,
+define_charset! {
+	/// Cyrillic ROM of the `ST7066U-0B` and the Winstar `WH1602B-...-CT` modules. Only the
+	/// letters without a latin look-alike have codes of their own.
+	pub struct CharsetCyrillic {
+		columns: [
+			CGRAM_COLUMN, EMPTY_COLUMN,
+			" !\"#$%&'()*+,-./", "0123456789:;<=>?", "@ABCDEFGHIJKLMNO", "PQRSTUVWXYZ[�]^_",
+			"`abcdefghijklmno", "pqrstuvwxyz{|}��",
+			EMPTY_COLUMN, EMPTY_COLUMN,
+			"БГЁЖЗИЙЛПУФЧШЪЫЭ", "ЮЯбвгёжзийклмнпт",
+			"чшъыьэюя��������", EMPTY_COLUMN,
+			"ДЦЩдфцщ���������", EMPTY_COLUMN,
+		],
+		aliases: {
+			'А' => 0x41, 'В' => 0x42, 'Е' => 0x45, 'К' => 0x4B, 'М' => 0x4D, 'Н' => 0x48, 'О' => 0x4F,
+			'Р' => 0x50, 'С' => 0x43, 'Т' => 0x54, 'Х' => 0x58, 'Ь' => 0x62,
+			'а' => 0x61, 'е' => 0x65, 'о' => 0x6F, 'р' => 0x70, 'с' => 0x63, 'у' => 0x79, 'х' => 0x78,
+		},
+	}
+}
+
+define_charset! {
+	/// Hebrew ROM, with the 27 letters (including final forms) in alphabetical order from
+	/// `0xA0`. No controller datasheet lists it, compare it with the code table of the module.
+	pub struct CharsetHebrew {
+		columns: [
+			CGRAM_COLUMN, EMPTY_COLUMN,
+			" !\"#$%&'()*+,-./", "0123456789:;<=>?", "@ABCDEFGHIJKLMNO", "PQRSTUVWXYZ[�]^_",
+			"`abcdefghijklmno", "pqrstuvwxyz{|}��",
+			EMPTY_COLUMN, EMPTY_COLUMN,
+			"אבגדהוזחטיךכלםמן", "נסעףפץצקרשת�����",
+			EMPTY_COLUMN, EMPTY_COLUMN, EMPTY_COLUMN, EMPTY_COLUMN,
+		],
+		tests: hebrew_tests,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const GLYPHS: &[(char, [u8; 8])] = &[
+		('ą', [1; 8]),
+		('ć', [2; 8]),
+		('ę', [3; 8]),
+		('ł', [4; 8]),
+		('ń', [5; 8]),
+		('ó', [6; 8]),
+		('ś', [7; 8]),
+		('ź', [8; 8]),
+		('ż', [9; 8]),
+	];
+
+	#[test]
+	fn round_trip_universal() {
+		assert_round_trip(&CharsetUniversal);
+	}
+
+	#[test]
+	fn round_trip_a00() {
+		assert_round_trip(&CharsetA00);
+	}
+
+	#[test]
+	fn round_trip_a02() {
+		assert_round_trip(&CharsetA02);
+	}
+
+	#[test]
+	fn round_trip_vendor_roms() {
+		assert_round_trip(&CharsetWesternEuropean);
+		assert_round_trip(&CharsetCyrillic);
+	}
+
+	/// Code table as printed in a datasheet: one string per column of the table, holding the
+	/// characters of `0xN0..=0xNF` for `N` of `0x2` to `0xF`. `�` marks codes without a Unicode
+	/// character or left empty.
+	type DatasheetTable = [&'static str; 14];
+
+	const ASCII_WITHOUT_YEN_AND_ARROWS: [&str; 6] = [
+		" !\"#$%&'()*+,-./",
+		"0123456789:;<=>?",
+		"@ABCDEFGHIJKLMNO",
+		"PQRSTUVWXYZ[�]^_",
+		"`abcdefghijklmno",
+		"pqrstuvwxyz{|}��",
+	];
+
+	fn assert_matches_datasheet(charset: &impl Charset, table: &DatasheetTable) {
+		for (column, high) in table.iter().zip(0x2..=0xF_u8) {
+			let mut chars = column.chars();
+			for low in 0..=0xF {
+				let code = high << 4 | low;
+				let Some(expected) = chars.next() else {
+					panic!("column {high:X} is missing {code:#04X}");
+				};
+				let expected = Some(expected).filter(|&ch| ch != '\u{FFFD}');
+				assert_eq!(charset.char_from_code(code), expected, "mismatch at {code:#04X}");
+			}
+			assert_eq!(chars.next(), None, "column {high:X} has more than 16 characters");
 		}
 	}
+
+	#[test]
+	fn western_european_datasheet() {
+		let [a, b, c, _, e, _] = ASCII_WITHOUT_YEN_AND_ARROWS;
+		#[rustfmt::skip]
+		let table = [
+			a, b, c, "PQRSTUVWXYZ[\\]^_", e, "pqrstuvwxyz{|}~�",
+			EMPTY_COLUMN, EMPTY_COLUMN,
+			"�¡¢£¤¥¦§¨©ª«¬\u{AD}®¯", "°±²³´µ¶·¸¹º»¼½¾¿",
+			"ÀÁÂÃÄÅÆÇÈÉÊËÌÍÎÏ", "ÐÑÒÓÔÕÖ×ØÙÚÛÜÝÞß",
+			"àáâãäåæçèéêëìíîï", "ðñòóôõö÷øùúûüýþÿ",
+		];
+		assert_matches_datasheet(&CharsetWesternEuropean, &table);
+	}
+
+	#[test]
+	fn cyrillic_datasheet() {
+		let [a, b, c, d, e, f] = ASCII_WITHOUT_YEN_AND_ARROWS;
+		#[rustfmt::skip]
+		let table = [
+			a, b, c, d, e, f,
+			EMPTY_COLUMN, EMPTY_COLUMN,
+			"БГЁЖЗИЙЛПУФЧШЪЫЭ", "ЮЯбвгёжзийклмнпт",
+			"чшъыьэюя��������", EMPTY_COLUMN,
+			"ДЦЩдфцщ���������", EMPTY_COLUMN,
+		];
+		assert_matches_datasheet(&CharsetCyrillic, &table);
+	}
+
+	#[test]
+	fn hebrew_datasheet() {
+		let [a, b, c, d, e, f] = ASCII_WITHOUT_YEN_AND_ARROWS;
+		#[rustfmt::skip]
+		let table = [
+			a, b, c, d, e, f,
+			EMPTY_COLUMN, EMPTY_COLUMN,
+			"אבגדהוזחטיךכלםמן", "נסעףפץצקרשת�����",
+			EMPTY_COLUMN, EMPTY_COLUMN, EMPTY_COLUMN, EMPTY_COLUMN,
+		];
+		assert_matches_datasheet(&CharsetHebrew, &table);
+	}
+
+	#[test]
+	fn reverse_lookup() {
+		assert_eq!(CharsetUniversal.char_from_code(b'A'), Some('A'));
+		assert_eq!(CharsetUniversal.char_from_code(0x5C), None);
+		assert_eq!(CharsetA00.char_from_code(0x5C), Some('¥'));
+		assert_eq!(CharsetA00.char_from_code(0xB1), Some('ア'));
+		assert_eq!(CharsetA00.char_from_code(0xA0), None);
+		assert_eq!(CharsetA02.char_from_code(0x10), Some('⏵'));
+		assert_eq!(CharsetA02.char_from_code(0xE9), Some('é'));
+		assert_eq!(CharsetA02.char_from_code(0xAC), Some('Ю'));
+		assert_eq!(CharsetA02::QUESTION_FALLBACK.char_from_code_with_fallback(0xAC), Some('Ю'));
+	}
+
+	#[test]
+	fn transliterating_fallback() {
+		let charset = CharsetUniversal::TRANSLITERATING_FALLBACK;
+
+		assert_eq!(charset.code_from_utf8_with_fallback('a'), b'a');
+		assert_eq!(charset.code_from_utf8_with_fallback('é'), b'e');
+		assert_eq!(charset.code_from_utf8_with_fallback('Ł'), b'L');
+		assert_eq!(charset.code_from_utf8_with_fallback('ñ'), b'n');
+		assert_eq!(charset.code_from_utf8_with_fallback('’'), b'\'');
+		assert_eq!(charset.code_from_utf8_with_fallback('“'), b'"');
+		assert_eq!(charset.code_from_utf8_with_fallback('—'), b'-');
+		assert_eq!(charset.code_from_utf8_with_fallback('…'), b'.');
+		assert_eq!(charset.code_from_utf8_with_fallback('€'), b'E');
+		assert_eq!(charset.code_from_utf8_with_fallback('π'), b'?');
+	}
+
+	#[test]
+	fn transliterating_fallback_prefers_rom() {
+		let charset = CharsetA00::TRANSLITERATING_FALLBACK;
+
+		// Available in the A00 ROM
+		assert_eq!(charset.code_from_utf8_with_fallback('ñ'), 0xEE);
+		assert_eq!(charset.code_from_utf8_with_fallback('ä'), 0xE1);
+		// Missing from the A00 ROM
+		assert_eq!(charset.code_from_utf8_with_fallback('é'), b'e');
+
+		let charset = TransliteratingFallback::<_, b' '>::new(CharsetA02);
+		assert_eq!(charset.code_from_utf8_with_fallback('é'), 0xE9);
+		assert_eq!(charset.code_from_utf8_with_fallback('ł'), b'l');
+		assert_eq!(charset.code_from_utf8_with_fallback('ア'), b' ');
+	}
+
+	#[test]
+	fn multi_cell_katakana() {
+		let charset = CharsetA00::QUESTION_FALLBACK;
+
+		assert_eq!(charset.codes_from_utf8_with_fallback('カ').as_slice(), &[0xB6]);
+		assert_eq!(charset.codes_from_utf8_with_fallback('ガ').as_slice(), &[0xB6, 0xDE]);
+		assert_eq!(charset.codes_from_utf8_with_fallback('ヅ').as_slice(), &[0xC2, 0xDE]);
+		assert_eq!(charset.codes_from_utf8_with_fallback('ポ').as_slice(), &[0xCE, 0xDF]);
+		assert_eq!(charset.codes_from_utf8_with_fallback('ヴ').as_slice(), &[0xB3, 0xDE]);
+		assert_eq!(charset.code_from_utf8_with_fallback('ガ'), b'?');
+		assert_eq!(charset.text_width("ガイド"), 5);
+	}
+
+	#[test]
+	fn a00_hiragana_and_full_width() {
+		let charset = CharsetA00::QUESTION_FALLBACK;
+
+		assert_eq!(charset.code_from_utf8_with_fallback('あ'), 0xB1);
+		assert_eq!(charset.code_from_utf8_with_fallback('ん'), 0xDD);
+		assert_eq!(charset.code_from_utf8_with_fallback('ゃ'), 0xAC);
+		assert_eq!(charset.codes_from_utf8_with_fallback('が').as_slice(), &[0xB6, 0xDE]);
+		assert_eq!(charset.codes_from_utf8_with_fallback('ぱ').as_slice(), &[0xCA, 0xDF]);
+
+		assert_eq!(charset.code_from_utf8_with_fallback('Ａ'), b'A');
+		assert_eq!(charset.code_from_utf8_with_fallback('ｚ'), b'z');
+		assert_eq!(charset.code_from_utf8_with_fallback('１'), b'1');
+		assert_eq!(charset.code_from_utf8_with_fallback('！'), b'!');
+		assert_eq!(charset.code_from_utf8_with_fallback('＼'), b'?');
+		assert_eq!(charset.code_from_utf8_with_fallback('￥'), 0x5C);
+		assert_eq!(charset.code_from_utf8_with_fallback('\u{3000}'), b' ');
+
+		assert_eq!(charset.code_from_utf8_with_fallback('｡'), 0xA1);
+		assert_eq!(charset.code_from_utf8_with_fallback('ｦ'), 0xA6);
+		assert_eq!(charset.code_from_utf8_with_fallback('ｱ'), 0xB1);
+		assert_eq!(charset.code_from_utf8_with_fallback('ﾝ'), 0xDD);
+		assert_eq!(charset.code_from_utf8_with_fallback('ﾟ'), 0xDF);
+	}
+
+	#[test]
+	fn multi_cell_transliteration() {
+		let charset = CharsetUniversal::TRANSLITERATING_FALLBACK;
+
+		assert_eq!(charset.codes_from_utf8_with_fallback('€').as_slice(), b"EUR");
+		assert_eq!(charset.codes_from_utf8_with_fallback('…').as_slice(), b"...");
+		assert_eq!(charset.codes_from_utf8_with_fallback('ß').as_slice(), b"ss");
+		assert_eq!(charset.codes_from_utf8_with_fallback('é').as_slice(), b"e");
+		assert_eq!(charset.codes_from_utf8_with_fallback('π').as_slice(), b"?");
+		assert_eq!(charset.text_width("5 €…"), 8);
+
+		// A00 has no 'ß', but 's' is available
+		let charset = CharsetA00::TRANSLITERATING_FALLBACK;
+		assert_eq!(charset.codes_from_utf8_with_fallback('ß').as_slice(), b"ss");
+	}
+
+	#[test]
+	fn dynamic_charset_uploads_once() {
+		let mut charset = DynamicCharset::new(CharsetA00::QUESTION_FALLBACK, GLYPHS);
+
+		assert_eq!(charset.glyph_for('a'), None);
+		assert_eq!(charset.code_from_utf8_with_fallback('a'), b'a');
+
+		assert_eq!(charset.glyph_for('ł'), Some(GlyphUpload { slot: 0, pattern: [4; 8] }));
+		assert_eq!(charset.code_from_utf8_with_fallback('ł'), 0);
+		assert_eq!(charset.glyph_for('ł'), None);
+		assert_eq!(charset.code_from_utf8_with_fallback('ł'), 0);
+	}
+
+	#[test]
+	fn dynamic_charset_prefers_rom() {
+		let mut charset = DynamicCharset::new(CharsetA02::QUESTION_FALLBACK, &[('°', [1; 8]), ('ł', [4; 8])]);
+
+		assert!(!charset.needs_glyph('°'));
+		assert_eq!(charset.glyph_for('°'), None);
+		assert_eq!(charset.code_from_utf8_with_fallback('°'), 0xB0);
+		assert_eq!(charset.glyph_for('ł'), Some(GlyphUpload { slot: 0, pattern: [4; 8] }));
+	}
+
+	#[test]
+	fn dynamic_charset_keeps_glyphs_on_screen() {
+		let mut charset = DynamicCharset::new(CharsetA00::QUESTION_FALLBACK, GLYPHS);
+
+		for (slot, &(ch, _)) in GLYPHS.iter().take(8).enumerate() {
+			assert_eq!(charset.glyph_for(ch).map(|g| g.slot), Some(slot as u8));
+		}
+
+		// All slots are visible, so the ninth glyph has to fall back
+		assert_eq!(charset.glyph_for('ż'), None);
+		assert_eq!(charset.code_from_utf8_with_fallback('ż'), b'?');
+	}
+
+	#[test]
+	fn dynamic_charset_evicts_least_recently_used() {
+		let mut charset = DynamicCharset::new(CharsetA00::QUESTION_FALLBACK, GLYPHS);
+
+		for &(ch, _) in GLYPHS.iter().take(8) {
+			charset.glyph_for(ch);
+		}
+		charset.screen_cleared();
+		charset.glyph_for('ą');
+
+		// 'ć' is the least recently used glyph not on the new screen
+		assert_eq!(charset.glyph_for('ż'), Some(GlyphUpload { slot: 1, pattern: [9; 8] }));
+		assert_eq!(charset.code_from_utf8_with_fallback('ć'), b'?');
+		assert_eq!(charset.code_from_utf8_with_fallback('ą'), 0);
+	}
 }
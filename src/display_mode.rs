@@ -1,5 +1,8 @@
 use crate::{Cursor, CursorBlink, Display};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 pub struct DisplayMode {
 	pub cursor_visibility: Cursor,
 	pub cursor_blink: CursorBlink,
@@ -13,7 +16,7 @@ impl Default for DisplayMode {
 }
 
 impl DisplayMode {
-	pub fn as_byte(&self) -> u8 {
+	pub const fn as_byte(&self) -> u8 {
 		let cursor_blink_bits = match self.cursor_blink {
 			CursorBlink::On => 0b0000_0001,
 			CursorBlink::Off => 0,
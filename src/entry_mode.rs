@@ -28,7 +28,7 @@ impl From<bool> for ShiftMode {
 	}
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 pub struct EntryMode {
@@ -37,7 +37,7 @@ pub struct EntryMode {
 }
 
 impl EntryMode {
-	pub fn as_byte(&self) -> u8 {
+	pub const fn as_byte(&self) -> u8 {
 		let cursor_bits = match self.cursor_mode {
 			CursorMode::Increment => 0b0000_0010,
 			CursorMode::Decrement => 0,
@@ -1,13 +1,24 @@
+use embedded_hal::digital;
+
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error<IoE> {
 	/// Error related to IO of the MCU.
 	Io {
 		/// Which port (pin or interface) the error belongs to.
 		port: Port,
+		/// Parallel buses report the [`ErrorKind`](digital::ErrorKind) of the failing pin, as their
+		/// pins may each have a different error type.
 		error: IoE,
 	},
 	/// Invalid coordinates on the display.
 	Position { position: (u8, u8), size: (u8, u8) },
+	/// Not enough room left in the queue of a [polling][`crate::polling::HD44780`] driver.
+	QueueFull,
+	/// The controller lacks the feature, like graphic mode on a `US2066`.
+	Unsupported,
+	/// More glyphs than CGRAM slots, the display holds eight 5x8 glyphs.
+	TooManyGlyphs,
 }
 
 impl<E> Error<E> {
@@ -16,6 +27,13 @@ impl<E> Error<E> {
 	}
 }
 
+impl Error<digital::ErrorKind> {
+	/// Like [`Error::wrap_io`], for buses whose pins may each have a different error type.
+	pub(crate) fn wrap_pin<PE: digital::Error>(port: Port) -> impl FnOnce(PE) -> Self {
+		move |error| Self::Io { port, error: error.kind() }
+	}
+}
+
 impl<E: core::fmt::Debug> core::fmt::Display for Error<E> {
 	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
 		match self {
@@ -25,6 +43,9 @@ impl<E: core::fmt::Debug> core::fmt::Display for Error<E> {
 				"coordinates out of bounds: ({};{}) not fitting in a {}x{} display",
 				position.0, position.1, size.0, size.1
 			),
+			Self::QueueFull => write!(f, "command queue is full"),
+			Self::Unsupported => write!(f, "not supported by the controller"),
+			Self::TooManyGlyphs => write!(f, "more glyphs than CGRAM slots"),
 		}
 	}
 }
@@ -42,6 +63,9 @@ impl<E: defmt::Format> defmt::Format for Error<E> {
 				size.0,
 				size.1
 			),
+			Self::QueueFull => defmt::write!(fmt, "command queue is full"),
+			Self::Unsupported => defmt::write!(fmt, "not supported by the controller"),
+			Self::TooManyGlyphs => defmt::write!(fmt, "more glyphs than CGRAM slots"),
 		}
 	}
 }
@@ -62,6 +86,9 @@ impl<E: ufmt::uDebug> ufmt::uDisplay for Error<E> {
 				size.0,
 				size.1
 			),
+			Self::QueueFull => ufmt::uwrite!(f, "command queue is full"),
+			Self::Unsupported => ufmt::uwrite!(f, "not supported by the controller"),
+			Self::TooManyGlyphs => ufmt::uwrite!(f, "more glyphs than CGRAM slots"),
 		}
 	}
 }
@@ -101,12 +128,16 @@ pub enum Port {
 	/// Pin `D7` of a [FourBitBus][`crate::bus::FourBitBus`] or
 	/// [EightBitBus][`crate::bus::EightBitBus`].
 	D7,
-	/// Pin `RS` of a [FourBitBus][`crate::bus::FourBitBus`] or
-	/// [EightBitBus][`crate::bus::EightBitBus`].
+	/// Data lines of a [PortBus][`crate::bus::PortBus`].
+	Data,
+	/// Pin `RS` of a [FourBitBus][`crate::bus::FourBitBus`], [EightBitBus][`crate::bus::EightBitBus`],
+	/// [PortBus][`crate::bus::PortBus`] or SPI [SerialControlBus][`crate::bus::SerialControlBus`].
 	RS,
-	/// Pin `EN` of a [FourBitBus][`crate::bus::FourBitBus`] or
-	/// [EightBitBus][`crate::bus::EightBitBus`].
+	/// Pin `EN` of a [FourBitBus][`crate::bus::FourBitBus`], [EightBitBus][`crate::bus::EightBitBus`]
+	/// or [PortBus][`crate::bus::PortBus`].
 	EN,
-	/// [I2CBus][`crate::bus::I2CBus`].
+	/// [I2CBus][`crate::bus::I2CBus`] or I2C [SerialControlBus][`crate::bus::SerialControlBus`].
 	I2C,
+	/// SPI [SerialControlBus][`crate::bus::SerialControlBus`].
+	SPI,
 }
@@ -0,0 +1,398 @@
+pub(crate) mod sealed {
+	#[doc(hidden)]
+	pub trait Sealed {}
+}
+
+/// Custom character pattern, uploaded into CGRAM with
+/// [`HD44780::upload_glyph`](crate::HD44780::upload_glyph).
+pub trait Glyph: sealed::Sealed {
+	/// Number of glyphs of this size fitting into CGRAM.
+	const SLOTS: u8;
+	/// CGRAM bytes taken up by each slot.
+	const SLOT_SIZE: u8;
+
+	/// Rows written into CGRAM, top to bottom. Only the lower 5 bits are used.
+	fn rows(&self) -> &[u8];
+
+	/// CGRAM address of `slot`, wrapping around like the controller does.
+	fn cgram_address(slot: u8) -> u8 {
+		(slot % Self::SLOTS) * Self::SLOT_SIZE
+	}
+}
+
+/// Bit mask of the 5x8 codes (`0..=7`) sharing CGRAM with `slot` of a `G`.
+pub(crate) fn codes_of<G: Glyph>(slot: u8) -> u8 {
+	let count = Glyph5x8::SLOTS / G::SLOTS;
+	((1 << count) - 1) << (G::cgram_address(slot) / Glyph5x8::SLOT_SIZE)
+}
+
+/// Glyph of the 5x8 font, with the cursor line as its last row. Create it with
+/// [`glyph!`](crate::glyph!) from 8 rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct Glyph5x8 {
+	rows: [u8; 8],
+}
+
+impl Glyph5x8 {
+	pub const fn from_rows(rows: [u8; 8]) -> Self {
+		let mut rows = rows;
+		let mut i = 0;
+		while i < rows.len() {
+			rows[i] &= 0b1_1111;
+			i += 1;
+		}
+
+		Self { rows }
+	}
+
+	pub const fn into_rows(self) -> [u8; 8] {
+		self.rows
+	}
+}
+
+impl sealed::Sealed for Glyph5x8 {}
+
+impl Glyph for Glyph5x8 {
+	const SLOTS: u8 = 8;
+	const SLOT_SIZE: u8 = 8;
+
+	fn rows(&self) -> &[u8] {
+		&self.rows
+	}
+}
+
+/// Glyph of the 5x10 font, used by one line displays, which fits 4 of them into CGRAM.
+/// Create it with [`glyph!`](crate::glyph!) from 10 rows, the cursor line below is left blank.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct Glyph5x10 {
+	rows: [u8; 11],
+}
+
+impl Glyph5x10 {
+	pub const fn from_rows(rows: [u8; 10]) -> Self {
+		let mut all = [0; 11];
+		let mut i = 0;
+		while i < rows.len() {
+			all[i] = rows[i] & 0b1_1111;
+			i += 1;
+		}
+
+		Self { rows: all }
+	}
+
+	pub const fn into_rows(self) -> [u8; 10] {
+		let mut rows = [0; 10];
+		let mut i = 0;
+		while i < rows.len() {
+			rows[i] = self.rows[i];
+			i += 1;
+		}
+
+		rows
+	}
+}
+
+impl sealed::Sealed for Glyph5x10 {}
+
+impl Glyph for Glyph5x10 {
+	const SLOTS: u8 = 4;
+	const SLOT_SIZE: u8 = 16;
+
+	fn rows(&self) -> &[u8] {
+		&self.rows
+	}
+}
+
+/// Remembers which [`Glyph5x8`] is loaded into each CGRAM slot, so switching to another set of
+/// glyphs with [`HD44780::activate_glyphs`](crate::HD44780::activate_glyphs) only uploads the
+/// slots which changed.
+///
+/// The cache is dropped when the display is cleared, so a screen which starts with
+/// [`clear`](crate::HD44780::clear) also gets its glyphs back after the display lost power.
+/// Glyphs uploaded outside the bank, e.g. by a
+/// [`DynamicCharset`](crate::charset::DynamicCharset), drop it as well. A bank belongs to one
+/// display, which uploads every glyph on its first activation after being initialised again.
+///
+/// ```rust,ignore
+/// const STATUS: [Glyph5x8; 3] = [glyphs::BATTERY_FULL, glyphs::WIFI, glyphs::BELL];
+/// const PLAYER: [Glyph5x8; 3] = [glyphs::BATTERY_FULL, glyphs::PLAY, glyphs::PAUSE];
+///
+/// let mut bank = GlyphBank::new();
+/// lcd.activate_glyphs(&mut bank, &STATUS, &mut delay)?;
+/// // Slot 0 already holds the battery, only slots 1 and 2 are uploaded
+/// lcd.activate_glyphs(&mut bank, &PLAYER, &mut delay)?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct GlyphBank {
+	slots: [Option<Glyph5x8>; 8],
+	/// CGRAM generation of the display the slots were loaded into.
+	generation: Option<u32>,
+}
+
+impl GlyphBank {
+	pub const fn new() -> Self {
+		Self { slots: [None; 8], generation: None }
+	}
+
+	/// Forget what is loaded, so the next activation uploads every glyph.
+	pub fn invalidate(&mut self) {
+		self.slots = [None; 8];
+		self.generation = None;
+	}
+
+	/// Glyph loaded into `slot`, if the bank knows it.
+	pub fn loaded(&self, slot: u8) -> Option<&Glyph5x8> {
+		self.slots.get(slot as usize)?.as_ref()
+	}
+
+	/// Drop the cache if CGRAM changed since the last activation, or if the display's generation
+	/// is `None` as it wasn't activated since its init. Returns the generation display and bank
+	/// share afterwards, newer than either had before when the cache was dropped.
+	pub(crate) fn sync(&mut self, generation: Option<u32>) -> u32 {
+		match generation {
+			Some(generation) if self.generation == Some(generation) => generation,
+			_ => {
+				let next = generation.max(self.generation).map_or(0, |newest| newest.wrapping_add(1));
+				self.invalidate();
+				self.generation = Some(next);
+				next
+			}
+		}
+	}
+
+	/// Bit mask of the slots of `glyphs` which have to be uploaded. They count as unknown until
+	/// [`GlyphBank::loaded_into`] confirms the upload.
+	pub(crate) fn take_stale(&mut self, glyphs: &[Glyph5x8]) -> u8 {
+		let mut stale = 0;
+		for (slot, (glyph, loaded)) in glyphs.iter().zip(self.slots.iter_mut()).enumerate() {
+			if loaded.as_ref() != Some(glyph) {
+				*loaded = None;
+				stale |= 1 << slot;
+			}
+		}
+
+		stale
+	}
+
+	pub(crate) fn loaded_into(&mut self, slot: u8, glyph: Glyph5x8) {
+		self.slots[slot as usize] = Some(glyph);
+	}
+}
+
+/// Row of [`glyph!`](crate::glyph!): 5 pixels, `#` for on and `.` for off.
+#[doc(hidden)]
+pub const fn parse_row(row: &str) -> u8 {
+	let bytes = row.as_bytes();
+	assert!(bytes.len() == 5, "glyph rows are 5 pixels wide");
+
+	let mut bits = 0;
+	let mut i = 0;
+	while i < bytes.len() {
+		bits <<= 1;
+		match bytes[i] {
+			b'#' => bits |= 1,
+			b'.' => {}
+			_ => panic!("glyph pixels are `#` or `.`"),
+		}
+		i += 1;
+	}
+
+	bits
+}
+
+/// Defines a [`Glyph5x8`] from 8 rows or a [`Glyph5x10`] from 10 rows of ASCII art, `#` for
+/// pixels which are on and `.` for those which are off. Rows and pixels are checked at compile
+/// time.
+///
+/// ```rust,ignore
+/// const BELL: Glyph5x8 = glyph![
+///     "..#..",
+///     ".###.",
+///     ".###.",
+///     ".###.",
+///     "#####",
+///     ".....",
+///     "..#..",
+///     ".....",
+/// ];
+///
+/// lcd.upload_glyph(0, &BELL, &mut delay)?;
+/// lcd.write_byte(0, &mut delay)?;
+/// ```
+#[macro_export]
+macro_rules! glyph {
+	($r0:literal, $r1:literal, $r2:literal, $r3:literal, $r4:literal, $r5:literal, $r6:literal, $r7:literal $(,)?) => {{
+		const GLYPH: $crate::glyph::Glyph5x8 = $crate::glyph::Glyph5x8::from_rows([
+			$crate::glyph::parse_row($r0),
+			$crate::glyph::parse_row($r1),
+			$crate::glyph::parse_row($r2),
+			$crate::glyph::parse_row($r3),
+			$crate::glyph::parse_row($r4),
+			$crate::glyph::parse_row($r5),
+			$crate::glyph::parse_row($r6),
+			$crate::glyph::parse_row($r7),
+		]);
+		GLYPH
+	}};
+	(
+		$r0:literal, $r1:literal, $r2:literal, $r3:literal, $r4:literal,
+		$r5:literal, $r6:literal, $r7:literal, $r8:literal, $r9:literal $(,)?
+	) => {{
+		const GLYPH: $crate::glyph::Glyph5x10 = $crate::glyph::Glyph5x10::from_rows([
+			$crate::glyph::parse_row($r0),
+			$crate::glyph::parse_row($r1),
+			$crate::glyph::parse_row($r2),
+			$crate::glyph::parse_row($r3),
+			$crate::glyph::parse_row($r4),
+			$crate::glyph::parse_row($r5),
+			$crate::glyph::parse_row($r6),
+			$crate::glyph::parse_row($r7),
+			$crate::glyph::parse_row($r8),
+			$crate::glyph::parse_row($r9),
+		]);
+		GLYPH
+	}};
+	($($row:literal),* $(,)?) => {
+		compile_error!("glyphs have 8 rows (5x8) or 10 rows (5x10)")
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		charset::{CharsetA02, CharsetWithFallback, DynamicCharset},
+		display_mode::DisplayMode,
+		entry_mode::EntryMode,
+		error::Error,
+		glyphs,
+		memory_map::MemoryMap1602,
+		test_util::{NoDelay, Recorder},
+		HD44780,
+	};
+
+	/// Number of CGRAM slots addressed on the bus.
+	fn uploads(bus: &Recorder) -> usize {
+		bus.commands().filter(|command| command & 0b1100_0000 == 0b0100_0000).count()
+	}
+
+	#[test]
+	fn rows_from_ascii_art() {
+		const HEART: Glyph5x8 = glyph![".....", ".#.#.", "#####", "#####", ".###.", "..#..", ".....", "....."];
+		assert_eq!(HEART.into_rows(), [0b00000, 0b01010, 0b11111, 0b11111, 0b01110, 0b00100, 0b00000, 0b00000]);
+
+		let tall = glyph!["#....", ".....", ".....", ".....", ".....", ".....", ".....", ".....", ".....", "....#"];
+		assert_eq!(tall.rows(), [0b10000, 0, 0, 0, 0, 0, 0, 0, 0, 0b00001, 0]);
+		assert_eq!(Glyph5x10::cgram_address(5), 16);
+		assert_eq!(codes_of::<Glyph5x10>(5), 0b0000_1100);
+	}
+
+	#[test]
+	fn glyph_bank_uploads_differences() {
+		let mut lcd = HD44780::new_raw(
+			Recorder::default(),
+			MemoryMap1602::new(),
+			CharsetA02::QUESTION_FALLBACK,
+			EntryMode::default(),
+			DisplayMode::default(),
+		);
+		let mut bank = GlyphBank::new();
+		let mut uploads = |lcd: &mut HD44780<Recorder, _, _>, glyphs: &[Glyph5x8]| {
+			let before = uploads(&lcd.bus);
+			lcd.activate_glyphs(&mut bank, glyphs, &mut NoDelay).unwrap();
+			uploads(&lcd.bus) - before
+		};
+
+		assert_eq!(uploads(&mut lcd, &[glyphs::BATTERY_FULL, glyphs::WIFI, glyphs::BELL]), 3);
+		assert_eq!(uploads(&mut lcd, &[glyphs::BATTERY_FULL, glyphs::PLAY, glyphs::BELL]), 1);
+		assert_eq!(uploads(&mut lcd, &[glyphs::BATTERY_FULL, glyphs::PLAY]), 0);
+
+		lcd.clear(&mut NoDelay).unwrap();
+		assert_eq!(uploads(&mut lcd, &[glyphs::BATTERY_FULL, glyphs::PLAY]), 2);
+
+		lcd.upload_glyph(1, &glyphs::HEART, &mut NoDelay).unwrap();
+		assert_eq!(uploads(&mut lcd, &[glyphs::BATTERY_FULL, glyphs::PLAY]), 2);
+	}
+
+	#[test]
+	fn glyph_bank_rejects_more_than_eight() {
+		let mut lcd = HD44780::new_raw(
+			Recorder::default(),
+			MemoryMap1602::new(),
+			CharsetA02::QUESTION_FALLBACK,
+			EntryMode::default(),
+			DisplayMode::default(),
+		);
+		let mut bank = GlyphBank::new();
+
+		let result = lcd.activate_glyphs(&mut bank, &[glyphs::HEART; 9], &mut NoDelay);
+		assert!(matches!(result, Err(Error::TooManyGlyphs)));
+		assert_eq!(uploads(&lcd.bus), 0);
+
+		lcd.activate_glyphs(&mut bank, &[glyphs::HEART; 8], &mut NoDelay).unwrap();
+		assert_eq!(uploads(&lcd.bus), 8);
+	}
+
+	#[test]
+	fn glyph_bank_after_init_again() {
+		let display = || {
+			HD44780::new_raw(
+				Recorder::default(),
+				MemoryMap1602::new(),
+				CharsetA02::QUESTION_FALLBACK,
+				EntryMode::default(),
+				DisplayMode::default(),
+			)
+		};
+		let mut bank = GlyphBank::new();
+
+		let mut lcd = display();
+		lcd.activate_glyphs(&mut bank, &[glyphs::BELL, glyphs::LOCK], &mut NoDelay).unwrap();
+		assert_eq!(uploads(&lcd.bus), 2);
+
+		// The new instance starts where the old one did, but its CGRAM may have lost power
+		let mut lcd = display();
+		lcd.activate_glyphs(&mut bank, &[glyphs::BELL, glyphs::LOCK], &mut NoDelay).unwrap();
+		assert_eq!(uploads(&lcd.bus), 2);
+
+		// A second bank takes over the CGRAM, the first one no longer matches it
+		let mut other = GlyphBank::new();
+		lcd.activate_glyphs(&mut other, &[glyphs::WIFI], &mut NoDelay).unwrap();
+		lcd.activate_glyphs(&mut bank, &[glyphs::BELL, glyphs::LOCK], &mut NoDelay).unwrap();
+		assert_eq!(uploads(&lcd.bus), 5);
+	}
+
+	#[test]
+	fn uploads_replace_charset_glyphs() {
+		const GLYPHS: &[(char, [u8; 8])] = &[('ł', [4; 8]), ('ż', [9; 8])];
+
+		let mut lcd = HD44780::new_raw(
+			Recorder::default(),
+			MemoryMap1602::new(),
+			DynamicCharset::new(CharsetA02::QUESTION_FALLBACK, GLYPHS),
+			EntryMode::default(),
+			DisplayMode::default(),
+		);
+
+		lcd.write_str("łż", &mut NoDelay).unwrap();
+		assert_eq!(uploads(&lcd.bus), 2);
+
+		lcd.upload_glyph(0, &glyphs::HEART, &mut NoDelay).unwrap();
+		lcd.write_str("łż", &mut NoDelay).unwrap();
+		assert_eq!(uploads(&lcd.bus), 4);
+
+		lcd.activate_glyphs(&mut GlyphBank::new(), &[glyphs::BELL, glyphs::LOCK], &mut NoDelay).unwrap();
+		lcd.write_str("ż", &mut NoDelay).unwrap();
+		assert_eq!(uploads(&lcd.bus), 7);
+		assert_eq!(lcd.charset.code_from_utf8_with_fallback('ł'), 2);
+		assert_eq!(lcd.charset.code_from_utf8_with_fallback('ż'), 3);
+
+		lcd.charset.reset();
+		lcd.write_str("ł", &mut NoDelay).unwrap();
+		assert_eq!(lcd.charset.code_from_utf8_with_fallback('ł'), 0);
+	}
+}
@@ -0,0 +1,347 @@
+use crate::glyph;
+use crate::glyph::Glyph5x8;
+
+/// Battery from empty (`BATTERY[0]`) to full (`BATTERY[5]`).
+pub const BATTERY: [Glyph5x8; 6] = [battery(0), battery(1), battery(2), battery(3), battery(4), battery(5)];
+pub const BATTERY_EMPTY: Glyph5x8 = BATTERY[0];
+pub const BATTERY_FULL: Glyph5x8 = BATTERY[5];
+
+/// Signal strength bars, from no signal (`SIGNAL[0]`) to full strength (`SIGNAL[5]`).
+pub const SIGNAL: [Glyph5x8; 6] = [signal(0), signal(1), signal(2), signal(3), signal(4), signal(5)];
+
+#[rustfmt::skip]
+pub const WIFI: Glyph5x8 = glyph![
+	".....",
+	".###.",
+	"#...#",
+	"..#..",
+	".#.#.",
+	".....",
+	"..#..",
+	".....",
+];
+
+#[rustfmt::skip]
+pub const DEGREE: Glyph5x8 = glyph![
+	".##..",
+	"#..#.",
+	"#..#.",
+	".##..",
+	".....",
+	".....",
+	".....",
+	".....",
+];
+
+#[rustfmt::skip]
+pub const BELL: Glyph5x8 = glyph![
+	"..#..",
+	".###.",
+	".###.",
+	".###.",
+	"#####",
+	".....",
+	"..#..",
+	".....",
+];
+
+#[rustfmt::skip]
+pub const LOCK: Glyph5x8 = glyph![
+	".###.",
+	"#...#",
+	"#...#",
+	"#####",
+	"##.##",
+	"##.##",
+	"#####",
+	".....",
+];
+
+#[rustfmt::skip]
+pub const PLAY: Glyph5x8 = glyph![
+	"#....",
+	"##...",
+	"###..",
+	"####.",
+	"###..",
+	"##...",
+	"#....",
+	".....",
+];
+
+#[rustfmt::skip]
+pub const PAUSE: Glyph5x8 = glyph![
+	".....",
+	"##.##",
+	"##.##",
+	"##.##",
+	"##.##",
+	"##.##",
+	".....",
+	".....",
+];
+
+#[rustfmt::skip]
+pub const ARROW_UP: Glyph5x8 = glyph![
+	"..#..",
+	".###.",
+	"#.#.#",
+	"..#..",
+	"..#..",
+	"..#..",
+	"..#..",
+	".....",
+];
+
+#[rustfmt::skip]
+pub const ARROW_DOWN: Glyph5x8 = glyph![
+	"..#..",
+	"..#..",
+	"..#..",
+	"..#..",
+	"#.#.#",
+	".###.",
+	"..#..",
+	".....",
+];
+
+#[rustfmt::skip]
+pub const CHECK: Glyph5x8 = glyph![
+	".....",
+	"....#",
+	"...##",
+	"#.##.",
+	"###..",
+	".#...",
+	".....",
+	".....",
+];
+
+#[rustfmt::skip]
+pub const CROSS: Glyph5x8 = glyph![
+	".....",
+	"##.##",
+	".###.",
+	"..#..",
+	".###.",
+	"##.##",
+	".....",
+	".....",
+];
+
+#[rustfmt::skip]
+pub const HEART: Glyph5x8 = glyph![
+	".....",
+	".#.#.",
+	"#####",
+	"#####",
+	".###.",
+	"..#..",
+	".....",
+	".....",
+];
+
+/// Uses all 8 rows, so the bulb covers an underline cursor.
+#[rustfmt::skip]
+pub const THERMOMETER: Glyph5x8 = glyph![
+	"..#..",
+	".#.#.",
+	".#.#.",
+	".#.#.",
+	".###.",
+	"#####",
+	"#####",
+	".###.",
+];
+
+/// Symbols drawn with the icons above, in the format of
+/// [`DynamicCharset::new`](crate::charset::DynamicCharset::new).
+///
+/// ```rust,ignore
+/// let options = DisplayOptionsI2C::new(MemoryMap1602::new())
+///     .with_charset(DynamicCharset::new(CharsetA00::QUESTION_FALLBACK, UNICODE_GLYPHS));
+/// let mut lcd = HD44780::new(options, &mut delay)?;
+///
+/// lcd.write_str("🔒 21°C ♥", &mut delay)?;
+/// ```
+///
+/// Symbols the ROM of the wrapped charset already has, like `°`, `↑` or `♥` on the `A02` ROM,
+/// are written with their ROM code and never take up a CGRAM slot.
+pub const UNICODE_GLYPHS: &[(char, [u8; 8])] = &[
+	('\u{1F50B}', BATTERY_FULL.into_rows()),  // 🔋
+	('\u{1FAAB}', BATTERY_EMPTY.into_rows()), // 🪫
+	('\u{1F4F6}', SIGNAL[5].into_rows()),     // 📶
+	('\u{1F6DC}', WIFI.into_rows()),          // 🛜
+	('\u{00B0}', DEGREE.into_rows()),         // °
+	('\u{1F514}', BELL.into_rows()),          // 🔔
+	('\u{1F512}', LOCK.into_rows()),          // 🔒
+	('\u{25B6}', PLAY.into_rows()),           // ▶
+	('\u{23F8}', PAUSE.into_rows()),          // ⏸
+	('\u{2191}', ARROW_UP.into_rows()),       // ↑
+	('\u{2193}', ARROW_DOWN.into_rows()),     // ↓
+	('\u{2713}', CHECK.into_rows()),          // ✓
+	('\u{2714}', CHECK.into_rows()),          // ✔
+	('\u{2717}', CROSS.into_rows()),          // ✗
+	('\u{2718}', CROSS.into_rows()),          // ✘
+	('\u{2665}', HEART.into_rows()),          // ♥
+	('\u{2764}', HEART.into_rows()),          // ❤
+	('\u{1F321}', THERMOMETER.into_rows()),   // 🌡
+];
+
+/// Battery with a cap, an outline and `level` of its 5 rows filled from the bottom.
+const fn battery(level: usize) -> Glyph5x8 {
+	let mut rows = [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11111, 0];
+	let mut row = 0;
+	while row < level {
+		rows[5 - row] = 0b11111;
+		row += 1;
+	}
+
+	Glyph5x8::from_rows(rows)
+}
+
+/// Bars growing to the right, `level` of them filled. Empty bars only show their foot.
+const fn signal(level: usize) -> Glyph5x8 {
+	let mut rows = [0; 8];
+	let mut bar = 0;
+	while bar < 5 {
+		let column = 0b10000 >> bar;
+		let height = if bar < level { bar + 2 } else { 1 };
+		let mut row = 0;
+		while row < height {
+			rows[6 - row] |= column;
+			row += 1;
+		}
+		bar += 1;
+	}
+
+	Glyph5x8::from_rows(rows)
+}
+
+/// Icons for one line displays using the 5x10 font.
+pub mod tall {
+	use crate::glyph;
+	use crate::glyph::Glyph5x10;
+
+	/// Battery from empty (`BATTERY[0]`) to full (`BATTERY[8]`).
+	pub const BATTERY: [Glyph5x10; 9] =
+		[battery(0), battery(1), battery(2), battery(3), battery(4), battery(5), battery(6), battery(7), battery(8)];
+
+	#[rustfmt::skip]
+	pub const BELL: Glyph5x10 = glyph![
+		"..#..",
+		".###.",
+		".###.",
+		".###.",
+		".###.",
+		"#####",
+		"#####",
+		".....",
+		"..#..",
+		".....",
+	];
+
+	#[rustfmt::skip]
+	pub const LOCK: Glyph5x10 = glyph![
+		".###.",
+		"#...#",
+		"#...#",
+		"#...#",
+		"#####",
+		"##.##",
+		"##.##",
+		"##.##",
+		"#####",
+		".....",
+	];
+
+	#[rustfmt::skip]
+	pub const ARROW_UP: Glyph5x10 = glyph![
+		"..#..",
+		".###.",
+		"#.#.#",
+		"..#..",
+		"..#..",
+		"..#..",
+		"..#..",
+		"..#..",
+		"..#..",
+		".....",
+	];
+
+	#[rustfmt::skip]
+	pub const ARROW_DOWN: Glyph5x10 = glyph![
+		"..#..",
+		"..#..",
+		"..#..",
+		"..#..",
+		"..#..",
+		"..#..",
+		"#.#.#",
+		".###.",
+		"..#..",
+		".....",
+	];
+
+	#[rustfmt::skip]
+	pub const THERMOMETER: Glyph5x10 = glyph![
+		"..#..",
+		".#.#.",
+		".#.#.",
+		".#.#.",
+		".#.#.",
+		".###.",
+		".###.",
+		"#####",
+		"#####",
+		".###.",
+	];
+
+	/// Battery with a cap, an outline and `level` of its 8 rows filled from the bottom.
+	const fn battery(level: usize) -> Glyph5x10 {
+		let mut rows = [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11111];
+		let mut row = 0;
+		while row < level {
+			rows[8 - row] = 0b11111;
+			row += 1;
+		}
+
+		Glyph5x10::from_rows(rows)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::charset::{CharsetA00, CharsetA02, CharsetWithFallback, DynamicCharset, GlyphUpload};
+
+	#[test]
+	fn generated_levels() {
+		assert_eq!(BATTERY_EMPTY, glyph![".###.", "#...#", "#...#", "#...#", "#...#", "#...#", "#####", "....."]);
+		assert_eq!(BATTERY[2], glyph![".###.", "#...#", "#...#", "#...#", "#####", "#####", "#####", "....."]);
+		assert_eq!(SIGNAL[0], glyph![".....", ".....", ".....", ".....", ".....", ".....", "#####", "....."]);
+		assert_eq!(SIGNAL[3], glyph![".....", ".....", ".....", "..#..", ".##..", "###..", "#####", "....."]);
+		assert_eq!(
+			tall::BATTERY[8].into_rows(),
+			[0b01110, 0b11111, 0b11111, 0b11111, 0b11111, 0b11111, 0b11111, 0b11111, 0b11111, 0b11111]
+		);
+	}
+
+	#[test]
+	fn unicode_glyphs() {
+		for (i, &(ch, _)) in UNICODE_GLYPHS.iter().enumerate() {
+			assert!(UNICODE_GLYPHS[i + 1..].iter().all(|&(other, _)| other != ch));
+		}
+
+		let mut charset = DynamicCharset::new(CharsetA00::QUESTION_FALLBACK, UNICODE_GLYPHS);
+		assert_eq!(charset.glyph_for('♥'), Some(GlyphUpload { slot: 0, pattern: HEART.into_rows() }));
+		assert_eq!(charset.code_from_utf8_with_fallback('♥'), 0);
+		assert_eq!(charset.glyph_for('°'), Some(GlyphUpload { slot: 1, pattern: DEGREE.into_rows() }));
+
+		let mut charset = DynamicCharset::new(CharsetA02::QUESTION_FALLBACK, UNICODE_GLYPHS);
+		for ch in ['°', '↑', '↓', '♥', '🔔', '⏸'] {
+			assert_eq!(charset.glyph_for(ch), None, "{ch:?} is in the A02 ROM");
+		}
+		assert_eq!(charset.code_from_utf8_with_fallback('°'), 0xB0);
+	}
+}
@@ -0,0 +1,200 @@
+use crate::{
+	entry_mode::{CursorMode, EntryMode, ShiftMode},
+	Cursor, CursorBlink, Direction, Display, DisplayMode,
+};
+
+/// Whether [`Instruction::CursorDisplayShift`] moves the cursor or the entire display
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub enum ShiftTarget {
+	Cursor,
+	Display,
+}
+
+/// Width of the data bus, set with [`Instruction::FunctionSet`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub enum DataLength {
+	FourBit,
+	EightBit,
+}
+
+/// Number of display lines, set with [`Instruction::FunctionSet`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub enum Lines {
+	One,
+	Two,
+}
+
+/// Character font, set with [`Instruction::FunctionSet`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub enum Font {
+	Dots5x8,
+	Dots5x10,
+}
+
+/// The instruction set of the `HD44780`.
+///
+/// ```rust,ignore
+/// lcd.send(Instruction::SetDdramAddr(0x40), &mut delay)?;
+///
+/// assert_eq!(Instruction::ClearDisplay.to_byte(), 0b0000_0001);
+/// assert_eq!(Instruction::from_byte(0b1100_0000), Some(Instruction::SetDdramAddr(0x40)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub enum Instruction {
+	/// Clear the display and move the cursor to the first position.
+	ClearDisplay,
+	/// Move the cursor to the first position and unshift the display.
+	ReturnHome,
+	/// Set the cursor direction and whether the display shifts on write.
+	EntryModeSet(EntryMode),
+	/// Turn the display, cursor and cursor blinking on or off.
+	DisplayControl(DisplayMode),
+	/// Move the cursor or shift the entire display by one position.
+	CursorDisplayShift { target: ShiftTarget, direction: Direction },
+	/// Set the bus width, number of lines and font.
+	FunctionSet { data_length: DataLength, lines: Lines, font: Font },
+	/// Set the 6-bit CGRAM address. Each custom character takes up 8 addresses.
+	SetCgramAddr(u8),
+	/// Set the 7-bit DDRAM address, moving the cursor.
+	SetDdramAddr(u8),
+}
+
+impl Instruction {
+	/// Encode the instruction to the byte sent to the `HD44780`.
+	pub const fn to_byte(self) -> u8 {
+		match self {
+			Self::ClearDisplay => 0b0000_0001,
+			Self::ReturnHome => 0b0000_0010,
+			Self::EntryModeSet(entry_mode) => entry_mode.as_byte(),
+			Self::DisplayControl(display_mode) => display_mode.as_byte(),
+			Self::CursorDisplayShift { target, direction } => {
+				let target_bits = match target {
+					ShiftTarget::Cursor => 0,
+					ShiftTarget::Display => 0b0000_1000,
+				};
+
+				let direction_bits = match direction {
+					Direction::Left => 0,
+					Direction::Right => 0b0000_0100,
+				};
+
+				0b0001_0000 | target_bits | direction_bits
+			}
+			Self::FunctionSet { data_length, lines, font } => {
+				let data_length_bits = match data_length {
+					DataLength::FourBit => 0,
+					DataLength::EightBit => 0b0001_0000,
+				};
+
+				let lines_bits = match lines {
+					Lines::One => 0,
+					Lines::Two => 0b0000_1000,
+				};
+
+				let font_bits = match font {
+					Font::Dots5x8 => 0,
+					Font::Dots5x10 => 0b0000_0100,
+				};
+
+				0b0010_0000 | data_length_bits | lines_bits | font_bits
+			}
+			Self::SetCgramAddr(address) => 0b0100_0000 | (address & 0b0011_1111),
+			Self::SetDdramAddr(address) => 0b1000_0000 | (address & 0b0111_1111),
+		}
+	}
+
+	/// Decode a byte sent to the `HD44780`. Bits the instruction doesn't use are ignored,
+	/// so only `0x00` is not an instruction.
+	pub const fn from_byte(byte: u8) -> Option<Self> {
+		let instruction = match byte.leading_zeros() {
+			0 => Self::SetDdramAddr(byte & 0b0111_1111),
+			1 => Self::SetCgramAddr(byte & 0b0011_1111),
+			2 => Self::FunctionSet {
+				data_length: if byte & 0b0001_0000 != 0 { DataLength::EightBit } else { DataLength::FourBit },
+				lines: if byte & 0b0000_1000 != 0 { Lines::Two } else { Lines::One },
+				font: if byte & 0b0000_0100 != 0 { Font::Dots5x10 } else { Font::Dots5x8 },
+			},
+			3 => Self::CursorDisplayShift {
+				target: if byte & 0b0000_1000 != 0 { ShiftTarget::Display } else { ShiftTarget::Cursor },
+				direction: if byte & 0b0000_0100 != 0 { Direction::Right } else { Direction::Left },
+			},
+			4 => Self::DisplayControl(DisplayMode {
+				display: if byte & 0b0000_0100 != 0 { Display::On } else { Display::Off },
+				cursor_visibility: if byte & 0b0000_0010 != 0 { Cursor::Visible } else { Cursor::Invisible },
+				cursor_blink: if byte & 0b0000_0001 != 0 { CursorBlink::On } else { CursorBlink::Off },
+			}),
+			5 => Self::EntryModeSet(EntryMode {
+				cursor_mode: if byte & 0b0000_0010 != 0 { CursorMode::Increment } else { CursorMode::Decrement },
+				shift_mode: if byte & 0b0000_0001 != 0 { ShiftMode::Enabled } else { ShiftMode::Disabled },
+			}),
+			6 => Self::ReturnHome,
+			7 => Self::ClearDisplay,
+			_ => return None,
+		};
+
+		Some(instruction)
+	}
+}
+
+impl From<Instruction> for u8 {
+	fn from(instruction: Instruction) -> u8 {
+		instruction.to_byte()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn encoding() {
+		assert_eq!(Instruction::ClearDisplay.to_byte(), 0x01);
+		assert_eq!(Instruction::ReturnHome.to_byte(), 0x02);
+		assert_eq!(Instruction::EntryModeSet(EntryMode::default()).to_byte(), 0x06);
+		assert_eq!(Instruction::DisplayControl(DisplayMode::default()).to_byte(), 0x0F);
+		assert_eq!(
+			Instruction::CursorDisplayShift { target: ShiftTarget::Cursor, direction: Direction::Right }.to_byte(),
+			0x14
+		);
+		assert_eq!(
+			Instruction::CursorDisplayShift { target: ShiftTarget::Display, direction: Direction::Left }.to_byte(),
+			0x18
+		);
+		assert_eq!(
+			Instruction::FunctionSet { data_length: DataLength::FourBit, lines: Lines::Two, font: Font::Dots5x8 }
+				.to_byte(),
+			0x28
+		);
+		assert_eq!(Instruction::SetCgramAddr(0x08).to_byte(), 0x48);
+		assert_eq!(Instruction::SetDdramAddr(0x40).to_byte(), 0xC0);
+	}
+
+	#[test]
+	fn decoding_round_trips() {
+		assert_eq!(Instruction::from_byte(0x00), None);
+
+		for byte in 1..=255 {
+			let instruction = Instruction::from_byte(byte).unwrap();
+			assert_eq!(Instruction::from_byte(instruction.to_byte()), Some(instruction));
+		}
+	}
+
+	#[test]
+	fn decoding_ignores_unused_bits() {
+		assert_eq!(Instruction::from_byte(0x03), Some(Instruction::ReturnHome));
+		assert_eq!(
+			Instruction::from_byte(0x1F),
+			Some(Instruction::CursorDisplayShift { target: ShiftTarget::Display, direction: Direction::Right })
+		);
+	}
+}
@@ -1,6 +1,4 @@
 #![no_std]
-#![cfg_attr(feature = "async", feature(type_alias_impl_trait))]
-#![cfg_attr(feature = "async", feature(impl_trait_in_assoc_type))]
 
 use charset::CharsetWithFallback;
 use display_size::DisplaySize;
@@ -25,8 +23,39 @@ pub mod memory_map;
 pub mod display_mode;
 pub mod display_size;
 
+pub mod instruction;
+
+/// Custom characters drawn as ASCII art
+pub mod glyph;
+use glyph::{Glyph, Glyph5x8, GlyphBank};
+
+/// Icons for custom characters
+pub mod glyphs;
+
+/// Init sequences and timing of `HD44780` clones
+pub mod profile;
+
+/// Driver which queues up bus writes instead of waiting
+pub mod polling;
+
+mod with_delay;
+pub use with_delay::HD44780WithDelay;
+
+/// Extended instruction set of the `ST7032` and `ST7036` controllers
+pub mod st703x;
+
+/// Character OLEDs on `WS0010`, `US2066` and `SSD1311` controllers
+pub mod oled;
+
+/// Queue of display updates which interrupt handlers can push into
+#[cfg(feature = "critical-section")]
+pub mod queue;
+
 pub use display_mode::DisplayMode;
+pub use instruction::Instruction;
+use instruction::{Lines, ShiftTarget};
 use memory_map::DisplayMemoryMap;
+use profile::ExecutionTimes;
 use setup::blocking::DisplayOptions;
 
 /// Implementation of async functionality
@@ -39,25 +68,45 @@ pub struct HD44780<B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback> {
 	charset: C,
 	entry_mode: EntryMode,
 	display_mode: DisplayMode,
+	ddram_address: u8,
+	/// Line count of the last function set, decides where the address counter wraps.
+	lines: Lines,
+	/// Waits after each write, from the controller profile.
+	execution_times: ExecutionTimes,
+	/// Changes whenever CGRAM might no longer hold what a [`GlyphBank`] uploaded. `None` until
+	/// the first activation, so a display initialised again never matches an old bank.
+	cgram_generation: Option<u32>,
 }
 
 /// Used in the direction argument for shifting the cursor and the display
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 pub enum Direction {
 	Left,
 	Right,
 }
 
 /// Used in set_display_mode to make the parameters more clear
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 pub enum Display {
 	On,
 	Off,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 pub enum Cursor {
 	Visible,
 	Invisible,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 pub enum CursorBlink {
 	On,
 	Off,
@@ -89,7 +138,44 @@ where
 	}
 
 	pub(crate) fn new_raw(bus: B, memory_map: M, charset: C, entry_mode: EntryMode, display_mode: DisplayMode) -> Self {
-		Self { bus, memory_map, charset, entry_mode, display_mode }
+		Self {
+			bus,
+			memory_map,
+			charset,
+			entry_mode,
+			display_mode,
+			ddram_address: 0,
+			lines: Lines::Two,
+			execution_times: ExecutionTimes::default(),
+			cgram_generation: None,
+		}
+	}
+
+	/// Set the line count the init sequence left the controller in.
+	pub(crate) fn with_lines(mut self, lines: Lines) -> Self {
+		self.lines = lines;
+		self
+	}
+
+	/// Wait as long as the controller profile says after each write.
+	pub(crate) fn with_execution_times(mut self, execution_times: ExecutionTimes) -> Self {
+		self.execution_times = execution_times;
+		self
+	}
+
+	/// The same display, with `charset` describing the character ROM instead.
+	pub(crate) fn replace_charset<C2: CharsetWithFallback>(self, charset: C2) -> HD44780<B, M, C2> {
+		HD44780 {
+			bus: self.bus,
+			memory_map: self.memory_map,
+			charset,
+			entry_mode: self.entry_mode,
+			display_mode: self.display_mode,
+			ddram_address: self.ddram_address,
+			lines: self.lines,
+			execution_times: self.execution_times,
+			cgram_generation: self.cgram_generation,
+		}
 	}
 
 	/// Unshifts the display and sets the cursor position to 0
@@ -98,7 +184,7 @@ where
 	/// lcd.reset();
 	/// ```
 	pub fn reset<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), B::Error> {
-		self.write_command(0b0000_0010, delay)?;
+		self.send(Instruction::ReturnHome, delay)?;
 
 		Ok(())
 	}
@@ -111,9 +197,7 @@ where
 	pub fn set_display_mode<D: DelayNs>(&mut self, display_mode: DisplayMode, delay: &mut D) -> Result<(), B::Error> {
 		self.display_mode = display_mode;
 
-		let cmd_byte = self.display_mode.as_byte();
-
-		self.write_command(cmd_byte, delay)?;
+		self.send(Instruction::DisplayControl(self.display_mode), delay)?;
 
 		Ok(())
 	}
@@ -124,7 +208,8 @@ where
 	/// lcd.clear();
 	/// ```
 	pub fn clear<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), B::Error> {
-		self.write_command(0b0000_0001, delay)?;
+		self.send(Instruction::ClearDisplay, delay)?;
+		self.screen_cleared();
 
 		Ok(())
 	}
@@ -139,6 +224,20 @@ where
 		self.memory_map.display_size()
 	}
 
+	/// Number of cells `text` takes up when written with [write_str](#method.write_str).
+	pub fn text_width(&self, text: &str) -> usize {
+		self.charset.text_width(text)
+	}
+
+	/// Turn the backlight on or off, if the bus controls one like the I2C backpacks do
+	///
+	/// ```rust,ignore
+	/// lcd.set_backlight(false, &mut delay)?;
+	/// ```
+	pub fn set_backlight<D: DelayNs>(&mut self, on: bool, delay: &mut D) -> Result<(), B::Error> {
+		self.bus.set_backlight(on, delay)
+	}
+
 	/// If enabled, automatically scroll the display when a new
 	/// character is written to the display
 	///
@@ -148,9 +247,7 @@ where
 	pub fn set_autoscroll<D: DelayNs>(&mut self, enabled: bool, delay: &mut D) -> Result<(), B::Error> {
 		self.entry_mode.shift_mode = enabled.into();
 
-		let cmd = self.entry_mode.as_byte();
-
-		self.write_command(cmd, delay)?;
+		self.send(Instruction::EntryModeSet(self.entry_mode), delay)?;
 
 		Ok(())
 	}
@@ -159,9 +256,7 @@ where
 	pub fn set_cursor_visibility<D: DelayNs>(&mut self, visibility: Cursor, delay: &mut D) -> Result<(), B::Error> {
 		self.display_mode.cursor_visibility = visibility;
 
-		let cmd = self.display_mode.as_byte();
-
-		self.write_command(cmd, delay)?;
+		self.send(Instruction::DisplayControl(self.display_mode), delay)?;
 
 		Ok(())
 	}
@@ -170,9 +265,7 @@ where
 	pub fn set_display<D: DelayNs>(&mut self, display: Display, delay: &mut D) -> Result<(), B::Error> {
 		self.display_mode.display = display;
 
-		let cmd = self.display_mode.as_byte();
-
-		self.write_command(cmd, delay)?;
+		self.send(Instruction::DisplayControl(self.display_mode), delay)?;
 
 		Ok(())
 	}
@@ -181,9 +274,7 @@ where
 	pub fn set_cursor_blink<D: DelayNs>(&mut self, blink: CursorBlink, delay: &mut D) -> Result<(), B::Error> {
 		self.display_mode.cursor_blink = blink;
 
-		let cmd = self.display_mode.as_byte();
-
-		self.write_command(cmd, delay)?;
+		self.send(Instruction::DisplayControl(self.display_mode), delay)?;
 
 		Ok(())
 	}
@@ -200,9 +291,7 @@ where
 	pub fn set_cursor_mode<D: DelayNs>(&mut self, mode: CursorMode, delay: &mut D) -> Result<(), B::Error> {
 		self.entry_mode.cursor_mode = mode;
 
-		let cmd = self.entry_mode.as_byte();
-
-		self.write_command(cmd, delay)?;
+		self.send(Instruction::EntryModeSet(self.entry_mode), delay)?;
 
 		Ok(())
 	}
@@ -215,9 +304,7 @@ where
 	/// lcd.set_cursor_pos(40);
 	/// ```
 	pub fn set_cursor_pos<D: DelayNs>(&mut self, position: u8, delay: &mut D) -> Result<(), B::Error> {
-		let lower_7_bits = 0b0111_1111 & position;
-
-		self.write_command(0b1000_0000 | lower_7_bits, delay)
+		self.send(Instruction::SetDdramAddr(position), delay)
 	}
 
 	/// Set the cursor position
@@ -232,7 +319,7 @@ where
 			return Err(Error::Position { position, size });
 		};
 
-		self.write_command(0b1000_0000 | pos, delay)?;
+		self.send(Instruction::SetDdramAddr(pos), delay)?;
 
 		Ok(())
 	}
@@ -244,12 +331,7 @@ where
 	/// lcd.shift_cursor(Direction::Right);
 	/// ```
 	pub fn shift_cursor<D: DelayNs>(&mut self, dir: Direction, delay: &mut D) -> Result<(), B::Error> {
-		let bits = match dir {
-			Direction::Left => 0b0000_0000,
-			Direction::Right => 0b0000_0100,
-		};
-
-		self.write_command(0b0001_0000 | bits | bits, delay)?;
+		self.send(Instruction::CursorDisplayShift { target: ShiftTarget::Cursor, direction: dir }, delay)?;
 
 		Ok(())
 	}
@@ -261,12 +343,7 @@ where
 	/// lcd.shift_display(Direction::Right);
 	/// ```
 	pub fn shift_display<D: DelayNs>(&mut self, dir: Direction, delay: &mut D) -> Result<(), B::Error> {
-		let bits = match dir {
-			Direction::Left => 0b0000_0000,
-			Direction::Right => 0b0000_0100,
-		};
-
-		self.write_command(0b0001_1000 | bits, delay)?;
+		self.send(Instruction::CursorDisplayShift { target: ShiftTarget::Display, direction: dir }, delay)?;
 
 		Ok(())
 	}
@@ -280,17 +357,138 @@ where
 	/// lcd.write_char('A', &mut delay)?; // prints 'A'
 	/// ```
 	pub fn write_char<D: DelayNs>(&mut self, data: char, delay: &mut D) -> Result<(), B::Error> {
-		self.write_byte(self.charset.code_from_utf8_with_fallback(data), delay)
+		if let Some(glyph) = self.charset.glyph_for(data) {
+			self.load_glyph(glyph.slot, &Glyph5x8::from_rows(glyph.pattern), delay)?;
+		}
+
+		for &code in self.charset.codes_from_utf8_with_fallback(data).as_slice() {
+			self.write_byte(code, delay)?;
+		}
+
+		Ok(())
+	}
+
+	/// Send an instruction to the `HD44780`. The methods above cover the common cases,
+	/// this is an escape hatch for everything else.
+	///
+	/// ```rust,ignore
+	/// lcd.send(Instruction::SetDdramAddr(0x40), &mut delay)?;
+	/// ```
+	pub fn send<D: DelayNs>(&mut self, instruction: Instruction, delay: &mut D) -> Result<(), B::Error> {
+		self.write_command(instruction.to_byte(), delay)
 	}
 
+	/// Send a raw command byte to the `HD44780`. Prefer [send](#method.send) where possible.
 	pub fn write_command<D: DelayNs>(&mut self, cmd: u8, delay: &mut D) -> Result<(), B::Error> {
 		self.bus.write(cmd, false, delay)?;
+		self.ddram_address = ddram_address_after_command(self.ddram_address, cmd, self.lines);
+		self.lines = lines_after_command(self.lines, cmd);
 
 		// Wait for the command to be processed
-		delay.delay_us(100);
+		delay.delay_us(self.execution_times.command(cmd));
+		Ok(())
+	}
+
+	/// Store a 5x8 custom character in one of the eight CGRAM slots (`0..=7`). Afterwards the
+	/// character is printed by writing the slot number with [write_byte](#method.write_byte).
+	/// Only the lower 5 bits of each row are used.
+	///
+	/// ```rust,ignore
+	/// let heart = [0b00000, 0b01010, 0b11111, 0b11111, 0b01110, 0b00100, 0b00000, 0b00000];
+	/// lcd.set_custom_char(0, &heart, &mut delay)?;
+	/// lcd.write_byte(0, &mut delay)?; // prints the heart
+	/// ```
+	pub fn set_custom_char<D: DelayNs>(&mut self, slot: u8, pattern: &[u8; 8], delay: &mut D) -> Result<(), B::Error> {
+		self.upload_glyph(slot, &Glyph5x8::from_rows(*pattern), delay)
+	}
+
+	/// Store a [`Glyph5x8`] in one of the eight CGRAM slots (`0..=7`), or a
+	/// [`Glyph5x10`](glyph::Glyph5x10) in one of the four slots (`0..=3`) of a display using the
+	/// 5x10 font. A 5x10 glyph in slot `n` is printed by writing `2 * n` (or `2 * n + 1`) with
+	/// [write_byte](#method.write_byte).
+	///
+	/// ```rust,ignore
+	/// const HEART: Glyph5x8 = glyph![".....", ".#.#.", "#####", "#####", ".###.", "..#..", ".....", "....."];
+	/// lcd.upload_glyph(0, &HEART, &mut delay)?;
+	/// lcd.write_byte(0, &mut delay)?; // prints the heart
+	/// ```
+	pub fn upload_glyph<G: Glyph, D: DelayNs>(&mut self, slot: u8, glyph: &G, delay: &mut D) -> Result<(), B::Error> {
+		self.charset.cgram_overwritten(glyph::codes_of::<G>(slot));
+		self.load_glyph(slot, glyph, delay)
+	}
+
+	/// Load `glyphs` into the CGRAM slots `0..=7`, in order, skipping the slots `bank` knows to
+	/// hold the same glyph already. More than eight glyphs are rejected with
+	/// [`Error::TooManyGlyphs`]. See [`GlyphBank`] for when the bank forgets what is loaded.
+	///
+	/// ```rust,ignore
+	/// let mut bank = GlyphBank::new();
+	/// lcd.activate_glyphs(&mut bank, &[glyphs::BATTERY_FULL, glyphs::WIFI], &mut delay)?;
+	/// ```
+	pub fn activate_glyphs<D: DelayNs>(
+		&mut self,
+		bank: &mut GlyphBank,
+		glyphs: &[Glyph5x8],
+		delay: &mut D,
+	) -> Result<(), B::Error> {
+		if glyphs.len() > Glyph5x8::SLOTS as usize {
+			return Err(Error::TooManyGlyphs);
+		}
+
+		self.cgram_generation = Some(bank.sync(self.cgram_generation));
+
+		let stale = bank.take_stale(glyphs);
+		if stale == 0 {
+			return Ok(());
+		}
+
+		self.charset.cgram_overwritten(stale);
+
+		let address = self.ddram_address;
+		for (slot, glyph) in glyphs.iter().enumerate().filter(|&(slot, _)| stale & (1 << slot) != 0) {
+			self.write_glyph(slot as u8, glyph, delay)?;
+			bank.loaded_into(slot as u8, *glyph);
+		}
+
+		self.send(Instruction::SetDdramAddr(address), delay)
+	}
+
+	/// Upload `glyph` without telling the charset, which asked for it or was told already.
+	fn load_glyph<G: Glyph, D: DelayNs>(&mut self, slot: u8, glyph: &G, delay: &mut D) -> Result<(), B::Error> {
+		let address = self.ddram_address;
+		self.cgram_changed();
+
+		self.write_glyph(slot, glyph, delay)?;
+
+		// Writing the CGRAM moved the address counter, so point it back to the cursor
+		self.send(Instruction::SetDdramAddr(address), delay)
+	}
+
+	/// Write `glyph` into CGRAM, leaving the address counter behind it.
+	fn write_glyph<G: Glyph, D: DelayNs>(&mut self, slot: u8, glyph: &G, delay: &mut D) -> Result<(), B::Error> {
+		self.send(Instruction::SetCgramAddr(G::cgram_address(slot)), delay)?;
+
+		for &row in glyph.rows() {
+			self.bus.write(row & 0b0001_1111, true, delay)?;
+
+			// Wait for the data to be processed
+			delay.delay_us(self.execution_times.data());
+		}
+
 		Ok(())
 	}
 
+	/// The screen was cleared, so neither text nor glyphs on it need to be kept.
+	pub(crate) fn screen_cleared(&mut self) {
+		self.charset.screen_cleared();
+		self.cgram_changed();
+	}
+
+	/// CGRAM no longer holds what a [`GlyphBank`] remembers.
+	pub(crate) fn cgram_changed(&mut self) {
+		self.cgram_generation = self.cgram_generation.map(|generation| generation.wrapping_add(1));
+	}
+
 	/// Writes a string to the HD44780. Internally, this just prints the string byte-by-byte, so
 	/// make sure the characters in the string fit in a normal `u8`. See the documentation on
 	/// [write_byte](#method.write_byte) for more details on compatibility.
@@ -299,10 +497,30 @@ where
 	/// lcd.write_str("Hello, World!", &mut delay)?;
 	/// ```
 	pub fn write_str<D: DelayNs>(&mut self, string: &str, delay: &mut D) -> Result<(), B::Error> {
+		// Collect the codes, so buses which can send several bytes at once get to do so
+		let mut batch = [0; 32];
+		let mut len = 0;
+
 		for ch in string.chars() {
-			self.write_char(ch, delay)?;
+			if let Some(glyph) = self.charset.glyph_for(ch) {
+				self.write_bytes(&batch[..len], delay)?;
+				len = 0;
+
+				self.load_glyph(glyph.slot, &Glyph5x8::from_rows(glyph.pattern), delay)?;
+			}
+
+			let codes = self.charset.codes_from_utf8_with_fallback(ch);
+
+			if len + codes.len() > batch.len() {
+				self.write_bytes(&batch[..len], delay)?;
+				len = 0;
+			}
+
+			batch[len..len + codes.len()].copy_from_slice(&codes);
+			len += codes.len();
 		}
-		Ok(())
+
+		self.write_bytes(&batch[..len], delay)
 	}
 
 	/// Writes a sequence of bytes to the HD44780. See the documentation on the
@@ -312,9 +530,12 @@ where
 	/// lcd.write_bytes(b"Hello, World!", &mut delay)?;
 	/// ```
 	pub fn write_bytes<D: DelayNs>(&mut self, string: &[u8], delay: &mut D) -> Result<(), B::Error> {
-		for &b in string {
-			self.write_byte(b, delay)?;
+		self.bus.write_data(string, delay)?;
+
+		for _ in string {
+			self.ddram_address = next_ddram_address(self.ddram_address, self.entry_mode.cursor_mode, self.lines);
 		}
+
 		Ok(())
 	}
 
@@ -334,9 +555,10 @@ where
 	/// ```
 	pub fn write_byte<D: DelayNs>(&mut self, data: u8, delay: &mut D) -> Result<(), B::Error> {
 		self.bus.write(data, true, delay)?;
+		self.ddram_address = next_ddram_address(self.ddram_address, self.entry_mode.cursor_mode, self.lines);
 
-		// Wait for the command to be processed
-		delay.delay_us(100);
+		// Wait for the data to be processed
+		delay.delay_us(self.execution_times.data());
 
 		Ok(())
 	}
@@ -349,20 +571,90 @@ where
 	}*/
 }
 
-//impl<B> Write for HD44780<B>
-//where
-//    B: DataBus,
-//{
-//    fn write_str(&mut self, string: &str) -> Result {
-//        for c in string.chars() {
-//            self.write_char(c, delay);
-//        }
-//        Ok(())
-//    }
-//}
+/// Address counter after the cursor moved by one position. Follows the wrap-around of the
+/// DDRAM layout set by `lines`: `0x00..=0x4F` for one line, `0x00..=0x27` and `0x40..=0x67`
+/// for two lines.
+pub(crate) const fn next_ddram_address(address: u8, mode: CursorMode, lines: Lines) -> u8 {
+	match (lines, mode, address) {
+		(Lines::One, CursorMode::Increment, 0x4F) => 0x00,
+		(Lines::One, CursorMode::Decrement, 0x00) => 0x4F,
+		(Lines::Two, CursorMode::Increment, 0x27) => 0x40,
+		(Lines::Two, CursorMode::Increment, 0x67) => 0x00,
+		(Lines::Two, CursorMode::Decrement, 0x00) => 0x67,
+		(Lines::Two, CursorMode::Decrement, 0x40) => 0x27,
+		(_, CursorMode::Increment, _) => address.wrapping_add(1) & 0x7F,
+		(_, CursorMode::Decrement, _) => address.wrapping_sub(1) & 0x7F,
+	}
+}
+
+/// Address counter after the `HD44780` executed the command `cmd`.
+pub(crate) const fn ddram_address_after_command(address: u8, cmd: u8, lines: Lines) -> u8 {
+	match Instruction::from_byte(cmd) {
+		Some(Instruction::SetDdramAddr(new_address)) => new_address,
+		// Display shifts leave the address counter alone
+		Some(Instruction::CursorDisplayShift { target: ShiftTarget::Cursor, direction: Direction::Left }) => {
+			next_ddram_address(address, CursorMode::Decrement, lines)
+		}
+		Some(Instruction::CursorDisplayShift { target: ShiftTarget::Cursor, direction: Direction::Right }) => {
+			next_ddram_address(address, CursorMode::Increment, lines)
+		}
+		Some(Instruction::ClearDisplay | Instruction::ReturnHome) => 0,
+		_ => address,
+	}
+}
+
+/// Line count after the `HD44780` executed the command `cmd`.
+pub(crate) const fn lines_after_command(lines: Lines, cmd: u8) -> Lines {
+	match Instruction::from_byte(cmd) {
+		Some(Instruction::FunctionSet { lines, .. }) => lines,
+		_ => lines,
+	}
+}
 
 mod sealed {
 	/// Marker used to restrict access to internal sealed trait funcitons.
 	#[doc(hidden)]
 	pub struct Internal;
 }
+
+#[cfg(test)]
+mod test_util;
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn ddram_address_wraps_between_lines() {
+		assert_eq!(next_ddram_address(0x05, CursorMode::Increment, Lines::Two), 0x06);
+		assert_eq!(next_ddram_address(0x27, CursorMode::Increment, Lines::Two), 0x40);
+		assert_eq!(next_ddram_address(0x67, CursorMode::Increment, Lines::Two), 0x00);
+		assert_eq!(next_ddram_address(0x40, CursorMode::Decrement, Lines::Two), 0x27);
+		assert_eq!(next_ddram_address(0x00, CursorMode::Decrement, Lines::Two), 0x67);
+	}
+
+	#[test]
+	fn ddram_address_wraps_on_one_line() {
+		assert_eq!(next_ddram_address(0x27, CursorMode::Increment, Lines::One), 0x28);
+		assert_eq!(next_ddram_address(0x4F, CursorMode::Increment, Lines::One), 0x00);
+		assert_eq!(next_ddram_address(0x00, CursorMode::Decrement, Lines::One), 0x4F);
+	}
+
+	#[test]
+	fn ddram_address_follows_commands() {
+		assert_eq!(ddram_address_after_command(0x12, 0b1100_0011, Lines::Two), 0x43);
+		assert_eq!(ddram_address_after_command(0x12, 0b0000_0001, Lines::Two), 0x00);
+		assert_eq!(ddram_address_after_command(0x12, 0b0000_0010, Lines::Two), 0x00);
+		assert_eq!(ddram_address_after_command(0x12, 0b0001_0100, Lines::Two), 0x13);
+		assert_eq!(ddram_address_after_command(0x12, 0b0001_0000, Lines::Two), 0x11);
+		assert_eq!(ddram_address_after_command(0x12, 0b0001_1100, Lines::Two), 0x12);
+		assert_eq!(ddram_address_after_command(0x12, 0b0100_1000, Lines::Two), 0x12);
+	}
+
+	#[test]
+	fn lines_follow_function_set() {
+		assert_eq!(lines_after_command(Lines::Two, 0b0011_0000), Lines::One);
+		assert_eq!(lines_after_command(Lines::One, 0b0010_1000), Lines::Two);
+		assert_eq!(lines_after_command(Lines::One, 0b0000_0001), Lines::One);
+	}
+}
@@ -69,7 +69,7 @@ impl<const W: u8, const H: u8, const L: u8> DisplayMemoryMap for StandardMemoryM
 }
 
 const fn scrollable_margin(w: u8, h: u8, l: u8) -> u8 {
-	l - w * ((h + 1) / 2)
+	l - w * h.div_ceil(2)
 }
 
 /// Memory Map for single-row displays that are using one line / contiguous memory.
@@ -1,20 +1,45 @@
-use core::future::Future;
 use embedded_hal_async::delay::DelayNs;
 
 pub use crate::bus::I2CBus;
 pub use crate::bus::{EightBitBus, EightBitBusPins};
 pub use crate::bus::{FourBitBus, FourBitBusPins};
+pub use crate::bus::{ParallelPort, PortBus, PortBusPins};
+pub use crate::bus::{SerialControlBus, SpiError};
 
 use crate::error::Result;
+use crate::instruction::DataLength;
 
+// The returned futures are not required to be `Send`, as embedded executors usually run
+// on a single thread.
+#[allow(async_fn_in_trait)]
 pub trait DataBus {
 	type Error: core::fmt::Debug;
 
-	type WriteFuture<'a, D: 'a + DelayNs>: Future<Output = Result<(), Self::Error>>
-	where
-		Self: 'a;
+	async fn write<D: DelayNs>(&mut self, byte: u8, data: bool, delay: &mut D) -> Result<(), Self::Error>;
 
-	fn write<'a, D: DelayNs + 'a>(&'a mut self, byte: u8, data: bool, delay: &'a mut D) -> Self::WriteFuture<'a, D>;
+	/// Write several data bytes in a row, waiting for each of them to be processed.
+	///
+	/// Buses which can transfer many bytes at once override this.
+	async fn write_data<D: DelayNs>(&mut self, bytes: &[u8], delay: &mut D) -> Result<(), Self::Error> {
+		for &byte in bytes {
+			self.write(byte, true, delay).await?;
+
+			// Wait for the data to be processed
+			delay.delay_us(100).await;
+		}
+
+		Ok(())
+	}
+
+	/// Turn the backlight on or off. Does nothing on buses without control over a backlight.
+	async fn set_backlight<D: DelayNs>(&mut self, _on: bool, _delay: &mut D) -> Result<(), Self::Error> {
+		Ok(())
+	}
+
+	/// Width of the data path to the `HD44780`, which function set instructions have to keep.
+	fn data_length(&self) -> DataLength {
+		DataLength::FourBit
+	}
 
 	// TODO
 	// fn read(...)
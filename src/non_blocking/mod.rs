@@ -9,9 +9,12 @@ use bus::DataBus;
 use crate::charset::CharsetWithFallback;
 use crate::display_size::DisplaySize;
 pub use crate::error;
+use crate::glyph::{self, Glyph, Glyph5x8, GlyphBank};
 use crate::memory_map::DisplayMemoryMap;
+use crate::profile::ExecutionTimes;
 use crate::sealed::Internal;
 use crate::setup::non_blocking::DisplayOptions;
+use crate::{ddram_address_after_command, lines_after_command, next_ddram_address};
 use error::Result;
 
 pub use crate::entry_mode;
@@ -28,6 +31,14 @@ pub struct HD44780<B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback> {
 	charset: C,
 	entry_mode: EntryMode,
 	display_mode: DisplayMode,
+	ddram_address: u8,
+	/// Line count of the last function set, decides where the address counter wraps.
+	lines: Lines,
+	/// Waits after each write, from the controller profile.
+	execution_times: ExecutionTimes,
+	/// Changes whenever CGRAM might no longer hold what a [`GlyphBank`] uploaded. `None` until
+	/// the first activation, so a display initialised again never matches an old bank.
+	cgram_generation: Option<u32>,
 }
 
 pub use crate::Cursor;
@@ -35,6 +46,24 @@ pub use crate::CursorBlink;
 pub use crate::Direction;
 pub use crate::Display;
 
+pub use crate::instruction;
+
+pub use instruction::Instruction;
+use instruction::{Lines, ShiftTarget};
+
+mod with_delay;
+pub use with_delay::HD44780WithDelay;
+
+/// Extended instruction set of the `ST7032` and `ST7036` controllers
+pub mod st703x;
+
+/// Character OLEDs on `WS0010`, `US2066` and `SSD1311` controllers
+pub mod oled;
+
+/// Display shared between tasks, which each get a region of it
+#[cfg(feature = "embassy-sync")]
+pub mod shared;
+
 use self::error::Error;
 
 impl<B, M, C> HD44780<B, M, C>
@@ -66,7 +95,44 @@ where
 	}
 
 	pub(crate) fn new_raw(bus: B, memory_map: M, charset: C, entry_mode: EntryMode, display_mode: DisplayMode) -> Self {
-		Self { bus, memory_map, charset, entry_mode, display_mode }
+		Self {
+			bus,
+			memory_map,
+			charset,
+			entry_mode,
+			display_mode,
+			ddram_address: 0,
+			lines: Lines::Two,
+			execution_times: ExecutionTimes::default(),
+			cgram_generation: None,
+		}
+	}
+
+	/// Set the line count the init sequence left the controller in.
+	pub(crate) fn with_lines(mut self, lines: Lines) -> Self {
+		self.lines = lines;
+		self
+	}
+
+	/// Wait as long as the controller profile says after each write.
+	pub(crate) fn with_execution_times(mut self, execution_times: ExecutionTimes) -> Self {
+		self.execution_times = execution_times;
+		self
+	}
+
+	/// The same display, with `charset` describing the character ROM instead.
+	pub(crate) fn replace_charset<C2: CharsetWithFallback>(self, charset: C2) -> HD44780<B, M, C2> {
+		HD44780 {
+			bus: self.bus,
+			memory_map: self.memory_map,
+			charset,
+			entry_mode: self.entry_mode,
+			display_mode: self.display_mode,
+			ddram_address: self.ddram_address,
+			lines: self.lines,
+			execution_times: self.execution_times,
+			cgram_generation: self.cgram_generation,
+		}
 	}
 
 	/// Unshifts the display and sets the cursor position to 0
@@ -74,8 +140,8 @@ where
 	/// ```rust,ignore
 	/// lcd.reset().await?;
 	/// ```
-	pub async fn reset<'a, D: DelayNs>(&mut self, delay: &'a mut D) -> Result<(), B::Error> {
-		self.write_command(0b0000_0010, delay).await
+	pub async fn reset<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), B::Error> {
+		self.send(Instruction::ReturnHome, delay).await
 	}
 
 	/// Set if the display should be on, if the cursor should be
@@ -83,16 +149,14 @@ where
 	///
 	/// Note: This is equivilent to calling all of the other relavent
 	/// methods however this operation does it all in one go to the `HD44780`
-	pub async fn set_display_mode<'a, D: DelayNs>(
+	pub async fn set_display_mode<D: DelayNs>(
 		&mut self,
 		display_mode: DisplayMode,
-		delay: &'a mut D,
+		delay: &mut D,
 	) -> Result<(), B::Error> {
 		self.display_mode = display_mode;
 
-		let cmd_byte = self.display_mode.as_byte();
-
-		self.write_command(cmd_byte, delay).await?;
+		self.send(Instruction::DisplayControl(self.display_mode), delay).await?;
 
 		Ok(())
 	}
@@ -102,8 +166,11 @@ where
 	/// ```rust,ignore
 	/// lcd.clear().await?;
 	/// ```
-	pub async fn clear<'a, D: DelayNs>(&mut self, delay: &'a mut D) -> Result<(), B::Error> {
-		self.write_command(0b0000_0001, delay).await
+	pub async fn clear<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), B::Error> {
+		self.send(Instruction::ClearDisplay, delay).await?;
+		self.screen_cleared();
+
+		Ok(())
 	}
 
 	/// Get the memory map information for this display.
@@ -116,53 +183,55 @@ where
 		self.memory_map.display_size()
 	}
 
+	/// Number of cells `text` takes up when written with [write_str](#method.write_str).
+	pub fn text_width(&self, text: &str) -> usize {
+		self.charset.text_width(text)
+	}
+
+	/// Turn the backlight on or off, if the bus controls one like the I2C backpacks do
+	///
+	/// ```rust,ignore
+	/// lcd.set_backlight(false, &mut delay).await?;
+	/// ```
+	pub async fn set_backlight<D: DelayNs>(&mut self, on: bool, delay: &mut D) -> Result<(), B::Error> {
+		self.bus.set_backlight(on, delay).await
+	}
+
 	/// If enabled, automatically scroll the display when a new
 	/// character is written to the display
 	///
 	/// ```rust,ignore
 	/// lcd.set_autoscroll(true).await?;
 	/// ```
-	pub async fn set_autoscroll<'a, D: DelayNs>(&mut self, enabled: bool, delay: &'a mut D) -> Result<(), B::Error> {
+	pub async fn set_autoscroll<D: DelayNs>(&mut self, enabled: bool, delay: &mut D) -> Result<(), B::Error> {
 		self.entry_mode.shift_mode = enabled.into();
 
-		let cmd = self.entry_mode.as_byte();
-
-		self.write_command(cmd, delay).await
+		self.send(Instruction::EntryModeSet(self.entry_mode), delay).await
 	}
 
 	/// Set if the cursor should be visible
-	pub async fn set_cursor_visibility<'a, D: DelayNs>(
+	pub async fn set_cursor_visibility<D: DelayNs>(
 		&mut self,
 		visibility: Cursor,
-		delay: &'a mut D,
+		delay: &mut D,
 	) -> Result<(), B::Error> {
 		self.display_mode.cursor_visibility = visibility;
 
-		let cmd = self.display_mode.as_byte();
-
-		self.write_command(cmd, delay).await
+		self.send(Instruction::DisplayControl(self.display_mode), delay).await
 	}
 
 	/// Set if the characters on the display should be visible
-	pub async fn set_display<'a, D: DelayNs>(&mut self, display: Display, delay: &'a mut D) -> Result<(), B::Error> {
+	pub async fn set_display<D: DelayNs>(&mut self, display: Display, delay: &mut D) -> Result<(), B::Error> {
 		self.display_mode.display = display;
 
-		let cmd = self.display_mode.as_byte();
-
-		self.write_command(cmd, delay).await
+		self.send(Instruction::DisplayControl(self.display_mode), delay).await
 	}
 
 	/// Set if the cursor should blink
-	pub async fn set_cursor_blink<'a, D: DelayNs>(
-		&mut self,
-		blink: CursorBlink,
-		delay: &'a mut D,
-	) -> Result<(), B::Error> {
+	pub async fn set_cursor_blink<D: DelayNs>(&mut self, blink: CursorBlink, delay: &mut D) -> Result<(), B::Error> {
 		self.display_mode.cursor_blink = blink;
 
-		let cmd = self.display_mode.as_byte();
-
-		self.write_command(cmd, delay).await
+		self.send(Instruction::DisplayControl(self.display_mode), delay).await
 	}
 
 	/// Set which way the cursor will move when a new character is written
@@ -174,16 +243,10 @@ where
 	/// // Move left when a new character is written
 	/// lcd.set_cursor_mode(CursorMode::Left).await?;
 	/// ```
-	pub async fn set_cursor_mode<'a, D: DelayNs>(
-		&mut self,
-		mode: CursorMode,
-		delay: &'a mut D,
-	) -> Result<(), B::Error> {
+	pub async fn set_cursor_mode<D: DelayNs>(&mut self, mode: CursorMode, delay: &mut D) -> Result<(), B::Error> {
 		self.entry_mode.cursor_mode = mode;
 
-		let cmd = self.entry_mode.as_byte();
-
-		self.write_command(cmd, delay).await
+		self.send(Instruction::EntryModeSet(self.entry_mode), delay).await
 	}
 
 	/// Set the cursor position
@@ -192,10 +255,8 @@ where
 	/// // Move to line 2
 	/// lcd.set_cursor_pos(40).await?;
 	/// ```
-	pub async fn set_cursor_pos<'a, D: DelayNs>(&mut self, position: u8, delay: &'a mut D) -> Result<(), B::Error> {
-		let lower_7_bits = 0b0111_1111 & position;
-
-		self.write_command(0b1000_0000 | lower_7_bits, delay).await
+	pub async fn set_cursor_pos<D: DelayNs>(&mut self, position: u8, delay: &mut D) -> Result<(), B::Error> {
+		self.send(Instruction::SetDdramAddr(position), delay).await
 	}
 
 	/// Set the cursor position
@@ -210,7 +271,7 @@ where
 			return Err(Error::Position { position, size });
 		};
 
-		self.write_command(0b1000_0000 | pos, delay).await
+		self.send(Instruction::SetDdramAddr(pos), delay).await
 	}
 
 	/// Shift just the cursor to the left or the right
@@ -219,13 +280,8 @@ where
 	/// lcd.shift_cursor(Direction::Left).await?;
 	/// lcd.shift_cursor(Direction::Right).await?;
 	/// ```
-	pub async fn shift_cursor<'a, D: DelayNs>(&mut self, dir: Direction, delay: &'a mut D) -> Result<(), B::Error> {
-		let bits = match dir {
-			Direction::Left => 0b0000_0000,
-			Direction::Right => 0b0000_0100,
-		};
-
-		self.write_command(0b0001_0000 | bits | bits, delay).await
+	pub async fn shift_cursor<D: DelayNs>(&mut self, dir: Direction, delay: &mut D) -> Result<(), B::Error> {
+		self.send(Instruction::CursorDisplayShift { target: ShiftTarget::Cursor, direction: dir }, delay).await
 	}
 
 	/// Shift the entire display to the left or the right
@@ -234,13 +290,8 @@ where
 	/// lcd.shift_display(Direction::Left).await?;
 	/// lcd.shift_display(Direction::Right).await?;
 	/// ```
-	pub async fn shift_display<'a, D: DelayNs>(&mut self, dir: Direction, delay: &'a mut D) -> Result<(), B::Error> {
-		let bits = match dir {
-			Direction::Left => 0b0000_0000,
-			Direction::Right => 0b0000_0100,
-		};
-
-		self.write_command(0b0001_1000 | bits, delay).await
+	pub async fn shift_display<D: DelayNs>(&mut self, dir: Direction, delay: &mut D) -> Result<(), B::Error> {
+		self.send(Instruction::CursorDisplayShift { target: ShiftTarget::Display, direction: dir }, delay).await
 	}
 
 	/// Write a single character to the `HD44780`. This `char` just gets downcast to a `u8`
@@ -249,44 +300,189 @@ where
 	/// See the documentation on that function for more details about compatibility.
 	///
 	/// ```rust,ignore
-	/// lcd.write_char('A', &'a mut DelayUs).await?; // prints 'A'
+	/// lcd.write_char('A', &mut DelayUs).await?; // prints 'A'
 	/// ```
-	pub async fn write_char<'a, D: DelayNs>(&mut self, data: char, delay: &'a mut D) -> Result<(), B::Error> {
-		self.write_byte(self.charset.code_from_utf8_with_fallback(data), delay).await
+	pub async fn write_char<D: DelayNs>(&mut self, data: char, delay: &mut D) -> Result<(), B::Error> {
+		if let Some(glyph) = self.charset.glyph_for(data) {
+			self.load_glyph(glyph.slot, &Glyph5x8::from_rows(glyph.pattern), delay).await?;
+		}
+
+		for &code in self.charset.codes_from_utf8_with_fallback(data).as_slice() {
+			self.write_byte(code, delay).await?;
+		}
+
+		Ok(())
 	}
 
-	async fn write_command<'a, D: DelayNs>(&mut self, cmd: u8, delay: &'a mut D) -> Result<(), B::Error> {
+	/// Send an instruction to the `HD44780`. The methods above cover the common cases,
+	/// this is an escape hatch for everything else.
+	///
+	/// ```rust,ignore
+	/// lcd.send(Instruction::SetDdramAddr(0x40), &mut delay).await?;
+	/// ```
+	pub async fn send<D: DelayNs>(&mut self, instruction: Instruction, delay: &mut D) -> Result<(), B::Error> {
+		self.write_command(instruction.to_byte(), delay).await
+	}
+
+	async fn write_command<D: DelayNs>(&mut self, cmd: u8, delay: &mut D) -> Result<(), B::Error> {
 		self.bus.write(cmd, false, delay).await?;
+		self.ddram_address = ddram_address_after_command(self.ddram_address, cmd, self.lines);
+		self.lines = lines_after_command(self.lines, cmd);
 
 		// Wait for the command to be processed
-		delay.delay_us(100).await;
+		delay.delay_us(self.execution_times.command(cmd)).await;
 		Ok(())
 	}
 
+	/// Store a 5x8 custom character in one of the eight CGRAM slots (`0..=7`). Afterwards the
+	/// character is printed by writing the slot number with [write_byte](#method.write_byte).
+	/// Only the lower 5 bits of each row are used.
+	///
+	/// ```rust,ignore
+	/// let heart = [0b00000, 0b01010, 0b11111, 0b11111, 0b01110, 0b00100, 0b00000, 0b00000];
+	/// lcd.set_custom_char(0, &heart, &mut delay).await?;
+	/// lcd.write_byte(0, &mut delay).await?; // prints the heart
+	/// ```
+	pub async fn set_custom_char<D: DelayNs>(
+		&mut self,
+		slot: u8,
+		pattern: &[u8; 8],
+		delay: &mut D,
+	) -> Result<(), B::Error> {
+		self.upload_glyph(slot, &Glyph5x8::from_rows(*pattern), delay).await
+	}
+
+	/// Store a [`Glyph5x8`] or [`Glyph5x10`](crate::glyph::Glyph5x10) in CGRAM. See
+	/// [HD44780::upload_glyph](crate::HD44780::upload_glyph).
+	///
+	/// ```rust,ignore
+	/// const HEART: Glyph5x8 = glyph![".....", ".#.#.", "#####", "#####", ".###.", "..#..", ".....", "....."];
+	/// lcd.upload_glyph(0, &HEART, &mut delay).await?;
+	/// lcd.write_byte(0, &mut delay).await?; // prints the heart
+	/// ```
+	pub async fn upload_glyph<G: Glyph, D: DelayNs>(
+		&mut self,
+		slot: u8,
+		glyph: &G,
+		delay: &mut D,
+	) -> Result<(), B::Error> {
+		self.charset.cgram_overwritten(glyph::codes_of::<G>(slot));
+		self.load_glyph(slot, glyph, delay).await
+	}
+
+	/// Load `glyphs` into CGRAM, skipping the slots `bank` knows to hold them already. See
+	/// [HD44780::activate_glyphs](crate::HD44780::activate_glyphs).
+	pub async fn activate_glyphs<D: DelayNs>(
+		&mut self,
+		bank: &mut GlyphBank,
+		glyphs: &[Glyph5x8],
+		delay: &mut D,
+	) -> Result<(), B::Error> {
+		if glyphs.len() > Glyph5x8::SLOTS as usize {
+			return Err(Error::TooManyGlyphs);
+		}
+
+		self.cgram_generation = Some(bank.sync(self.cgram_generation));
+
+		let stale = bank.take_stale(glyphs);
+		if stale == 0 {
+			return Ok(());
+		}
+
+		self.charset.cgram_overwritten(stale);
+
+		let address = self.ddram_address;
+		for (slot, glyph) in glyphs.iter().enumerate().filter(|&(slot, _)| stale & (1 << slot) != 0) {
+			self.write_glyph(slot as u8, glyph, delay).await?;
+			bank.loaded_into(slot as u8, *glyph);
+		}
+
+		self.send(Instruction::SetDdramAddr(address), delay).await
+	}
+
+	/// Upload `glyph` without telling the charset, which asked for it or was told already.
+	async fn load_glyph<G: Glyph, D: DelayNs>(&mut self, slot: u8, glyph: &G, delay: &mut D) -> Result<(), B::Error> {
+		let address = self.ddram_address;
+		self.cgram_changed();
+
+		self.write_glyph(slot, glyph, delay).await?;
+
+		// Writing the CGRAM moved the address counter, so point it back to the cursor
+		self.send(Instruction::SetDdramAddr(address), delay).await
+	}
+
+	/// Write `glyph` into CGRAM, leaving the address counter behind it.
+	async fn write_glyph<G: Glyph, D: DelayNs>(&mut self, slot: u8, glyph: &G, delay: &mut D) -> Result<(), B::Error> {
+		self.send(Instruction::SetCgramAddr(G::cgram_address(slot)), delay).await?;
+
+		for &row in glyph.rows() {
+			self.bus.write(row & 0b0001_1111, true, delay).await?;
+
+			// Wait for the data to be processed
+			delay.delay_us(self.execution_times.data()).await;
+		}
+
+		Ok(())
+	}
+
+	/// The screen was cleared, so neither text nor glyphs on it need to be kept.
+	pub(crate) fn screen_cleared(&mut self) {
+		self.charset.screen_cleared();
+		self.cgram_changed();
+	}
+
+	/// CGRAM no longer holds what a [`GlyphBank`] remembers.
+	pub(crate) fn cgram_changed(&mut self) {
+		self.cgram_generation = self.cgram_generation.map(|generation| generation.wrapping_add(1));
+	}
+
 	/// Writes a string to the HD44780. Internally, this just prints the string byte-by-byte, so
 	/// make sure the characters in the string fit in a normal `u8`. See the documentation on
 	/// [write_byte](#method.write_byte) for more details on compatibility.
 	///
 	/// ```rust,ignore
-	/// lcd.write_str("Hello, World!", &'a mut DelayUs).await?;
+	/// lcd.write_str("Hello, World!", &mut DelayUs).await?;
 	/// ```
-	pub async fn write_str<'a, D: DelayNs>(&mut self, string: &str, delay: &'a mut D) -> Result<(), B::Error> {
+	pub async fn write_str<D: DelayNs>(&mut self, string: &str, delay: &mut D) -> Result<(), B::Error> {
+		// Collect the codes, so buses which can send several bytes at once get to do so
+		let mut batch = [0; 32];
+		let mut len = 0;
+
 		for ch in string.chars() {
-			self.write_char(ch, delay).await?;
+			if let Some(glyph) = self.charset.glyph_for(ch) {
+				self.write_bytes(&batch[..len], delay).await?;
+				len = 0;
+
+				self.load_glyph(glyph.slot, &Glyph5x8::from_rows(glyph.pattern), delay).await?;
+			}
+
+			let codes = self.charset.codes_from_utf8_with_fallback(ch);
+
+			if len + codes.len() > batch.len() {
+				self.write_bytes(&batch[..len], delay).await?;
+				len = 0;
+			}
+
+			batch[len..len + codes.len()].copy_from_slice(&codes);
+			len += codes.len();
 		}
-		Ok(())
+
+		self.write_bytes(&batch[..len], delay).await
 	}
 
 	/// Writes a sequence of bytes to the HD44780. See the documentation on the
 	/// [write_byte](#method.write_byte) function for more details about compatibility.
 	///
 	/// ```rust,ignore
-	/// lcd.write_bytes(b"Hello, World!", &'a mut DelayUs).await?;
+	/// lcd.write_bytes(b"Hello, World!", &mut DelayUs).await?;
 	/// ```
-	pub async fn write_bytes<'a, D: DelayNs>(&mut self, string: &[u8], delay: &'a mut D) -> Result<(), B::Error> {
-		for &b in string {
-			self.write_byte(b, delay).await?;
+	pub async fn write_bytes<D: DelayNs>(&mut self, string: &[u8], delay: &mut D) -> Result<(), B::Error> {
+		self.bus.write_data(string, delay).await?;
+
+		for _ in string {
+			self.ddram_address = next_ddram_address(self.ddram_address, self.entry_mode.cursor_mode, self.lines);
 		}
+
 		Ok(())
 	}
 
@@ -299,16 +495,17 @@ where
 	/// More information can be found in the Hitachi datasheets for the HD44780.
 	///
 	/// ```rust,ignore
-	/// lcd.write_byte(b'A', &'a mut DelayUs)?; // prints 'A'
-	/// lcd.write_byte(b'\\', &'a mut DelayUs)?; // usually prints ¥
-	/// lcd.write_byte(b'~', &'a mut DelayUs)?; // usually prints 🡢
-	/// lcd.write_byte(b'\x7f', &'a mut DelayUs)?; // usually prints 🡠
+	/// lcd.write_byte(b'A', &mut DelayUs)?; // prints 'A'
+	/// lcd.write_byte(b'\\', &mut DelayUs)?; // usually prints ¥
+	/// lcd.write_byte(b'~', &mut DelayUs)?; // usually prints 🡢
+	/// lcd.write_byte(b'\x7f', &mut DelayUs)?; // usually prints 🡠
 	/// ```
-	pub async fn write_byte<'a, D: DelayNs>(&mut self, data: u8, delay: &'a mut D) -> Result<(), B::Error> {
+	pub async fn write_byte<D: DelayNs>(&mut self, data: u8, delay: &mut D) -> Result<(), B::Error> {
 		self.bus.write(data, true, delay).await?;
+		self.ddram_address = next_ddram_address(self.ddram_address, self.entry_mode.cursor_mode, self.lines);
 
-		// Wait for the command to be processed
-		delay.delay_us(100).await;
+		// Wait for the data to be processed
+		delay.delay_us(self.execution_times.data()).await;
 
 		Ok(())
 	}
@@ -332,3 +529,37 @@ where
 //        Ok(())
 //    }
 //}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::bus::FourBitBusPins;
+	use crate::memory_map::MemoryMap1602;
+	use crate::setup::DisplayOptions4Bit;
+	use crate::test_util::{block_on, NoDelay, Pin};
+
+	#[test]
+	fn borrowed_pins() {
+		let (mut rs, mut en, mut d4, mut d5, mut d6, mut d7) =
+			(Pin(false), Pin(false), Pin(false), Pin(false), Pin(false), Pin(false));
+		let mut delay = NoDelay;
+
+		let options = DisplayOptions4Bit::new(MemoryMap1602::new()).with_pins(FourBitBusPins {
+			rs: &mut rs,
+			en: &mut en,
+			d4: &mut d4,
+			d5: &mut d5,
+			d6: &mut d6,
+			d7: &mut d7,
+		});
+
+		let Ok(mut lcd) = block_on(HD44780::new(options, &mut delay)) else {
+			panic!("setting up the display failed");
+		};
+		block_on(lcd.write_str("Hi", &mut delay)).unwrap();
+		lcd.destroy();
+
+		// The last nibble of 'i' (0x69) is still on the data pins
+		assert!(d4.0 && !d5.0 && !d6.0 && d7.0);
+	}
+}
@@ -0,0 +1,238 @@
+use core::ops::{Deref, DerefMut};
+
+use embedded_hal_async::delay::DelayNs;
+
+use super::{bus::DataBus, HD44780};
+use crate::{
+	charset::CharsetWithFallback,
+	error::{Error, Result},
+	memory_map::DisplayMemoryMap,
+	oled::{FontTable, OledConfig, State, Step, Steps, GRAPHIC_ROWS, GRAPHIC_WIDTH},
+};
+
+#[cfg(feature = "graphics")]
+use crate::oled::FrameBuffer;
+
+/// [`HD44780`] on a `WS0010`, `US2066` or `SSD1311` character OLED controller. Adds font table
+/// selection and power control, OLEDs have no backlight.
+///
+/// All methods of [`HD44780`] are available through `Deref`:
+///
+/// ```rust,ignore
+/// let config = OledConfig::new(Controller::Us2066);
+/// let lcd = HD44780::new(options.with_profile(OledProfile::new(config)), &mut delay).await?;
+/// let mut lcd = Oled::new(lcd, config);
+///
+/// lcd.write_str("Hello", &mut delay).await?;
+/// lcd.set_power(false, &mut delay).await?;
+/// ```
+pub struct Oled<B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback> {
+	lcd: HD44780<B, M, C>,
+	state: State,
+}
+
+impl<B, M, C> Oled<B, M, C>
+where
+	B: DataBus,
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+{
+	/// Wrap a display set up with [`HD44780::new`] and an
+	/// [`OledProfile`](crate::oled::OledProfile) of the same `config`.
+	///
+	/// The charset of the display should match [`OledConfig::font_table`].
+	pub fn new(lcd: HD44780<B, M, C>, config: OledConfig) -> Self {
+		let state = State { config, data_length: lcd.bus.data_length(), four_lines: lcd.display_size().get().1 > 2 };
+
+		Self { lcd, state }
+	}
+
+	pub fn into_inner(self) -> HD44780<B, M, C> {
+		self.lcd
+	}
+
+	pub fn config(&self) -> &OledConfig {
+		&self.state.config
+	}
+
+	/// Switch to another character ROM, with `charset` matching it. Text already on the display
+	/// changes as well. Hands the display back unchanged if that fails.
+	pub async fn set_font_table<C2: CharsetWithFallback, D: DelayNs>(
+		mut self,
+		font_table: FontTable,
+		charset: C2,
+		delay: &mut D,
+	) -> FontTableResult<B, M, C, C2> {
+		let state = State { config: OledConfig { font_table, ..self.state.config }, ..self.state };
+		if let Err(error) = self.run(&state.font_table(), delay).await {
+			return Err((self, error));
+		}
+
+		Ok(Oled { lcd: self.lcd.replace_charset(charset), state })
+	}
+
+	/// Power the panel up or down. The display contents are kept while it is off.
+	pub async fn set_power<D: DelayNs>(&mut self, on: bool, delay: &mut D) -> Result<(), B::Error> {
+		let steps = self.state.power(on, self.lcd.display_mode);
+		self.run(&steps, delay).await
+	}
+
+	/// Switch a `WS0010` to its 100x16 pixel graphic mode, which starts out blank. Hands the
+	/// display back if that fails, with [`Error::Unsupported`] on a `US2066`.
+	pub async fn into_graphics<D: DelayNs>(mut self, delay: &mut D) -> GraphicsResult<B, M, C> {
+		let steps = match self.state.graphic_mode(true, self.lcd.entry_mode, self.lcd.display_mode) {
+			Ok(steps) => steps,
+			Err(error) => return Err((self, error)),
+		};
+		if let Err(error) = self.run(&steps, delay).await {
+			return Err((self, error));
+		}
+
+		let mut graphics = OledGraphics { oled: self };
+		match graphics.clear(delay).await {
+			Ok(()) => Ok(graphics),
+			Err(error) => Err((graphics.oled, error)),
+		}
+	}
+
+	/// OLED commands share their encoding with other instructions, so they bypass the
+	/// address tracking of [`HD44780::write_command`].
+	async fn run<D: DelayNs>(&mut self, steps: &Steps, delay: &mut D) -> Result<(), B::Error> {
+		for step in steps.iter() {
+			match step {
+				Step::Command(byte) => {
+					self.lcd.bus.write(byte, false, delay).await?;
+
+					// Wait for the command to be processed
+					delay.delay_us(self.lcd.execution_times.command(byte)).await;
+				}
+				Step::Data(byte) => {
+					self.lcd.bus.write(byte, true, delay).await?;
+					delay.delay_us(self.lcd.execution_times.data()).await;
+				}
+				Step::Wait(ms) => delay.delay_ms(ms).await,
+			}
+		}
+
+		Ok(())
+	}
+}
+
+type GraphicsResult<B, M, C> =
+	core::result::Result<OledGraphics<B, M, C>, (Oled<B, M, C>, Error<<B as DataBus>::Error>)>;
+
+type FontTableResult<B, M, C, C2> = core::result::Result<Oled<B, M, C2>, (Oled<B, M, C>, Error<<B as DataBus>::Error>)>;
+
+type TextResult<B, M, C> = core::result::Result<Oled<B, M, C>, (OledGraphics<B, M, C>, Error<<B as DataBus>::Error>)>;
+
+/// `WS0010` in graphic mode, writing bytes of 8 vertical pixels (top pixel in the least
+/// significant bit) instead of characters. Uses the same bus as character mode.
+///
+/// ```rust,ignore
+/// let mut graphics = lcd.into_graphics(&mut delay).await.map_err(|(_, e)| e)?;
+/// graphics.blit(10, 0, &[0xFF, 0x81, 0xFF], &mut delay).await?;
+///
+/// let lcd = graphics.into_text(&mut delay).await.map_err(|(_, e)| e)?;
+/// ```
+pub struct OledGraphics<B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback> {
+	oled: Oled<B, M, C>,
+}
+
+impl<B, M, C> OledGraphics<B, M, C>
+where
+	B: DataBus,
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+{
+	/// Write column bytes to `row` (0 or 1), starting at `column`.
+	pub async fn blit<D: DelayNs>(&mut self, column: u8, row: u8, bytes: &[u8], delay: &mut D) -> Result<(), B::Error> {
+		let steps = State::blit_address(column, row, bytes.len())?;
+		self.oled.run(&steps, delay).await?;
+		self.oled.lcd.bus.write_data(bytes, delay).await
+	}
+
+	/// Turn all pixels off.
+	pub async fn clear<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), B::Error> {
+		for row in 0..GRAPHIC_ROWS {
+			self.blit(0, row, &[0; GRAPHIC_WIDTH as usize], delay).await?;
+		}
+
+		Ok(())
+	}
+
+	/// Send all pixels of `frame`.
+	#[cfg(feature = "graphics")]
+	pub async fn flush<D: DelayNs>(&mut self, frame: &FrameBuffer, delay: &mut D) -> Result<(), B::Error> {
+		for row in 0..GRAPHIC_ROWS {
+			self.blit(0, row, frame.row(row), delay).await?;
+		}
+
+		Ok(())
+	}
+
+	/// Go back to character mode, with a cleared display. Hands the graphics back if that fails.
+	pub async fn into_text<D: DelayNs>(mut self, delay: &mut D) -> TextResult<B, M, C> {
+		let lcd = &self.oled.lcd;
+		let steps = match self.oled.state.graphic_mode(false, lcd.entry_mode, lcd.display_mode) {
+			Ok(steps) => steps,
+			Err(error) => return Err((self, error)),
+		};
+		if let Err(error) = self.oled.run(&steps, delay).await {
+			return Err((self, error));
+		}
+
+		let mut oled = self.oled;
+		oled.lcd.ddram_address = 0;
+		oled.lcd.screen_cleared();
+
+		Ok(oled)
+	}
+}
+
+impl<B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback> Deref for Oled<B, M, C> {
+	type Target = HD44780<B, M, C>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.lcd
+	}
+}
+
+impl<B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback> DerefMut for Oled<B, M, C> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.lcd
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		charset::{CharsetA00, CharsetUniversal},
+		entry_mode::EntryMode,
+		memory_map::MemoryMap1602,
+		oled::Controller,
+		test_util::{block_on, NoDelay, Recorder},
+		DisplayMode,
+	};
+
+	#[test]
+	fn font_table_keeps_function_set() {
+		let lcd = HD44780::new_raw(
+			Recorder::default(),
+			MemoryMap1602::new(),
+			CharsetUniversal::QUESTION_FALLBACK,
+			EntryMode::default(),
+			DisplayMode::default(),
+		);
+		let lcd = Oled::new(lcd, OledConfig::new(Controller::Ws0010));
+		let Ok(mut lcd) =
+			block_on(lcd.set_font_table(FontTable::EnglishJapanese, CharsetA00::QUESTION_FALLBACK, &mut NoDelay))
+		else {
+			panic!("switching the font table failed");
+		};
+		block_on(lcd.write_str("ä", &mut NoDelay)).unwrap();
+
+		let bus = lcd.into_inner().destroy();
+		assert_eq!(bus.writes(), &[(0x28, false), (0xE1, true)]);
+	}
+}
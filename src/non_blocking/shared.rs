@@ -0,0 +1,236 @@
+use embassy_sync::{blocking_mutex::raw::RawMutex, mutex::Mutex};
+use embedded_hal_async::delay::DelayNs;
+
+use super::{bus::DataBus, HD44780};
+use crate::{
+	charset::CharsetWithFallback,
+	error::{Error, Result},
+	memory_map::DisplayMemoryMap,
+};
+
+/// An async [`HD44780`] shared between tasks, each drawing into its own rectangle.
+///
+/// The display is guarded by an `embassy-sync` mutex, so it works with any executor. Tasks
+/// get a [`Region`] handle, which only writes inside its rectangle:
+///
+/// ```rust,ignore
+/// static DISPLAY: StaticCell<SharedDisplay<CriticalSectionRawMutex, Bus, MemoryMap1602, Charset>> = StaticCell::new();
+///
+/// let display = DISPLAY.init(SharedDisplay::new(lcd));
+/// let clock = display.region((0, 0), (8, 1)).unwrap();
+/// let status = display.region((0, 1), (16, 1)).unwrap();
+///
+/// spawner.spawn(clock_task(clock))?;
+/// spawner.spawn(status_task(status))?;
+/// ```
+pub struct SharedDisplay<R: RawMutex, B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback> {
+	lcd: Mutex<R, HD44780<B, M, C>>,
+	size: (u8, u8),
+}
+
+impl<R, B, M, C> SharedDisplay<R, B, M, C>
+where
+	R: RawMutex,
+	B: DataBus,
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+{
+	pub fn new(lcd: HD44780<B, M, C>) -> Self {
+		let size = lcd.display_size().get();
+
+		Self { lcd: Mutex::new(lcd), size }
+	}
+
+	pub fn into_inner(self) -> HD44780<B, M, C> {
+		self.lcd.into_inner()
+	}
+
+	/// Lock the whole display, e.g. to set it up or clear it.
+	pub async fn lock(&self) -> embassy_sync::mutex::MutexGuard<'_, R, HD44780<B, M, C>> {
+		self.lcd.lock().await
+	}
+
+	/// Handle for the rectangle starting at `origin` (column, row) spanning `size` (columns,
+	/// rows). Returns `None` if the rectangle is empty or doesn't fit on the display.
+	///
+	/// Regions may overlap, it's up to the application to hand out disjoint ones.
+	pub fn region(&self, origin: (u8, u8), size: (u8, u8)) -> Option<Region<'_, R, B, M, C>> {
+		let fits = |start: u8, len: u8, max: u8| len > 0 && start.checked_add(len).is_some_and(|end| end <= max);
+		if !fits(origin.0, size.0, self.size.0) || !fits(origin.1, size.1, self.size.1) {
+			return None;
+		}
+
+		Some(Region { display: self, origin, size })
+	}
+}
+
+/// Part of a [`SharedDisplay`], writes are clipped to its rectangle.
+pub struct Region<'a, R: RawMutex, B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback> {
+	display: &'a SharedDisplay<R, B, M, C>,
+	origin: (u8, u8),
+	size: (u8, u8),
+}
+
+impl<R, B, M, C> Clone for Region<'_, R, B, M, C>
+where
+	R: RawMutex,
+	B: DataBus,
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+{
+	fn clone(&self) -> Self {
+		Self { display: self.display, origin: self.origin, size: self.size }
+	}
+}
+
+impl<R, B, M, C> Region<'_, R, B, M, C>
+where
+	R: RawMutex,
+	B: DataBus,
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+{
+	/// Position of the top left corner on the display.
+	pub fn origin(&self) -> (u8, u8) {
+		self.origin
+	}
+
+	/// Number of columns and rows.
+	pub fn size(&self) -> (u8, u8) {
+		self.size
+	}
+
+	/// Write `text` starting at `position`, relative to the region. Characters which would
+	/// cross the right edge of the region are left out.
+	///
+	/// ```rust,ignore
+	/// clock.write_at((0, 0), "12:30", &mut delay).await?;
+	/// ```
+	pub async fn write_at<D: DelayNs>(&self, position: (u8, u8), text: &str, delay: &mut D) -> Result<(), B::Error> {
+		let mut lcd = self.display.lcd.lock().await;
+
+		self.write_clipped(&mut lcd, position, text, delay).await?;
+
+		Ok(())
+	}
+
+	/// Replace a row of the region with `text`, filling the rest of it with spaces.
+	pub async fn write_line<D: DelayNs>(&self, row: u8, text: &str, delay: &mut D) -> Result<(), B::Error> {
+		let mut lcd = self.display.lcd.lock().await;
+
+		let remaining = self.write_clipped(&mut lcd, (0, row), text, delay).await?;
+		for _ in 0..remaining {
+			lcd.write_char(' ', delay).await?;
+		}
+
+		Ok(())
+	}
+
+	/// Fill the region with spaces.
+	pub async fn clear<D: DelayNs>(&self, delay: &mut D) -> Result<(), B::Error> {
+		for row in 0..self.size.1 {
+			self.write_line(row, "", delay).await?;
+		}
+
+		Ok(())
+	}
+
+	/// Returns the number of cells left until the right edge of the region.
+	async fn write_clipped<D: DelayNs>(
+		&self,
+		lcd: &mut HD44780<B, M, C>,
+		position: (u8, u8),
+		text: &str,
+		delay: &mut D,
+	) -> Result<usize, B::Error> {
+		if position.0 >= self.size.0 || position.1 >= self.size.1 {
+			return Err(Error::Position { position, size: self.size });
+		}
+
+		lcd.set_cursor_xy((self.origin.0 + position.0, self.origin.1 + position.1), delay).await?;
+
+		let mut remaining = usize::from(self.size.0 - position.0);
+		for ch in text.chars() {
+			let width = lcd.charset.text_width(ch.encode_utf8(&mut [0; 4]));
+			if width > remaining {
+				break;
+			}
+
+			lcd.write_char(ch, delay).await?;
+			remaining -= width;
+		}
+
+		Ok(remaining)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+
+	use super::*;
+	use crate::{
+		charset::{CharsetA00, CharsetUniversal, DynamicCharset, QuestionFallback},
+		entry_mode::EntryMode,
+		memory_map::MemoryMap1602,
+		test_util::{block_on, NoDelay, Recorder},
+		DisplayMode,
+	};
+
+	type Display = SharedDisplay<NoopRawMutex, Recorder, MemoryMap1602, QuestionFallback<CharsetUniversal>>;
+
+	fn display() -> Display {
+		SharedDisplay::new(HD44780::new_raw(
+			Recorder::default(),
+			MemoryMap1602::new(),
+			CharsetUniversal::QUESTION_FALLBACK,
+			EntryMode::default(),
+			DisplayMode::default(),
+		))
+	}
+
+	#[test]
+	fn regions_must_fit() {
+		let display = display();
+
+		assert!(display.region((0, 0), (16, 2)).is_some());
+		assert!(display.region((12, 1), (4, 1)).is_some());
+		assert!(display.region((12, 1), (5, 1)).is_none());
+		assert!(display.region((0, 2), (1, 1)).is_none());
+		assert!(display.region((0, 0), (0, 1)).is_none());
+	}
+
+	#[test]
+	fn clips_writes_to_region() {
+		let display = display();
+		let region = display.region((10, 1), (3, 1)).unwrap();
+
+		block_on(region.write_at((1, 0), "abc", &mut NoDelay)).unwrap();
+		block_on(region.write_line(0, "x", &mut NoDelay)).unwrap();
+		assert!(matches!(block_on(region.write_at((0, 1), "y", &mut NoDelay)), Err(Error::Position { .. })));
+
+		let bus = display.into_inner().destroy();
+		assert_eq!(
+			bus.writes(),
+			&[(0xCB, false), (b'a', true), (b'b', true), (0xCA, false), (b'x', true), (b' ', true), (b' ', true)]
+		);
+	}
+
+	#[test]
+	fn measures_glyphs_as_one_cell() {
+		let display: SharedDisplay<NoopRawMutex, _, _, _> = SharedDisplay::new(HD44780::new_raw(
+			Recorder::default(),
+			MemoryMap1602::new(),
+			DynamicCharset::new(CharsetA00::TRANSLITERATING_FALLBACK, &[('€', [1; 8])]),
+			EntryMode::default(),
+			DisplayMode::default(),
+		));
+		let region = display.region((0, 0), (2, 1)).unwrap();
+
+		// Transliterated, '€' would take up the three cells of "EUR"
+		block_on(region.write_line(0, "€x", &mut NoDelay)).unwrap();
+
+		let bus = display.into_inner().destroy();
+		assert!(bus.data().eq([1, 1, 1, 1, 1, 1, 1, 1, 0, b'x']));
+	}
+}
@@ -0,0 +1,163 @@
+use core::ops::{Deref, DerefMut};
+
+use embedded_hal_async::delay::DelayNs;
+
+use super::{bus::DataBus, HD44780};
+use crate::{
+	charset::CharsetWithFallback,
+	error::Result,
+	instruction::Instruction,
+	memory_map::DisplayMemoryMap,
+	st703x::{DoubleHeight, St703xConfig, State, Table},
+};
+
+/// [`HD44780`] on an `ST7032` or `ST7036` controller, as used by EA DOGM, Newhaven and Midas
+/// displays. Adds software contrast, the voltage booster, double height lines and icons.
+///
+/// All methods of [`HD44780`] are available through `Deref`:
+///
+/// ```rust,ignore
+/// let config = St703xConfig::new(Controller::St7032);
+/// let mut lcd = St703x::new(HD44780::new(options.with_profile(St703xProfile::new(config)), &mut delay).await?, config);
+///
+/// lcd.set_contrast(40, &mut delay).await?;
+/// lcd.write_str("Hello", &mut delay).await?;
+/// ```
+pub struct St703x<B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback> {
+	lcd: HD44780<B, M, C>,
+	state: State,
+}
+
+impl<B, M, C> St703x<B, M, C>
+where
+	B: DataBus,
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+{
+	/// Wrap a display set up with [`HD44780::new`] and an
+	/// [`St703xProfile`](crate::st703x::St703xProfile) of the same `config`.
+	pub fn new(lcd: HD44780<B, M, C>, config: St703xConfig) -> Self {
+		let state = State::new(config, lcd.bus.data_length(), lcd.display_size());
+
+		Self { lcd, state }
+	}
+
+	pub fn into_inner(self) -> HD44780<B, M, C> {
+		self.lcd
+	}
+
+	pub fn config(&self) -> &St703xConfig {
+		&self.state.config
+	}
+
+	/// Set the contrast, values above 63 are clamped.
+	pub async fn set_contrast<D: DelayNs>(&mut self, contrast: u8, delay: &mut D) -> Result<(), B::Error> {
+		self.state.config.contrast = contrast.min(63);
+
+		self.command(self.state.function_set(Table::Extended), delay).await?;
+		self.command(self.state.contrast(), delay).await?;
+		self.command(self.state.power(), delay).await?;
+		self.command(self.state.function_set(Table::Normal), delay).await
+	}
+
+	pub async fn set_booster<D: DelayNs>(&mut self, on: bool, delay: &mut D) -> Result<(), B::Error> {
+		self.state.config.booster = on;
+
+		self.extended(Table::Extended, self.state.power(), delay).await
+	}
+
+	/// Show or hide the icons, see [write_icon](#method.write_icon).
+	pub async fn set_icons<D: DelayNs>(&mut self, on: bool, delay: &mut D) -> Result<(), B::Error> {
+		self.state.config.icons = on;
+
+		self.extended(Table::Extended, self.state.power(), delay).await
+	}
+
+	pub async fn set_double_height<D: DelayNs>(
+		&mut self,
+		double_height: DoubleHeight,
+		delay: &mut D,
+	) -> Result<(), B::Error> {
+		self.state.double_height = double_height;
+
+		match self.state.double_height_position() {
+			Some(position) => self.extended(Table::DoubleHeight, position, delay).await,
+			None => self.command(self.state.function_set(Table::Normal), delay).await,
+		}
+	}
+
+	/// Store the segments of one of the 16 icon RAM addresses, bit 4 being the leftmost one.
+	/// Which segments exist depends on the glass.
+	pub async fn write_icon<D: DelayNs>(&mut self, address: u8, segments: u8, delay: &mut D) -> Result<(), B::Error> {
+		self.command(self.state.function_set(Table::Extended), delay).await?;
+		self.command(State::icon_address(address), delay).await?;
+		self.lcd.bus.write(segments & 0b1_1111, true, delay).await?;
+		delay.delay_us(self.lcd.execution_times.data()).await;
+		self.command(self.state.function_set(Table::Normal), delay).await?;
+
+		// Text has to go to the DDRAM again
+		self.command(Instruction::SetDdramAddr(self.lcd.ddram_address).to_byte(), delay).await
+	}
+
+	/// Send an instruction from `table` and switch back to the normal instructions.
+	async fn extended<D: DelayNs>(&mut self, table: Table, byte: u8, delay: &mut D) -> Result<(), B::Error> {
+		self.command(self.state.function_set(table), delay).await?;
+		self.command(byte, delay).await?;
+		self.command(self.state.function_set(Table::Normal), delay).await
+	}
+
+	/// Extended instructions share their encoding with the normal ones, so they bypass the
+	/// address tracking of [`HD44780::send`].
+	async fn command<D: DelayNs>(&mut self, byte: u8, delay: &mut D) -> Result<(), B::Error> {
+		self.lcd.bus.write(byte, false, delay).await?;
+
+		// Wait for the command to be processed
+		delay.delay_us(self.lcd.execution_times.command(byte)).await;
+		Ok(())
+	}
+}
+
+impl<B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback> Deref for St703x<B, M, C> {
+	type Target = HD44780<B, M, C>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.lcd
+	}
+}
+
+impl<B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback> DerefMut for St703x<B, M, C> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.lcd
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		charset::CharsetUniversal,
+		entry_mode::EntryMode,
+		memory_map::MemoryMap1602,
+		st703x::Controller,
+		test_util::{block_on, NoDelay, Recorder},
+		DisplayMode,
+	};
+
+	#[test]
+	fn contrast_keeps_settings() {
+		let lcd = HD44780::new_raw(
+			Recorder::default(),
+			MemoryMap1602::new(),
+			CharsetUniversal::QUESTION_FALLBACK,
+			EntryMode::default(),
+			DisplayMode::default(),
+		);
+		let mut lcd = St703x::new(lcd, St703xConfig::new(Controller::St7032));
+		block_on(lcd.set_contrast(0x25, &mut NoDelay)).unwrap();
+
+		let bus = lcd.into_inner().destroy();
+		let commands = [0x29, 0x75, 0x56, 0x28];
+		assert_eq!(bus.writes().len(), commands.len());
+		assert!(bus.commands().eq(commands));
+	}
+}
@@ -0,0 +1,204 @@
+use embedded_hal_async::delay::DelayNs;
+
+use super::{bus::DataBus, HD44780};
+use crate::{
+	charset::CharsetWithFallback,
+	display_size::DisplaySize,
+	entry_mode::CursorMode,
+	error::{Error, Result},
+	glyph::{Glyph, Glyph5x8, GlyphBank},
+	memory_map::DisplayMemoryMap,
+	setup::non_blocking::{sealed::SealedDisplayOptions, DisplayOptions},
+	Cursor, CursorBlink, Direction, Display, DisplayMode, Instruction,
+};
+
+/// A [`HD44780`] which owns its delay, so none of the methods take one.
+///
+/// Create it with [HD44780::new_with_delay] or [HD44780::with_delay].
+///
+/// ```rust,ignore
+/// let mut lcd = HD44780::new_with_delay(options, embassy_time::Delay).await?;
+///
+/// lcd.clear().await?;
+/// lcd.write_str("Hello").await?;
+/// ```
+pub struct HD44780WithDelay<B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback, D: DelayNs> {
+	lcd: HD44780<B, M, C>,
+	delay: D,
+}
+
+type NewWithDelayResult<Options, D> = core::result::Result<
+	HD44780WithDelay<
+		<Options as SealedDisplayOptions>::Bus,
+		<Options as SealedDisplayOptions>::MemoryMap,
+		<Options as SealedDisplayOptions>::Charset,
+		D,
+	>,
+	(Options, D, Error<<Options as SealedDisplayOptions>::IoError>),
+>;
+
+impl<B, M, C> HD44780<B, M, C>
+where
+	B: DataBus,
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+{
+	/// Like [new](#method.new), but the delay is moved into the driver.
+	///
+	/// If there was an error when setting up the display, the settings and the delay
+	/// are returned together with the error.
+	pub async fn new_with_delay<Opt, D: DelayNs>(options: Opt, mut delay: D) -> NewWithDelayResult<Opt, D>
+	where
+		Opt: DisplayOptions<Bus = B, MemoryMap = M, Charset = C>,
+	{
+		match Self::new(options, &mut delay).await {
+			Ok(lcd) => Ok(lcd.with_delay(delay)),
+			Err((options, error)) => Err((options, delay, error)),
+		}
+	}
+
+	/// Move a delay into the driver.
+	pub fn with_delay<D: DelayNs>(self, delay: D) -> HD44780WithDelay<B, M, C, D> {
+		HD44780WithDelay { lcd: self, delay }
+	}
+}
+
+impl<B, M, C, D> HD44780WithDelay<B, M, C, D>
+where
+	B: DataBus,
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+	D: DelayNs,
+{
+	/// Get back the driver and the delay.
+	pub fn into_parts(self) -> (HD44780<B, M, C>, D) {
+		(self.lcd, self.delay)
+	}
+
+	pub fn destroy(self) -> (B, D) {
+		(self.lcd.destroy(), self.delay)
+	}
+
+	/// Get the memory map information for this display.
+	pub fn memory_map(&self) -> &M {
+		self.lcd.memory_map()
+	}
+
+	/// Get the display size.
+	pub fn display_size(&self) -> DisplaySize {
+		self.lcd.display_size()
+	}
+
+	/// Number of cells `text` takes up when written with [write_str](#method.write_str).
+	pub fn text_width(&self, text: &str) -> usize {
+		self.lcd.text_width(text)
+	}
+
+	/// Unshifts the display and sets the cursor position to 0. See [reset](HD44780::reset).
+	pub async fn reset(&mut self) -> Result<(), B::Error> {
+		self.lcd.reset(&mut self.delay).await
+	}
+
+	/// Set if the display should be on, if the cursor should be
+	/// visible, and if the cursor should blink. See [set_display_mode](HD44780::set_display_mode).
+	pub async fn set_display_mode(&mut self, display_mode: DisplayMode) -> Result<(), B::Error> {
+		self.lcd.set_display_mode(display_mode, &mut self.delay).await
+	}
+
+	/// Clear the entire display. See [clear](HD44780::clear).
+	pub async fn clear(&mut self) -> Result<(), B::Error> {
+		self.lcd.clear(&mut self.delay).await
+	}
+
+	/// Turn the backlight on or off. See [set_backlight](HD44780::set_backlight).
+	pub async fn set_backlight(&mut self, on: bool) -> Result<(), B::Error> {
+		self.lcd.set_backlight(on, &mut self.delay).await
+	}
+
+	/// If enabled, automatically scroll the display when a new
+	/// character is written to the display. See [set_autoscroll](HD44780::set_autoscroll).
+	pub async fn set_autoscroll(&mut self, enabled: bool) -> Result<(), B::Error> {
+		self.lcd.set_autoscroll(enabled, &mut self.delay).await
+	}
+
+	/// Set if the cursor should be visible. See [set_cursor_visibility](HD44780::set_cursor_visibility).
+	pub async fn set_cursor_visibility(&mut self, visibility: Cursor) -> Result<(), B::Error> {
+		self.lcd.set_cursor_visibility(visibility, &mut self.delay).await
+	}
+
+	/// Set if the characters on the display should be visible. See [set_display](HD44780::set_display).
+	pub async fn set_display(&mut self, display: Display) -> Result<(), B::Error> {
+		self.lcd.set_display(display, &mut self.delay).await
+	}
+
+	/// Set if the cursor should blink. See [set_cursor_blink](HD44780::set_cursor_blink).
+	pub async fn set_cursor_blink(&mut self, blink: CursorBlink) -> Result<(), B::Error> {
+		self.lcd.set_cursor_blink(blink, &mut self.delay).await
+	}
+
+	/// Set which way the cursor will move when a new character is written. See [set_cursor_mode](HD44780::set_cursor_mode).
+	pub async fn set_cursor_mode(&mut self, mode: CursorMode) -> Result<(), B::Error> {
+		self.lcd.set_cursor_mode(mode, &mut self.delay).await
+	}
+
+	/// Set the cursor position. See [set_cursor_pos](HD44780::set_cursor_pos).
+	pub async fn set_cursor_pos(&mut self, position: u8) -> Result<(), B::Error> {
+		self.lcd.set_cursor_pos(position, &mut self.delay).await
+	}
+
+	/// Set the cursor position. See [set_cursor_xy](HD44780::set_cursor_xy).
+	pub async fn set_cursor_xy(&mut self, position: (u8, u8)) -> Result<(), B::Error> {
+		self.lcd.set_cursor_xy(position, &mut self.delay).await
+	}
+
+	/// Shift just the cursor to the left or the right. See [shift_cursor](HD44780::shift_cursor).
+	pub async fn shift_cursor(&mut self, dir: Direction) -> Result<(), B::Error> {
+		self.lcd.shift_cursor(dir, &mut self.delay).await
+	}
+
+	/// Shift the entire display to the left or the right. See [shift_display](HD44780::shift_display).
+	pub async fn shift_display(&mut self, dir: Direction) -> Result<(), B::Error> {
+		self.lcd.shift_display(dir, &mut self.delay).await
+	}
+
+	/// Send an instruction to the `HD44780`. See [send](HD44780::send).
+	pub async fn send(&mut self, instruction: Instruction) -> Result<(), B::Error> {
+		self.lcd.send(instruction, &mut self.delay).await
+	}
+
+	/// Store a 5x8 custom character in one of the eight CGRAM slots (`0..=7`). See [set_custom_char](HD44780::set_custom_char).
+	pub async fn set_custom_char(&mut self, slot: u8, pattern: &[u8; 8]) -> Result<(), B::Error> {
+		self.lcd.set_custom_char(slot, pattern, &mut self.delay).await
+	}
+
+	/// Store a [`Glyph5x8`](crate::glyph::Glyph5x8) or [`Glyph5x10`](crate::glyph::Glyph5x10) in CGRAM. See [upload_glyph](HD44780::upload_glyph).
+	pub async fn upload_glyph<G: Glyph>(&mut self, slot: u8, glyph: &G) -> Result<(), B::Error> {
+		self.lcd.upload_glyph(slot, glyph, &mut self.delay).await
+	}
+
+	/// Load glyphs into CGRAM, skipping the slots `bank` knows to hold them already. See
+	/// [activate_glyphs](HD44780::activate_glyphs).
+	pub async fn activate_glyphs(&mut self, bank: &mut GlyphBank, glyphs: &[Glyph5x8]) -> Result<(), B::Error> {
+		self.lcd.activate_glyphs(bank, glyphs, &mut self.delay).await
+	}
+
+	/// Write a single character to the `HD44780`. See [write_char](HD44780::write_char).
+	pub async fn write_char(&mut self, data: char) -> Result<(), B::Error> {
+		self.lcd.write_char(data, &mut self.delay).await
+	}
+
+	/// Writes a string to the `HD44780`. See [write_str](HD44780::write_str).
+	pub async fn write_str(&mut self, string: &str) -> Result<(), B::Error> {
+		self.lcd.write_str(string, &mut self.delay).await
+	}
+
+	/// Writes a sequence of bytes to the `HD44780`. See [write_bytes](HD44780::write_bytes).
+	pub async fn write_bytes(&mut self, string: &[u8]) -> Result<(), B::Error> {
+		self.lcd.write_bytes(string, &mut self.delay).await
+	}
+
+	/// Writes a single byte to the `HD44780`. See [write_byte](HD44780::write_byte).
+	pub async fn write_byte(&mut self, data: u8) -> Result<(), B::Error> {
+		self.lcd.write_byte(data, &mut self.delay).await
+	}
+}
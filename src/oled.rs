@@ -0,0 +1,752 @@
+use core::ops::{Deref, DerefMut};
+
+use embedded_hal::delay::DelayNs;
+
+use crate::{
+	bus::DataBus,
+	charset::CharsetWithFallback,
+	display_mode::DisplayMode,
+	display_size::DisplaySize,
+	entry_mode::EntryMode,
+	error::{Error, Result},
+	instruction::{DataLength, Instruction},
+	memory_map::DisplayMemoryMap,
+	profile::{ControllerProfile, ExecutionTimes, InitSequence, InitStep},
+	setup::INIT_DISPLAY_CONTROL,
+	Cursor, CursorBlink, Display, HD44780,
+};
+
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::{
+	draw_target::DrawTarget,
+	geometry::{OriginDimensions, Size},
+	pixelcolor::BinaryColor,
+	Pixel,
+};
+
+/// Controller of a character OLED.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub enum Controller {
+	/// Winstar `WS0010`, as used by the WEH and WEG series.
+	Ws0010,
+	/// `US2066`, also covers the compatible `SSD1311`.
+	Us2066,
+}
+
+/// Character ROM used for codes above `0x7F`, and on some tables for the lower half too.
+///
+/// The `US2066` has three ROMs instead of four tables. The variants select ROM A, B, C and C.
+///
+/// Only [`FontTable::EnglishJapanese`] has a charset here. For the other tables, describe
+/// the code table of the datasheet with [`define_charset!`](crate::define_charset).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub enum FontTable {
+	/// Same as the `HD44780` A00 ROM, see [`CharsetA00`](crate::charset::CharsetA00).
+	EnglishJapanese,
+	WesternEuropean,
+	EnglishRussian,
+	WesternEuropean2,
+}
+
+impl FontTable {
+	const fn ws0010_bits(self) -> u8 {
+		match self {
+			Self::EnglishJapanese => 0b00,
+			Self::WesternEuropean => 0b01,
+			Self::EnglishRussian => 0b10,
+			Self::WesternEuropean2 => 0b11,
+		}
+	}
+
+	const fn us2066_rom(self) -> u8 {
+		match self {
+			Self::EnglishJapanese => 0b0000,
+			Self::EnglishRussian => 0b0100,
+			Self::WesternEuropean | Self::WesternEuropean2 => 0b1000,
+		}
+	}
+}
+
+/// Settings applied by the OLED init sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct OledConfig {
+	pub controller: Controller,
+	pub font_table: FontTable,
+	/// Internal regulator of the `US2066` logic supply, needed when running from 3.3V.
+	/// Turn it off for 5V. The `WS0010` has none.
+	pub internal_regulator: bool,
+}
+
+impl OledConfig {
+	pub const fn new(controller: Controller) -> Self {
+		Self { controller, font_table: FontTable::EnglishJapanese, internal_regulator: true }
+	}
+}
+
+/// Init sequence of a character OLED, selected with `with_profile` on the
+/// [`setup`](crate::setup) options in place of the `HD44780` one. It selects the font table
+/// and powers up the panel.
+///
+/// ```rust,ignore
+/// let config = OledConfig::new(Controller::Ws0010);
+/// let options = DisplayOptions4Bit::new(MemoryMap1602::new())
+///     .with_pins(pins)
+///     .with_charset(CharsetA00::QUESTION_FALLBACK)
+///     .with_profile(OledProfile::new(config));
+/// let mut lcd = Oled::new(HD44780::new(options, &mut delay)?, config);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct OledProfile {
+	config: OledConfig,
+}
+
+impl OledProfile {
+	pub const fn new(config: OledConfig) -> Self {
+		Self { config }
+	}
+}
+
+impl ControllerProfile for OledProfile {
+	fn init_sequence(&self, data_length: DataLength, entry_mode: EntryMode, size: DisplaySize) -> InitSequence {
+		let state = State { config: self.config, data_length, four_lines: size.get().1 > 2 };
+		let execution_times = ExecutionTimes::of(self);
+
+		let mut sequence = InitSequence::new();
+		sequence.push(InitStep::Wait { us: self.power_on_delay_ms() * 1000 });
+		// The `WS0010` only finds the start of a 4-bit transfer after a warm restart if it goes
+		// through 8-bit operation first, as the `HD44780` sequence does
+		sequence.synchronise(data_length);
+
+		for step in state.init(entry_mode).iter() {
+			let us = match step {
+				Step::Command(byte) => {
+					sequence.push(InitStep::Write(byte));
+					execution_times.command(byte)
+				}
+				Step::Data(byte) => {
+					sequence.push(InitStep::Data(byte));
+					execution_times.data()
+				}
+				Step::Wait(ms) => {
+					sequence.push(InitStep::Wait { us: ms * 1000 });
+					continue;
+				}
+			};
+
+			sequence.push(InitStep::Wait { us });
+		}
+
+		sequence
+	}
+}
+
+/// Part of a command sequence.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Step {
+	Command(u8),
+	/// Parameter of the previous command, sent as data.
+	Data(u8),
+	/// Wait for the previous command to be processed, in milliseconds.
+	Wait(u32),
+}
+
+/// Command sequence built by [`State`].
+#[derive(Debug)]
+pub(crate) struct Steps {
+	steps: [Step; Self::CAPACITY],
+	len: usize,
+}
+
+impl Steps {
+	const CAPACITY: usize = 32;
+
+	const fn new() -> Self {
+		Self { steps: [Step::Wait(0); Self::CAPACITY], len: 0 }
+	}
+
+	fn push(&mut self, steps: &[Step]) {
+		self.steps[self.len..self.len + steps.len()].copy_from_slice(steps);
+		self.len += steps.len();
+	}
+
+	pub(crate) fn iter(&self) -> impl Iterator<Item = Step> + '_ {
+		self.steps[..self.len].iter().copied()
+	}
+}
+
+/// Encodes the OLED specific command sequences for the current settings.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct State {
+	pub(crate) config: OledConfig,
+	pub(crate) data_length: DataLength,
+	/// Display has more than two rows.
+	pub(crate) four_lines: bool,
+}
+
+/// `WS0010` power control, shares its encoding with cursor shift.
+const WS0010_POWER_ON: u8 = 0b0001_0111;
+const WS0010_POWER_OFF: u8 = 0b0001_0011;
+/// Powered up in graphic mode.
+const WS0010_GRAPHIC_MODE: u8 = 0b0001_1111;
+
+/// Width of the `WS0010` graphic mode in pixels.
+pub const GRAPHIC_WIDTH: u8 = 100;
+/// Number of rows in graphic mode, each a byte (8 pixels) high.
+pub const GRAPHIC_ROWS: u8 = 2;
+
+impl State {
+	/// Function set, with the extended register selected for the `US2066`.
+	const fn function_set(&self, extended: bool) -> u8 {
+		let data_length = match self.data_length {
+			DataLength::FourBit => 0,
+			DataLength::EightBit => 0b1_0000,
+		};
+		let base = 0b0010_1000 | data_length;
+
+		match self.config.controller {
+			Controller::Ws0010 => base | self.config.font_table.ws0010_bits(),
+			Controller::Us2066 if extended => base | 0b10,
+			Controller::Us2066 => base,
+		}
+	}
+
+	fn init(&self, entry_mode: EntryMode) -> Steps {
+		let mut steps = Steps::new();
+		let display_off = Step::Command(0b0000_1000);
+
+		match self.config.controller {
+			Controller::Ws0010 => steps.push(&[
+				Step::Command(self.function_set(false)),
+				display_off,
+				Step::Command(Instruction::ClearDisplay.to_byte()),
+				Step::Wait(2),
+				Step::Command(Instruction::EntryModeSet(entry_mode).to_byte()),
+				Step::Command(Instruction::ReturnHome.to_byte()),
+				Step::Wait(2),
+				Step::Command(WS0010_POWER_ON),
+			]),
+			Controller::Us2066 => {
+				let regulator = if self.config.internal_regulator { 0x5C } else { 0x00 };
+				let lines = if self.four_lines { 0b1001 } else { 0b1000 };
+
+				steps.push(&[
+					Step::Command(self.function_set(true)),
+					// Function selection A: regulator
+					Step::Command(0x71),
+					Step::Data(regulator),
+					Step::Command(self.function_set(false)),
+					display_off,
+					Step::Command(self.function_set(true)),
+					// OLED command set: clock divider
+					Step::Command(0x79),
+					Step::Command(0xD5),
+					Step::Command(0x70),
+					Step::Command(0x78),
+					// Extended function set and COM/SEG direction
+					Step::Command(lines),
+					Step::Command(0x06),
+					// Function selection B: ROM
+					Step::Command(0x72),
+					Step::Data(self.config.font_table.us2066_rom()),
+					// OLED command set: pin configuration, contrast, precharge and VCOMH
+					Step::Command(0x79),
+					Step::Command(0xDA),
+					Step::Command(0x10),
+					Step::Command(0xDC),
+					Step::Command(0x00),
+					Step::Command(0x81),
+					Step::Command(0x7F),
+					Step::Command(0xD9),
+					Step::Command(0xF1),
+					Step::Command(0xDB),
+					Step::Command(0x40),
+					Step::Command(0x78),
+					Step::Command(self.function_set(false)),
+					Step::Command(Instruction::ClearDisplay.to_byte()),
+					Step::Wait(2),
+					Step::Command(Instruction::EntryModeSet(entry_mode).to_byte()),
+				]);
+			}
+		}
+
+		steps.push(&[Step::Command(INIT_DISPLAY_CONTROL.to_byte())]);
+		steps
+	}
+
+	pub(crate) fn font_table(&self) -> Steps {
+		let mut steps = Steps::new();
+
+		match self.config.controller {
+			Controller::Ws0010 => steps.push(&[Step::Command(self.function_set(false))]),
+			Controller::Us2066 => steps.push(&[
+				Step::Command(self.function_set(true)),
+				Step::Command(0x72),
+				Step::Data(self.config.font_table.us2066_rom()),
+				Step::Command(self.function_set(false)),
+			]),
+		}
+
+		steps
+	}
+
+	/// The `US2066` has no power control, it sleeps while the display is off.
+	pub(crate) fn power(&self, on: bool, display_mode: DisplayMode) -> Steps {
+		let mut steps = Steps::new();
+
+		match (self.config.controller, on) {
+			(Controller::Ws0010, true) => steps.push(&[Step::Command(WS0010_POWER_ON)]),
+			(Controller::Ws0010, false) => steps.push(&[Step::Command(WS0010_POWER_OFF)]),
+			(Controller::Us2066, true) => {
+				steps.push(&[Step::Command(Instruction::DisplayControl(display_mode).to_byte())])
+			}
+			(Controller::Us2066, false) => steps.push(&[Step::Command(0b0000_1000)]),
+		}
+
+		steps
+	}
+
+	/// Switching between character and graphic mode, the display is blanked while doing so.
+	/// Only the `WS0010` has a graphic mode.
+	pub(crate) fn graphic_mode<E>(
+		&self,
+		on: bool,
+		entry_mode: EntryMode,
+		display_mode: DisplayMode,
+	) -> Result<Steps, E> {
+		if self.config.controller != Controller::Ws0010 {
+			return Err(Error::Unsupported);
+		}
+
+		let mut steps = Steps::new();
+		let display_off = Step::Command(0b0000_1000);
+
+		if on {
+			// Columns are written left to right, without a cursor
+			let display_on = DisplayMode {
+				cursor_visibility: Cursor::Invisible,
+				cursor_blink: CursorBlink::Off,
+				display: Display::On,
+			};
+
+			steps.push(&[
+				display_off,
+				Step::Command(WS0010_GRAPHIC_MODE),
+				Step::Command(Instruction::EntryModeSet(EntryMode::default()).to_byte()),
+				Step::Command(Instruction::DisplayControl(display_on).to_byte()),
+			]);
+		} else {
+			steps.push(&[
+				display_off,
+				Step::Command(WS0010_POWER_ON),
+				Step::Command(Instruction::EntryModeSet(entry_mode).to_byte()),
+				Step::Command(Instruction::ClearDisplay.to_byte()),
+				Step::Wait(2),
+				Step::Command(Instruction::DisplayControl(display_mode).to_byte()),
+			]);
+		}
+
+		Ok(steps)
+	}
+
+	/// Addresses the graphic memory at `column` of `row`, checking that `len` bytes fit.
+	pub(crate) fn blit_address<E>(column: u8, row: u8, len: usize) -> Result<Steps, E> {
+		if row >= GRAPHIC_ROWS || usize::from(column) + len > usize::from(GRAPHIC_WIDTH) {
+			return Err(Error::Position { position: (column, row), size: (GRAPHIC_WIDTH, GRAPHIC_ROWS) });
+		}
+
+		let mut steps = Steps::new();
+		steps.push(&[
+			Step::Command(Instruction::SetDdramAddr(column).to_byte()),
+			Step::Command(Instruction::SetCgramAddr(row).to_byte()),
+		]);
+
+		Ok(steps)
+	}
+}
+
+/// Pixels of the `WS0010` graphic mode, to draw on with `embedded-graphics` and then send with
+/// [`OledGraphics::flush`].
+///
+/// Stored as the controller expects them: a byte per column of each row, with the top pixel
+/// in the least significant bit.
+#[cfg(feature = "graphics")]
+#[derive(Debug, Clone)]
+pub struct FrameBuffer {
+	pub(crate) rows: [[u8; GRAPHIC_WIDTH as usize]; GRAPHIC_ROWS as usize],
+}
+
+#[cfg(feature = "graphics")]
+impl FrameBuffer {
+	pub const fn new() -> Self {
+		Self { rows: [[0; GRAPHIC_WIDTH as usize]; GRAPHIC_ROWS as usize] }
+	}
+
+	/// Column bytes of a row.
+	pub fn row(&self, row: u8) -> &[u8] {
+		&self.rows[usize::from(row)]
+	}
+}
+
+#[cfg(feature = "graphics")]
+impl Default for FrameBuffer {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(feature = "graphics")]
+impl OriginDimensions for FrameBuffer {
+	fn size(&self) -> Size {
+		Size::new(GRAPHIC_WIDTH.into(), u32::from(GRAPHIC_ROWS) * 8)
+	}
+}
+
+#[cfg(feature = "graphics")]
+impl DrawTarget for FrameBuffer {
+	type Color = BinaryColor;
+	type Error = core::convert::Infallible;
+
+	fn draw_iter<I: IntoIterator<Item = Pixel<Self::Color>>>(
+		&mut self,
+		pixels: I,
+	) -> core::result::Result<(), Self::Error> {
+		for Pixel(point, color) in pixels {
+			let (Ok(x), Ok(y)) = (usize::try_from(point.x), usize::try_from(point.y)) else {
+				continue;
+			};
+			let Some(column) = self.rows.get_mut(y / 8).and_then(|row| row.get_mut(x)) else {
+				continue;
+			};
+
+			let bit = 1 << (y % 8);
+			match color {
+				BinaryColor::On => *column |= bit,
+				BinaryColor::Off => *column &= !bit,
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// [`HD44780`] on a `WS0010`, `US2066` or `SSD1311` character OLED controller. Adds font table
+/// selection and power control, OLEDs have no backlight.
+///
+/// All methods of [`HD44780`] are available through `Deref`:
+///
+/// ```rust,ignore
+/// let config = OledConfig::new(Controller::Ws0010);
+/// let options = options.with_charset(CharsetA00::QUESTION_FALLBACK).with_profile(OledProfile::new(config));
+/// let mut lcd = Oled::new(HD44780::new(options, &mut delay)?, config);
+///
+/// lcd.write_str("Hello", &mut delay)?;
+/// ```
+pub struct Oled<B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback> {
+	lcd: HD44780<B, M, C>,
+	state: State,
+}
+
+impl<B, M, C> Oled<B, M, C>
+where
+	B: DataBus,
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+{
+	/// Wrap a display set up with [`HD44780::new`] and an [`OledProfile`] of the same `config`.
+	///
+	/// The charset of the display should match [`OledConfig::font_table`].
+	pub fn new(lcd: HD44780<B, M, C>, config: OledConfig) -> Self {
+		let state = State { config, data_length: lcd.bus.data_length(), four_lines: lcd.display_size().get().1 > 2 };
+
+		Self { lcd, state }
+	}
+
+	pub fn into_inner(self) -> HD44780<B, M, C> {
+		self.lcd
+	}
+
+	pub fn config(&self) -> &OledConfig {
+		&self.state.config
+	}
+
+	/// Switch to another character ROM, with `charset` matching it. Text already on the display
+	/// changes as well. Hands the display back unchanged if that fails.
+	pub fn set_font_table<C2: CharsetWithFallback, D: DelayNs>(
+		mut self,
+		font_table: FontTable,
+		charset: C2,
+		delay: &mut D,
+	) -> FontTableResult<B, M, C, C2> {
+		let state = State { config: OledConfig { font_table, ..self.state.config }, ..self.state };
+		if let Err(error) = self.run(&state.font_table(), delay) {
+			return Err((self, error));
+		}
+
+		Ok(Oled { lcd: self.lcd.replace_charset(charset), state })
+	}
+
+	/// Power the panel up or down. The display contents are kept while it is off.
+	pub fn set_power<D: DelayNs>(&mut self, on: bool, delay: &mut D) -> Result<(), B::Error> {
+		let steps = self.state.power(on, self.lcd.display_mode);
+		self.run(&steps, delay)
+	}
+
+	/// Switch a `WS0010` to its 100x16 pixel graphic mode, which starts out blank. Hands the
+	/// display back if that fails, with [`Error::Unsupported`] on a `US2066`.
+	pub fn into_graphics<D: DelayNs>(mut self, delay: &mut D) -> GraphicsResult<B, M, C> {
+		let steps = match self.state.graphic_mode(true, self.lcd.entry_mode, self.lcd.display_mode) {
+			Ok(steps) => steps,
+			Err(error) => return Err((self, error)),
+		};
+		if let Err(error) = self.run(&steps, delay) {
+			return Err((self, error));
+		}
+
+		let mut graphics = OledGraphics { oled: self };
+		match graphics.clear(delay) {
+			Ok(()) => Ok(graphics),
+			Err(error) => Err((graphics.oled, error)),
+		}
+	}
+
+	/// OLED commands share their encoding with other instructions, so they bypass the
+	/// address tracking of [`HD44780::write_command`].
+	fn run<D: DelayNs>(&mut self, steps: &Steps, delay: &mut D) -> Result<(), B::Error> {
+		for step in steps.iter() {
+			match step {
+				Step::Command(byte) => {
+					self.lcd.bus.write(byte, false, delay)?;
+
+					// Wait for the command to be processed
+					delay.delay_us(self.lcd.execution_times.command(byte));
+				}
+				Step::Data(byte) => {
+					self.lcd.bus.write(byte, true, delay)?;
+					delay.delay_us(self.lcd.execution_times.data());
+				}
+				Step::Wait(ms) => delay.delay_ms(ms),
+			}
+		}
+
+		Ok(())
+	}
+}
+
+type GraphicsResult<B, M, C> =
+	core::result::Result<OledGraphics<B, M, C>, (Oled<B, M, C>, Error<<B as DataBus>::Error>)>;
+
+type FontTableResult<B, M, C, C2> = core::result::Result<Oled<B, M, C2>, (Oled<B, M, C>, Error<<B as DataBus>::Error>)>;
+
+type TextResult<B, M, C> = core::result::Result<Oled<B, M, C>, (OledGraphics<B, M, C>, Error<<B as DataBus>::Error>)>;
+
+/// `WS0010` in graphic mode, writing bytes of 8 vertical pixels (top pixel in the least
+/// significant bit) instead of characters. Uses the same bus as character mode.
+///
+/// ```rust,ignore
+/// let mut graphics = lcd.into_graphics(&mut delay).map_err(|(_, e)| e)?;
+/// graphics.blit(10, 0, &[0xFF, 0x81, 0xFF], &mut delay)?;
+///
+/// let lcd = graphics.into_text(&mut delay).map_err(|(_, e)| e)?;
+/// ```
+pub struct OledGraphics<B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback> {
+	oled: Oled<B, M, C>,
+}
+
+impl<B, M, C> OledGraphics<B, M, C>
+where
+	B: DataBus,
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+{
+	/// Write column bytes to `row` (0 or 1), starting at `column`.
+	pub fn blit<D: DelayNs>(&mut self, column: u8, row: u8, bytes: &[u8], delay: &mut D) -> Result<(), B::Error> {
+		let steps = State::blit_address(column, row, bytes.len())?;
+		self.oled.run(&steps, delay)?;
+		self.oled.lcd.bus.write_data(bytes, delay)
+	}
+
+	/// Turn all pixels off.
+	pub fn clear<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), B::Error> {
+		for row in 0..GRAPHIC_ROWS {
+			self.blit(0, row, &[0; GRAPHIC_WIDTH as usize], delay)?;
+		}
+
+		Ok(())
+	}
+
+	/// Send all pixels of `frame`.
+	#[cfg(feature = "graphics")]
+	pub fn flush<D: DelayNs>(&mut self, frame: &FrameBuffer, delay: &mut D) -> Result<(), B::Error> {
+		for row in 0..GRAPHIC_ROWS {
+			self.blit(0, row, frame.row(row), delay)?;
+		}
+
+		Ok(())
+	}
+
+	/// Go back to character mode, with a cleared display. Hands the graphics back if that fails.
+	pub fn into_text<D: DelayNs>(mut self, delay: &mut D) -> TextResult<B, M, C> {
+		let lcd = &self.oled.lcd;
+		let steps = match self.oled.state.graphic_mode(false, lcd.entry_mode, lcd.display_mode) {
+			Ok(steps) => steps,
+			Err(error) => return Err((self, error)),
+		};
+		if let Err(error) = self.oled.run(&steps, delay) {
+			return Err((self, error));
+		}
+
+		let mut oled = self.oled;
+		oled.lcd.ddram_address = 0;
+		oled.lcd.screen_cleared();
+
+		Ok(oled)
+	}
+}
+
+impl<B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback> Deref for Oled<B, M, C> {
+	type Target = HD44780<B, M, C>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.lcd
+	}
+}
+
+impl<B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback> DerefMut for Oled<B, M, C> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.lcd
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		charset::{CharsetA00, CharsetUniversal},
+		memory_map::MemoryMap1602,
+		test_util::{NoDelay, Recorder},
+	};
+
+	fn display(config: OledConfig) -> Oled<Recorder, MemoryMap1602, impl CharsetWithFallback> {
+		let lcd = HD44780::new_raw(
+			Recorder::default(),
+			MemoryMap1602::new(),
+			CharsetA00::QUESTION_FALLBACK,
+			EntryMode::default(),
+			DisplayMode::default(),
+		);
+
+		Oled::new(lcd, config)
+	}
+
+	/// Bytes of the init sequence, and whether they are written as data.
+	fn init_writes(config: OledConfig, data_length: DataLength) -> ([(u8, bool); 40], usize) {
+		let sequence =
+			OledProfile::new(config).init_sequence(data_length, EntryMode::default(), DisplaySize::new(16, 2));
+		let mut writes = [(0, false); 40];
+		let mut len = 0;
+		for step in sequence.steps() {
+			let write = match *step {
+				InitStep::Write(byte) => (byte, false),
+				InitStep::Data(byte) => (byte, true),
+				InitStep::Wait { .. } => continue,
+			};
+			writes[len] = write;
+			len += 1;
+		}
+
+		(writes, len)
+	}
+
+	#[test]
+	fn ws0010_init_replaces_hd44780_init() {
+		let config = OledConfig { font_table: FontTable::EnglishRussian, ..OledConfig::new(Controller::Ws0010) };
+
+		let (writes, len) = init_writes(config, DataLength::FourBit);
+		let commands = [0x33, 0x32, 0x2A, 0x08, 0x01, 0x06, 0x02, 0x17, 0x0E];
+		assert!(writes[..len].iter().map(|&(byte, _)| byte).eq(commands));
+		assert!(writes[..len].iter().all(|&(_, data)| !data));
+
+		let (writes, _) = init_writes(config, DataLength::EightBit);
+		assert_eq!(writes[..3], [(0x30, false), (0x30, false), (0x3A, false)]);
+	}
+
+	#[test]
+	fn us2066_init_parameters() {
+		let (writes, len) = init_writes(OledConfig::new(Controller::Us2066), DataLength::EightBit);
+		assert_eq!(writes[2..5], [(0x3A, false), (0x71, false), (0x5C, true)]);
+		assert_eq!(writes[len - 1], (0x0E, false));
+	}
+
+	#[test]
+	fn ws0010_power() {
+		let mut lcd = display(OledConfig::new(Controller::Ws0010));
+		lcd.set_power(false, &mut NoDelay).unwrap();
+		lcd.write_str("äa", &mut NoDelay).unwrap();
+
+		let bus = lcd.into_inner().destroy();
+		assert_eq!(bus.writes(), &[(0x13, false), (0xE1, true), (b'a', true)]);
+	}
+
+	#[test]
+	fn graphic_mode_blit() {
+		let lcd = display(OledConfig::new(Controller::Ws0010));
+		let Ok(mut graphics) = lcd.into_graphics(&mut NoDelay) else {
+			panic!("graphic mode failed");
+		};
+		let start = graphics.oled.lcd.bus.writes().len();
+		graphics.blit(98, 1, &[0x81, 0x7E], &mut NoDelay).unwrap();
+		assert!(matches!(graphics.blit(99, 1, &[0, 0], &mut NoDelay), Err(Error::Position { .. })));
+
+		let bus = &graphics.oled.lcd.bus;
+		assert_eq!(&bus.writes()[start..], &[(0xE2, false), (0x41, false), (0x81, true), (0x7E, true)]);
+		assert_eq!(&bus.writes()[..4], &[(0x08, false), (0x1F, false), (0x06, false), (0x0C, false)]);
+	}
+
+	#[test]
+	fn us2066_has_no_graphic_mode() {
+		let lcd = display(OledConfig::new(Controller::Us2066));
+		let Err((lcd, error)) = lcd.into_graphics(&mut NoDelay) else {
+			panic!("US2066 entered graphic mode");
+		};
+
+		assert!(matches!(error, Error::Unsupported));
+		assert!(lcd.into_inner().destroy().writes().is_empty());
+	}
+
+	#[cfg(feature = "graphics")]
+	#[test]
+	fn frame_buffer_pixels() {
+		use embedded_graphics_core::geometry::Point;
+
+		let mut frame = FrameBuffer::new();
+		let pixels = [Pixel(Point::new(3, 9), BinaryColor::On), Pixel(Point::new(100, 0), BinaryColor::On)];
+		frame.draw_iter(pixels).unwrap();
+
+		assert_eq!(frame.row(1)[3], 0b10);
+		assert_eq!(frame.rows.iter().flatten().filter(|&&column| column != 0).count(), 1);
+	}
+
+	#[test]
+	fn us2066_font_table() {
+		let lcd = display(OledConfig::new(Controller::Us2066));
+		let Ok(lcd) = lcd.set_font_table(FontTable::EnglishRussian, CharsetUniversal::QUESTION_FALLBACK, &mut NoDelay)
+		else {
+			panic!("switching the font table failed");
+		};
+		assert_eq!(lcd.config().font_table, FontTable::EnglishRussian);
+
+		let bus = lcd.into_inner().destroy();
+		assert_eq!(
+			&bus.writes()[bus.writes().len() - 4..],
+			&[(0x2A, false), (0x72, false), (0x04, true), (0x28, false)]
+		);
+	}
+}
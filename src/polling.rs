@@ -0,0 +1,507 @@
+use embedded_hal::delay::DelayNs;
+
+use crate::{
+	bus::DataBus,
+	charset::{CharCodes, CharsetWithFallback},
+	ddram_address_after_command,
+	display_size::DisplaySize,
+	entry_mode::{CursorMode, EntryMode},
+	error::{Error, Result},
+	instruction::{Instruction, ShiftTarget},
+	lines_after_command,
+	memory_map::DisplayMemoryMap,
+	next_ddram_address,
+	profile::ExecutionTimes,
+	Cursor, CursorBlink, Direction, Display, DisplayMode,
+};
+
+/// Steps of a CGRAM upload: setting the CGRAM address, 8 rows and restoring the cursor.
+const GLYPH_UPLOAD_STEPS: usize = 10;
+
+/// Point in time of a monotonic clock, in microseconds since an arbitrary epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct Instant {
+	micros: u64,
+}
+
+impl Instant {
+	pub const fn from_micros(micros: u64) -> Self {
+		Self { micros }
+	}
+
+	pub const fn from_millis(millis: u64) -> Self {
+		Self { micros: millis.saturating_mul(1_000) }
+	}
+
+	pub const fn as_micros(self) -> u64 {
+		self.micros
+	}
+
+	const fn after_micros(self, micros: u64) -> Self {
+		Self { micros: self.micros.saturating_add(micros) }
+	}
+}
+
+/// A single write on the bus.
+#[derive(Debug, Clone, Copy)]
+struct Step {
+	byte: u8,
+	data: bool,
+}
+
+impl Step {
+	/// Same time the blocking driver waits after the write.
+	const fn execution_time_us(self, execution_times: &ExecutionTimes) -> u64 {
+		let us = match self.data {
+			true => execution_times.data(),
+			false => execution_times.command(self.byte),
+		};
+		us as u64
+	}
+}
+
+/// Ring buffer of the steps waiting to be written.
+#[derive(Debug)]
+struct StepQueue<const N: usize> {
+	steps: [Step; N],
+	head: usize,
+	len: usize,
+}
+
+impl<const N: usize> StepQueue<N> {
+	const fn new() -> Self {
+		Self { steps: [Step { byte: 0, data: false }; N], head: 0, len: 0 }
+	}
+
+	const fn free(&self) -> usize {
+		N - self.len
+	}
+
+	fn push(&mut self, step: Step) {
+		debug_assert!(self.len < N, "room is reserved before pushing");
+
+		self.steps[(self.head + self.len) % N] = step;
+		self.len += 1;
+	}
+
+	fn pop(&mut self) -> Option<Step> {
+		if self.len == 0 {
+			return None;
+		}
+
+		let step = self.steps[self.head];
+		self.head = (self.head + 1) % N;
+		self.len -= 1;
+
+		Some(step)
+	}
+}
+
+/// The bus implementations wait while pulsing EN, the polling driver doesn't.
+struct NoDelay;
+
+impl DelayNs for NoDelay {
+	fn delay_ns(&mut self, _ns: u32) {}
+}
+
+/// Driver for superloop or RTIC firmware which never waits for the `HD44780`.
+///
+/// The methods only queue up the bus writes they need, up to `N` of them. Calling
+/// [poll](#method.poll) with the current time of a monotonic clock performs the next write
+/// once the previous one had time to execute. Characters and cursor moves take one write
+/// each, uploading a glyph into CGRAM takes ten.
+///
+/// The display is set up by the blocking driver, which is then turned into a polling one:
+///
+/// ```rust,ignore
+/// let lcd = hd44780_driver::HD44780::new(options, &mut delay)?;
+/// let mut lcd = polling::HD44780::<_, _, _, 64>::new(lcd);
+///
+/// lcd.set_cursor_xy((0, 1))?;
+/// lcd.write_str("Hello");
+///
+/// loop {
+///     lcd.poll(Instant::from_micros(timer.now_micros()))?;
+///     // other work
+/// }
+/// ```
+///
+/// Note: the bus still pulses EN, but without waiting in between. Most MCUs take longer than
+/// the required 450ns to toggle a pin twice, very fast ones may need slower GPIO settings.
+pub struct HD44780<B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback, const N: usize> {
+	lcd: crate::HD44780<B, M, C>,
+	queue: StepQueue<N>,
+	ready_at: Instant,
+}
+
+impl<B, M, C, const N: usize> HD44780<B, M, C, N>
+where
+	B: DataBus,
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+{
+	/// Take over an initialised display. The queue must hold at least 14 steps, so any single
+	/// character fits together with a glyph upload.
+	pub fn new(lcd: crate::HD44780<B, M, C>) -> Self {
+		const { assert!(N >= GLYPH_UPLOAD_STEPS + CharCodes::CAPACITY, "queue too small for a single character") };
+
+		Self { lcd, queue: StepQueue::new(), ready_at: Instant::from_micros(0) }
+	}
+
+	/// Turn back into a blocking driver. Steps which are still queued are discarded, so
+	/// wait for [is_idle](#method.is_idle) first.
+	pub fn into_inner(self) -> crate::HD44780<B, M, C> {
+		self.lcd
+	}
+
+	/// Whether every queued step was written.
+	pub fn is_idle(&self) -> bool {
+		self.queue.len == 0
+	}
+
+	/// Number of bus writes still queued.
+	pub fn pending(&self) -> usize {
+		self.queue.len
+	}
+
+	/// Perform the next queued bus write if the previous one had enough time to execute. Call
+	/// this regularly, e.g. from the main loop or a timer interrupt.
+	///
+	/// Returns when `poll` should be called next, or `None` once the queue is empty. A write
+	/// which fails is not retried.
+	pub fn poll(&mut self, now: Instant) -> Result<Option<Instant>, B::Error> {
+		if self.is_idle() {
+			return Ok(None);
+		}
+
+		if now < self.ready_at {
+			return Ok(Some(self.ready_at));
+		}
+
+		if let Some(step) = self.queue.pop() {
+			self.ready_at = now.after_micros(step.execution_time_us(&self.lcd.execution_times));
+			self.lcd.bus.write(step.byte, step.data, &mut NoDelay)?;
+		}
+
+		Ok((!self.is_idle()).then_some(self.ready_at))
+	}
+
+	/// Get the memory map information for this display.
+	pub fn memory_map(&self) -> &M {
+		&self.lcd.memory_map
+	}
+
+	/// Get the display size.
+	pub fn display_size(&self) -> DisplaySize {
+		self.lcd.display_size()
+	}
+
+	/// Number of cells `text` takes up when written with [write_str](#method.write_str).
+	pub fn text_width(&self, text: &str) -> usize {
+		self.lcd.text_width(text)
+	}
+
+	/// Queue an instruction. See [HD44780::send](crate::HD44780::send).
+	pub fn send(&mut self, instruction: Instruction) -> Result<(), B::Error> {
+		self.reserve(1)?;
+		self.push_instruction(instruction);
+
+		Ok(())
+	}
+
+	/// Unshifts the display and sets the cursor position to 0
+	pub fn reset(&mut self) -> Result<(), B::Error> {
+		self.send(Instruction::ReturnHome)
+	}
+
+	/// Clear the entire display
+	pub fn clear(&mut self) -> Result<(), B::Error> {
+		self.send(Instruction::ClearDisplay)?;
+		self.lcd.screen_cleared();
+
+		Ok(())
+	}
+
+	/// Set if the display should be on, if the cursor should be
+	/// visible, and if the cursor should blink
+	pub fn set_display_mode(&mut self, display_mode: DisplayMode) -> Result<(), B::Error> {
+		self.send(Instruction::DisplayControl(display_mode))?;
+		self.lcd.display_mode = display_mode;
+
+		Ok(())
+	}
+
+	/// If enabled, automatically scroll the display when a new
+	/// character is written to the display
+	pub fn set_autoscroll(&mut self, enabled: bool) -> Result<(), B::Error> {
+		let entry_mode = EntryMode { shift_mode: enabled.into(), ..self.lcd.entry_mode };
+
+		self.send(Instruction::EntryModeSet(entry_mode))?;
+		self.lcd.entry_mode = entry_mode;
+
+		Ok(())
+	}
+
+	/// Set which way the cursor will move when a new character is written
+	pub fn set_cursor_mode(&mut self, mode: CursorMode) -> Result<(), B::Error> {
+		let entry_mode = EntryMode { cursor_mode: mode, ..self.lcd.entry_mode };
+
+		self.send(Instruction::EntryModeSet(entry_mode))?;
+		self.lcd.entry_mode = entry_mode;
+
+		Ok(())
+	}
+
+	/// Set if the cursor should be visible
+	pub fn set_cursor_visibility(&mut self, visibility: Cursor) -> Result<(), B::Error> {
+		self.set_display_mode(DisplayMode { cursor_visibility: visibility, ..self.lcd.display_mode })
+	}
+
+	/// Set if the characters on the display should be visible
+	pub fn set_display(&mut self, display: Display) -> Result<(), B::Error> {
+		self.set_display_mode(DisplayMode { display, ..self.lcd.display_mode })
+	}
+
+	/// Set if the cursor should blink
+	pub fn set_cursor_blink(&mut self, blink: CursorBlink) -> Result<(), B::Error> {
+		self.set_display_mode(DisplayMode { cursor_blink: blink, ..self.lcd.display_mode })
+	}
+
+	/// Set the cursor position
+	pub fn set_cursor_pos(&mut self, position: u8) -> Result<(), B::Error> {
+		self.send(Instruction::SetDdramAddr(position))
+	}
+
+	/// Set the cursor position
+	pub fn set_cursor_xy(&mut self, position: (u8, u8)) -> Result<(), B::Error> {
+		let size = self.display_size().get();
+		let Some(pos) = self.lcd.memory_map.address_for_xy(position.0, position.1) else {
+			return Err(Error::Position { position, size });
+		};
+
+		self.send(Instruction::SetDdramAddr(pos))
+	}
+
+	/// Shift just the cursor to the left or the right
+	pub fn shift_cursor(&mut self, dir: Direction) -> Result<(), B::Error> {
+		self.send(Instruction::CursorDisplayShift { target: ShiftTarget::Cursor, direction: dir })
+	}
+
+	/// Shift the entire display to the left or the right
+	pub fn shift_display(&mut self, dir: Direction) -> Result<(), B::Error> {
+		self.send(Instruction::CursorDisplayShift { target: ShiftTarget::Display, direction: dir })
+	}
+
+	/// Queue a 5x8 custom character for one of the eight CGRAM slots. See
+	/// [HD44780::set_custom_char](crate::HD44780::set_custom_char).
+	pub fn set_custom_char(&mut self, slot: u8, pattern: &[u8; 8]) -> Result<(), B::Error> {
+		self.reserve(GLYPH_UPLOAD_STEPS)?;
+		self.lcd.charset.cgram_overwritten(1 << (slot & 0b111));
+		self.push_custom_char(slot, pattern);
+
+		Ok(())
+	}
+
+	/// Queue a single character. Fails without queueing anything when there's no room for it and
+	/// the glyph upload it needs.
+	pub fn write_char(&mut self, data: char) -> Result<(), B::Error> {
+		let steps = match self.lcd.charset.needs_glyph(data) {
+			// The uploaded glyph is displayed with its slot number
+			true => GLYPH_UPLOAD_STEPS + 1,
+			false => self.lcd.charset.codes_from_utf8_with_fallback(data).len(),
+		};
+
+		self.reserve(steps)?;
+		self.push_char(data);
+
+		Ok(())
+	}
+
+	/// Queue as much of `string` as fits, like [`write_char`](#method.write_char) for each
+	/// character. Returns the number of bytes of `string` queued, the rest can be written
+	/// once [poll](#method.poll) made room.
+	pub fn write_str(&mut self, string: &str) -> usize {
+		let mut queued = 0;
+
+		for ch in string.chars() {
+			if self.write_char(ch).is_err() {
+				break;
+			}
+
+			queued += ch.len_utf8();
+		}
+
+		queued
+	}
+
+	/// Queue a single byte. See [HD44780::write_byte](crate::HD44780::write_byte).
+	pub fn write_byte(&mut self, data: u8) -> Result<(), B::Error> {
+		self.reserve(1)?;
+		self.push_data(data);
+
+		Ok(())
+	}
+
+	/// Queue as many `bytes` as fit and return how many that were.
+	pub fn write_bytes(&mut self, bytes: &[u8]) -> usize {
+		let queued = bytes.len().min(self.queue.free());
+
+		for &byte in &bytes[..queued] {
+			self.push_data(byte);
+		}
+
+		queued
+	}
+
+	fn reserve(&self, steps: usize) -> Result<(), B::Error> {
+		if self.queue.free() < steps {
+			return Err(Error::QueueFull);
+		}
+
+		Ok(())
+	}
+
+	// The display state is tracked as of the end of the queue, so e.g. a glyph upload restores
+	// the cursor to where the text before it left it.
+
+	fn push_instruction(&mut self, instruction: Instruction) {
+		let byte = instruction.to_byte();
+
+		self.lcd.ddram_address = ddram_address_after_command(self.lcd.ddram_address, byte, self.lcd.lines);
+		self.lcd.lines = lines_after_command(self.lcd.lines, byte);
+		self.queue.push(Step { byte, data: false });
+	}
+
+	fn push_data(&mut self, byte: u8) {
+		self.lcd.ddram_address =
+			next_ddram_address(self.lcd.ddram_address, self.lcd.entry_mode.cursor_mode, self.lcd.lines);
+		self.queue.push(Step { byte, data: true });
+	}
+
+	fn push_custom_char(&mut self, slot: u8, pattern: &[u8; 8]) {
+		let address = self.lcd.ddram_address;
+		self.lcd.cgram_changed();
+
+		self.push_instruction(Instruction::SetCgramAddr((slot & 0b111) << 3));
+
+		for &row in pattern {
+			self.queue.push(Step { byte: row & 0b0001_1111, data: true });
+		}
+
+		self.push_instruction(Instruction::SetDdramAddr(address));
+	}
+
+	fn push_char(&mut self, data: char) {
+		if let Some(glyph) = self.lcd.charset.glyph_for(data) {
+			self.push_custom_char(glyph.slot, &glyph.pattern);
+		}
+
+		for &code in self.lcd.charset.codes_from_utf8_with_fallback(data).as_slice() {
+			self.push_data(code);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		charset::{CharsetUniversal, DynamicCharset},
+		memory_map::MemoryMap1602,
+		test_util::Recorder,
+	};
+
+	fn display<C: CharsetWithFallback, const N: usize>(charset: C) -> HD44780<Recorder, MemoryMap1602, C, N> {
+		let lcd = crate::HD44780::new_raw(
+			Recorder::default(),
+			MemoryMap1602::new(),
+			charset,
+			EntryMode::default(),
+			DisplayMode::default(),
+		);
+
+		HD44780::new(lcd)
+	}
+
+	fn run<C: CharsetWithFallback, const N: usize>(lcd: &mut HD44780<Recorder, MemoryMap1602, C, N>) {
+		let mut now = Instant::from_micros(0);
+
+		while let Some(next) = lcd.poll(now).unwrap() {
+			now = next;
+		}
+	}
+
+	#[test]
+	fn writes_one_step_per_poll() {
+		let mut lcd = display::<_, 16>(CharsetUniversal::EMPTY_FALLBACK);
+
+		lcd.clear().unwrap();
+		assert_eq!(lcd.write_str("ab"), 2);
+		assert!(lcd.lcd.bus.writes().is_empty());
+
+		assert_eq!(lcd.poll(Instant::from_micros(10)).unwrap(), Some(Instant::from_micros(2_010)));
+		assert_eq!(lcd.lcd.bus.writes(), &[(0x01, false)]);
+
+		// Still clearing the display
+		assert_eq!(lcd.poll(Instant::from_micros(500)).unwrap(), Some(Instant::from_micros(2_010)));
+		assert_eq!(lcd.lcd.bus.writes().len(), 1);
+
+		assert_eq!(lcd.poll(Instant::from_micros(2_010)).unwrap(), Some(Instant::from_micros(2_110)));
+		assert_eq!(lcd.poll(Instant::from_micros(2_200)).unwrap(), None);
+		assert_eq!(lcd.poll(Instant::from_micros(2_300)).unwrap(), None);
+
+		assert_eq!(lcd.lcd.bus.writes(), &[(0x01, false), (b'a', true), (b'b', true)]);
+	}
+
+	#[test]
+	fn queues_what_fits() {
+		let mut lcd = display::<_, 16>(CharsetUniversal::EMPTY_FALLBACK);
+
+		lcd.write_byte(b'-').unwrap();
+		lcd.set_cursor_xy((0, 1)).unwrap();
+		assert!(matches!(lcd.set_cursor_xy((0, 2)), Err(Error::Position { .. })));
+
+		// 14 free steps, one per character
+		assert_eq!(lcd.write_str("Hello, World!!!"), 14);
+		assert_eq!(lcd.pending(), 16);
+		assert!(matches!(lcd.write_char('!'), Err(Error::QueueFull)));
+
+		run(&mut lcd);
+
+		assert!(lcd.is_idle());
+		assert_eq!(lcd.write_str("!"), 1);
+		assert_eq!(lcd.write_bytes(&[0; 20]), 15);
+	}
+
+	#[test]
+	fn uploads_glyphs_in_order() {
+		const GLYPHS: &[(char, [u8; 8])] = &[('ą', [0b1_1111; 8])];
+
+		let mut lcd = display::<_, 32>(DynamicCharset::new(CharsetUniversal::EMPTY_FALLBACK, GLYPHS));
+
+		lcd.set_cursor_pos(0x40).unwrap();
+		assert_eq!(lcd.write_str("ną"), 3);
+		run(&mut lcd);
+
+		assert_eq!(
+			lcd.lcd.bus.writes(),
+			&[
+				(0xC0, false),
+				(b'n', true),
+				(0x40, false),
+				(0x1F, true),
+				(0x1F, true),
+				(0x1F, true),
+				(0x1F, true),
+				(0x1F, true),
+				(0x1F, true),
+				(0x1F, true),
+				(0x1F, true),
+				(0xC1, false),
+				(0, true),
+			]
+		);
+	}
+}
@@ -0,0 +1,325 @@
+use crate::{
+	display_size::DisplaySize,
+	entry_mode::EntryMode,
+	instruction::{DataLength, Font, Instruction, Lines},
+	setup::INIT_DISPLAY_CONTROL,
+};
+
+/// Controller specific timing and features, selected with `with_profile` on the
+/// [`setup`](crate::setup) options.
+///
+/// The clones all accept the `HD44780` init sequence, but need a different time after power
+/// on and execute instructions at different speeds. Implement this for other controllers.
+/// Character OLEDs replace the whole sequence, see [`OledProfile`](crate::oled::OledProfile).
+///
+/// The driver waits [`execution_time_us`](Self::execution_time_us) after every instruction,
+/// during the init sequence and afterwards. Data writes take as long as setting the DDRAM
+/// address.
+///
+/// ```rust,ignore
+/// let options = DisplayOptions4Bit::new(MemoryMap1602::new()).with_pins(pins).with_profile(St7066u);
+/// let lcd = HD44780::new(options, &mut delay)?;
+/// ```
+pub trait ControllerProfile {
+	/// Time after power on before the first instruction, in milliseconds.
+	fn power_on_delay_ms(&self) -> u32 {
+		15
+	}
+
+	/// Time the controller takes to execute `instruction`, in microseconds.
+	fn execution_time_us(&self, instruction: Instruction) -> u32 {
+		match instruction {
+			Instruction::ClearDisplay | Instruction::ReturnHome => 2000,
+			_ => 100,
+		}
+	}
+
+	fn capabilities(&self) -> Capabilities {
+		Capabilities::HD44780
+	}
+
+	/// Instructions bringing the controller of a display of `size` from any state into
+	/// `data_length` operation, with a cleared display and the cursor at the start of the first
+	/// line. The `HD44780` sequence always uses two lines.
+	fn init_sequence(&self, data_length: DataLength, entry_mode: EntryMode, _size: DisplaySize) -> InitSequence {
+		let mut sequence = InitSequence::new();
+		sequence.push(InitStep::Wait { us: self.power_on_delay_ms() * 1000 });
+		sequence.synchronise(data_length);
+
+		let function_set = Instruction::FunctionSet { data_length, lines: Lines::Two, font: Font::Dots5x8 };
+		for instruction in [
+			function_set,
+			INIT_DISPLAY_CONTROL,
+			Instruction::ClearDisplay,
+			Instruction::EntryModeSet(entry_mode),
+			Instruction::SetDdramAddr(0),
+		] {
+			sequence.instruction(instruction, self.execution_time_us(instruction));
+		}
+
+		sequence
+	}
+}
+
+/// Features which not every controller has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct Capabilities {
+	/// 5x10 dot font, for one line displays.
+	pub font_5x10: bool,
+	/// Can be wired to I2C or SPI instead of a parallel bus.
+	pub serial_interface: bool,
+	/// Number of custom characters in CGRAM.
+	pub custom_characters: u8,
+}
+
+impl Capabilities {
+	pub const HD44780: Self = Self { font_5x10: true, serial_interface: false, custom_characters: 8 };
+}
+
+/// Waits of the driver after each write, looked up once from
+/// [`ControllerProfile::execution_time_us`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ExecutionTimes {
+	/// Indexed by the leading zeros of the instruction byte, which tell the instructions apart.
+	instructions: [u32; 8],
+	data: u32,
+}
+
+impl ExecutionTimes {
+	pub(crate) fn of<CP: ControllerProfile + ?Sized>(profile: &CP) -> Self {
+		let instructions = core::array::from_fn(|zeros| {
+			Instruction::from_byte(0x80 >> zeros).map_or(0, |instruction| profile.execution_time_us(instruction))
+		});
+		let data = profile.execution_time_us(Instruction::SetDdramAddr(0));
+
+		Self { instructions, data }
+	}
+
+	/// Time the command `byte` takes to execute, in microseconds.
+	pub(crate) const fn command(&self, byte: u8) -> u32 {
+		// `0x00` is no instruction, treat it like the slowest one
+		let zeros = if byte == 0 { 7 } else { byte.leading_zeros() as usize };
+		self.instructions[zeros]
+	}
+
+	/// Time writing a data byte takes, in microseconds.
+	pub(crate) const fn data(&self) -> u32 {
+		self.data
+	}
+}
+
+impl Default for ExecutionTimes {
+	fn default() -> Self {
+		Self::of(&Hd44780)
+	}
+}
+
+/// Part of an [`InitSequence`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InitStep {
+	/// Byte written as a command.
+	Write(u8),
+	/// Byte written as data, a parameter of the previous command.
+	Data(u8),
+	Wait {
+		us: u32,
+	},
+}
+
+/// Steps of [`ControllerProfile::init_sequence`].
+#[derive(Debug, Clone)]
+pub struct InitSequence {
+	steps: [InitStep; Self::CAPACITY],
+	len: usize,
+}
+
+impl InitSequence {
+	pub const CAPACITY: usize = 80;
+
+	pub const fn new() -> Self {
+		Self { steps: [InitStep::Wait { us: 0 }; Self::CAPACITY], len: 0 }
+	}
+
+	/// # Panics
+	///
+	/// If the sequence already has [`InitSequence::CAPACITY`] steps.
+	pub fn push(&mut self, step: InitStep) {
+		self.steps[self.len] = step;
+		self.len += 1;
+	}
+
+	/// Bring the controller into `data_length` operation, wherever it was in a 4-bit transfer.
+	pub fn synchronise(&mut self, data_length: DataLength) {
+		// Three 8-bit function sets synchronise the controller. On a 4-bit bus each byte is two
+		// of them, 0x32 ends with the switch to 4-bit operation.
+		match data_length {
+			DataLength::FourBit => {
+				self.push(InitStep::Write(0x33));
+				self.push(InitStep::Wait { us: 4100 });
+				self.push(InitStep::Write(0x32));
+				self.push(InitStep::Wait { us: 100 });
+			}
+			DataLength::EightBit => {
+				self.push(InitStep::Write(0x30));
+				self.push(InitStep::Wait { us: 4100 });
+				self.push(InitStep::Write(0x30));
+				self.push(InitStep::Wait { us: 100 });
+			}
+		}
+	}
+
+	/// Write `instruction` and wait `us` for it to be executed.
+	pub fn instruction(&mut self, instruction: Instruction, us: u32) {
+		self.push(InitStep::Write(instruction.to_byte()));
+		self.push(InitStep::Wait { us });
+	}
+
+	pub fn steps(&self) -> &[InitStep] {
+		&self.steps[..self.len]
+	}
+}
+
+impl Default for InitSequence {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Hitachi `HD44780`, with waits generous enough for most clones. Used unless a different
+/// profile is selected.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Hd44780;
+
+impl ControllerProfile for Hd44780 {}
+
+/// Samsung `KS0066` and the compatible `S6A0069`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ks0066;
+
+impl ControllerProfile for Ks0066 {
+	fn power_on_delay_ms(&self) -> u32 {
+		30
+	}
+
+	fn execution_time_us(&self, instruction: Instruction) -> u32 {
+		match instruction {
+			Instruction::ClearDisplay | Instruction::ReturnHome => 1530,
+			_ => 39,
+		}
+	}
+}
+
+/// Sunplus `SPLC780D`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Splc780d;
+
+impl ControllerProfile for Splc780d {
+	fn execution_time_us(&self, instruction: Instruction) -> u32 {
+		match instruction {
+			Instruction::ClearDisplay | Instruction::ReturnHome => 1520,
+			_ => 37,
+		}
+	}
+}
+
+/// Sitronix `ST7066U`, which needs longer after power on at 3V.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct St7066u;
+
+impl ControllerProfile for St7066u {
+	fn power_on_delay_ms(&self) -> u32 {
+		40
+	}
+
+	fn execution_time_us(&self, instruction: Instruction) -> u32 {
+		match instruction {
+			Instruction::ClearDisplay | Instruction::ReturnHome => 1520,
+			_ => 37,
+		}
+	}
+}
+
+/// RAiO `RW1063`, which also has I2C and SPI interfaces.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Rw1063;
+
+impl ControllerProfile for Rw1063 {
+	fn power_on_delay_ms(&self) -> u32 {
+		40
+	}
+
+	fn execution_time_us(&self, instruction: Instruction) -> u32 {
+		match instruction {
+			Instruction::ClearDisplay | Instruction::ReturnHome => 1530,
+			_ => 39,
+		}
+	}
+
+	fn capabilities(&self) -> Capabilities {
+		Capabilities { serial_interface: true, ..Capabilities::HD44780 }
+	}
+}
+
+/// Novatek `NT7603`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Nt7603;
+
+impl ControllerProfile for Nt7603 {
+	fn power_on_delay_ms(&self) -> u32 {
+		40
+	}
+
+	fn execution_time_us(&self, instruction: Instruction) -> u32 {
+		match instruction {
+			Instruction::ClearDisplay | Instruction::ReturnHome => 1640,
+			_ => 40,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn writes(sequence: &InitSequence) -> impl Iterator<Item = u8> + '_ {
+		sequence.steps().iter().filter_map(|step| match step {
+			InitStep::Write(byte) => Some(*byte),
+			InitStep::Data(_) | InitStep::Wait { .. } => None,
+		})
+	}
+
+	#[test]
+	fn clones_share_the_init_sequence() {
+		let reference = Hd44780.init_sequence(DataLength::FourBit, EntryMode::default(), DisplaySize::default());
+		assert!(writes(&reference).eq([0x33, 0x32, 0x28, 0x0E, 0x01, 0x06, 0x80]));
+
+		let profiles: [&dyn ControllerProfile; 5] = [&Ks0066, &Splc780d, &St7066u, &Rw1063, &Nt7603];
+		for profile in profiles {
+			let sequence = profile.init_sequence(DataLength::FourBit, EntryMode::default(), DisplaySize::default());
+			assert!(writes(&sequence).eq(writes(&reference)));
+			assert_eq!(sequence.steps()[0], InitStep::Wait { us: profile.power_on_delay_ms() * 1000 });
+		}
+	}
+
+	#[test]
+	fn execution_times_from_profile() {
+		let times = ExecutionTimes::of(&Nt7603);
+		assert_eq!(times.command(Instruction::ClearDisplay.to_byte()), 1640);
+		assert_eq!(times.command(Instruction::ReturnHome.to_byte()), 1640);
+		assert_eq!(times.command(0xC0), 40);
+		assert_eq!(times.data(), 40);
+
+		let sequence = Ks0066.init_sequence(DataLength::FourBit, EntryMode::default(), DisplaySize::default());
+		assert!(sequence.steps().contains(&InitStep::Wait { us: 1530 }));
+		assert!(!sequence.steps().contains(&InitStep::Wait { us: 2000 }));
+	}
+
+	#[test]
+	fn serial_interface_capability() {
+		assert!(Rw1063.capabilities().serial_interface);
+		assert!(!Hd44780.capabilities().serial_interface);
+	}
+}
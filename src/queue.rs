@@ -0,0 +1,343 @@
+use core::cell::RefCell;
+
+use critical_section::Mutex;
+use embedded_hal::delay::DelayNs;
+
+use crate::{bus::DataBus, charset::CharsetWithFallback, error::Error, memory_map::DisplayMemoryMap, HD44780};
+
+/// Text of a [`Operation::WriteAt`], stored inline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Text {
+	bytes: [u8; Text::CAPACITY],
+	len: u8,
+}
+
+impl Text {
+	/// Longest text in bytes, enough for a line of a 40 column display.
+	pub const CAPACITY: usize = 40;
+
+	/// Copy `text`, returns `None` if it's longer than [`Text::CAPACITY`] bytes.
+	pub const fn new(text: &str) -> Option<Self> {
+		if text.len() > Self::CAPACITY {
+			return None;
+		}
+
+		let mut bytes = [0; Self::CAPACITY];
+		bytes.split_at_mut(text.len()).0.copy_from_slice(text.as_bytes());
+
+		Some(Self { bytes, len: text.len() as u8 })
+	}
+
+	pub fn as_str(&self) -> &str {
+		// Only ever copied from a `&str`
+		core::str::from_utf8(&self.bytes[..self.len as usize]).unwrap_or_default()
+	}
+}
+
+/// An update of the display, queued in a [`DisplayQueue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+	/// Move the cursor to `position` and write `text` there.
+	WriteAt { position: (u8, u8), text: Text },
+	/// Move the cursor, see [HD44780::set_cursor_xy].
+	SetCursor((u8, u8)),
+	/// Store a custom character, see [HD44780::set_custom_char].
+	DefineGlyph { slot: u8, pattern: [u8; 8] },
+	/// Turn the backlight on or off, see [HD44780::set_backlight].
+	Backlight(bool),
+}
+
+#[derive(Debug)]
+struct Ring<const N: usize> {
+	operations: [Operation; N],
+	head: usize,
+	len: usize,
+	dropped: usize,
+}
+
+/// Failed [`DisplayQueue::drain`]. The failed operation is still at the front of the queue.
+#[derive(Debug)]
+pub struct DrainError<E> {
+	pub error: Error<E>,
+	/// Operations dropped because the queue was full since the last `drain`.
+	pub dropped: usize,
+}
+
+impl<E: core::fmt::Debug> core::fmt::Display for DrainError<E> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		write!(f, "{} ({} operations dropped)", self.error, self.dropped)
+	}
+}
+
+#[cfg(feature = "defmt")]
+impl<E: defmt::Format> defmt::Format for DrainError<E> {
+	fn format(&self, fmt: defmt::Formatter) {
+		defmt::write!(fmt, "{} ({} operations dropped)", self.error, self.dropped)
+	}
+}
+
+#[cfg(feature = "ufmt")]
+impl<E: ufmt::uDebug> ufmt::uDisplay for DrainError<E> {
+	fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> core::result::Result<(), W::Error>
+	where
+		W: ufmt::uWrite + ?Sized,
+	{
+		ufmt::uwrite!(f, "{} ({} operations dropped)", self.error, self.dropped)
+	}
+}
+
+impl<E: core::error::Error + 'static> core::error::Error for DrainError<E> {
+	fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+		Some(&self.error)
+	}
+}
+
+/// Queue of display updates which interrupt handlers and the main loop can push into.
+///
+/// The queue is a fixed size ring buffer guarded by a critical section, so it can live in a
+/// `static`. Whichever context owns the display executes the operations with
+/// [drain](#method.drain):
+///
+/// ```rust,ignore
+/// static QUEUE: DisplayQueue<16> = DisplayQueue::new();
+///
+/// #[interrupt]
+/// fn TIM2() {
+///     let _ = QUEUE.push(Operation::WriteAt { position: (0, 1), text: Text::new("tick").unwrap() });
+/// }
+///
+/// loop {
+///     let dropped = QUEUE.drain(&mut lcd, &mut delay)?;
+/// }
+/// ```
+#[derive(Debug)]
+pub struct DisplayQueue<const N: usize> {
+	ring: Mutex<RefCell<Ring<N>>>,
+}
+
+impl<const N: usize> Default for DisplayQueue<N> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<const N: usize> DisplayQueue<N> {
+	pub const fn new() -> Self {
+		let ring = Ring { operations: [Operation::Backlight(false); N], head: 0, len: 0, dropped: 0 };
+
+		Self { ring: Mutex::new(RefCell::new(ring)) }
+	}
+
+	/// Queue an operation. When the queue is full, the operation is handed back and counted
+	/// as dropped.
+	pub fn push(&self, operation: Operation) -> core::result::Result<(), Operation> {
+		critical_section::with(|cs| {
+			let mut ring = self.ring.borrow_ref_mut(cs);
+
+			if ring.len == N {
+				ring.dropped += 1;
+				return Err(operation);
+			}
+
+			let tail = (ring.head + ring.len) % N;
+			ring.operations[tail] = operation;
+			ring.len += 1;
+
+			Ok(())
+		})
+	}
+
+	/// Number of queued operations.
+	pub fn len(&self) -> usize {
+		critical_section::with(|cs| self.ring.borrow_ref(cs).len)
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	fn front(&self) -> Option<Operation> {
+		critical_section::with(|cs| {
+			let ring = self.ring.borrow_ref(cs);
+			(ring.len > 0).then(|| ring.operations[ring.head])
+		})
+	}
+
+	/// Remove the operation returned by [`DisplayQueue::front`]. Pushes only add to the back, so
+	/// it is still at the front.
+	fn pop_front(&self) {
+		critical_section::with(|cs| {
+			let mut ring = self.ring.borrow_ref_mut(cs);
+			ring.head = (ring.head + 1) % N;
+			ring.len -= 1;
+		})
+	}
+
+	fn take_dropped(&self) -> usize {
+		critical_section::with(|cs| core::mem::take(&mut self.ring.borrow_ref_mut(cs).dropped))
+	}
+
+	/// Execute the queued operations in order, including the ones pushed while draining.
+	/// The critical section is only held to take an operation off the queue, not while
+	/// talking to the display.
+	///
+	/// Returns the number of operations dropped because the queue was full since the last
+	/// `drain`. If an operation fails on the bus, it stays at the front of the queue with the ones
+	/// after it, so the next `drain` retries it. Operations which can never succeed, like a write
+	/// outside the display, are removed instead. The error also reports the dropped operations.
+	pub fn drain<B, M, C, D>(
+		&self,
+		lcd: &mut HD44780<B, M, C>,
+		delay: &mut D,
+	) -> core::result::Result<usize, DrainError<B::Error>>
+	where
+		B: DataBus,
+		M: DisplayMemoryMap,
+		C: CharsetWithFallback,
+		D: DelayNs,
+	{
+		while let Some(operation) = self.front() {
+			let result = match operation {
+				Operation::WriteAt { position, text } => {
+					lcd.set_cursor_xy(position, delay).and_then(|()| lcd.write_str(text.as_str(), delay))
+				}
+				Operation::SetCursor(position) => lcd.set_cursor_xy(position, delay),
+				Operation::DefineGlyph { slot, pattern } => lcd.set_custom_char(slot, &pattern, delay),
+				Operation::Backlight(on) => lcd.set_backlight(on, delay),
+			};
+
+			if let Err(error) = result {
+				// Retrying only helps if the bus failed
+				if !matches!(error, Error::Io { .. }) {
+					self.pop_front();
+				}
+				return Err(DrainError { error, dropped: self.take_dropped() });
+			}
+			self.pop_front();
+		}
+
+		Ok(self.take_dropped())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		charset::CharsetUniversal,
+		entry_mode::EntryMode,
+		error::{Port, Result},
+		memory_map::MemoryMap1602,
+		test_util::{NoDelay, Recorder},
+		DisplayMode,
+	};
+
+	/// Bus whose writes fail while `broken`.
+	struct Flaky {
+		broken: bool,
+		writes: usize,
+	}
+
+	impl DataBus for Flaky {
+		type Error = ();
+
+		fn write<D: DelayNs>(&mut self, _byte: u8, _data: bool, _delay: &mut D) -> Result<(), ()> {
+			if self.broken {
+				return Err(Error::Io { port: Port::I2C, error: () });
+			}
+
+			self.writes += 1;
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn text_capacity() {
+		assert_eq!(Text::new("hi").unwrap().as_str(), "hi");
+		assert_eq!(Text::new("°").unwrap().as_str(), "°");
+		let long = [b'x'; Text::CAPACITY + 1];
+		assert!(Text::new(core::str::from_utf8(&long[1..]).unwrap()).is_some());
+		assert!(Text::new(core::str::from_utf8(&long).unwrap()).is_none());
+	}
+
+	#[test]
+	fn drains_in_order_and_reports_drops() {
+		static QUEUE: DisplayQueue<2> = DisplayQueue::new();
+
+		let mut lcd = HD44780::new_raw(
+			Recorder::default(),
+			MemoryMap1602::new(),
+			CharsetUniversal::QUESTION_FALLBACK,
+			EntryMode::default(),
+			DisplayMode::default(),
+		);
+
+		let write = Operation::WriteAt { position: (1, 1), text: Text::new("ok").unwrap() };
+
+		assert_eq!(QUEUE.push(Operation::SetCursor((0, 0))), Ok(()));
+		assert_eq!(QUEUE.push(write), Ok(()));
+		assert_eq!(QUEUE.push(Operation::Backlight(true)), Err(Operation::Backlight(true)));
+		assert_eq!(QUEUE.len(), 2);
+
+		assert_eq!(QUEUE.drain(&mut lcd, &mut NoDelay).unwrap(), 1);
+		assert!(QUEUE.is_empty());
+		assert_eq!(QUEUE.drain(&mut lcd, &mut NoDelay).unwrap(), 0);
+
+		let bus = lcd.destroy();
+		assert_eq!(bus.writes(), &[(0x80, false), (0xC1, false), (b'o', true), (b'k', true)]);
+	}
+
+	#[test]
+	fn keeps_failed_operation_queued() {
+		static QUEUE: DisplayQueue<2> = DisplayQueue::new();
+
+		let mut lcd = HD44780::new_raw(
+			Flaky { broken: true, writes: 0 },
+			MemoryMap1602::new(),
+			CharsetUniversal::QUESTION_FALLBACK,
+			EntryMode::default(),
+			DisplayMode::default(),
+		);
+
+		assert_eq!(QUEUE.push(Operation::SetCursor((0, 0))), Ok(()));
+		assert_eq!(QUEUE.push(Operation::SetCursor((1, 1))), Ok(()));
+		assert!(QUEUE.push(Operation::SetCursor((2, 1))).is_err());
+
+		let error = QUEUE.drain(&mut lcd, &mut NoDelay).unwrap_err();
+		assert!(matches!(error, DrainError { error: Error::Io { port: Port::I2C, .. }, dropped: 1 }));
+		assert_eq!(QUEUE.len(), 2);
+
+		lcd.bus.broken = false;
+		assert_eq!(QUEUE.drain(&mut lcd, &mut NoDelay).unwrap(), 0);
+		assert!(QUEUE.is_empty());
+		assert_eq!(lcd.bus.writes, 2);
+	}
+
+	#[test]
+	fn drops_operation_that_cannot_succeed() {
+		static QUEUE: DisplayQueue<2> = DisplayQueue::new();
+
+		let mut lcd = HD44780::new_raw(
+			Recorder::default(),
+			MemoryMap1602::new(),
+			CharsetUniversal::QUESTION_FALLBACK,
+			EntryMode::default(),
+			DisplayMode::default(),
+		);
+
+		let write = Operation::WriteAt { position: (0, 1), text: Text::new("ok").unwrap() };
+
+		assert_eq!(QUEUE.push(Operation::SetCursor((0, 2))), Ok(()));
+		assert_eq!(QUEUE.push(write), Ok(()));
+
+		let error = QUEUE.drain(&mut lcd, &mut NoDelay).unwrap_err();
+		assert!(matches!(error, DrainError { error: Error::Position { position: (0, 2), .. }, dropped: 0 }));
+		assert_eq!(QUEUE.len(), 1);
+
+		assert_eq!(QUEUE.drain(&mut lcd, &mut NoDelay).unwrap(), 0);
+		assert!(QUEUE.is_empty());
+
+		let bus = lcd.destroy();
+		assert_eq!(bus.writes(), &[(0xC0, false), (b'o', true), (b'k', true)]);
+	}
+}
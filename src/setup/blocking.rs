@@ -1,21 +1,32 @@
 use embedded_hal::{
 	delay::DelayNs,
-	digital::{self, OutputPin},
+	digital::{ErrorKind, OutputPin},
 	i2c::I2c,
+	spi::SpiDevice,
 };
 use sealed::SealedDisplayOptions;
 
 use crate::{
-	bus::{DataBus, EightBitBus, FourBitBus, I2CBus},
+	bus::{
+		DataBus, EightBitBus, FourBitBus, I2CBus, I2cControl, Lock, ParallelPort, PortBus, SerialControlBus, SharedBus,
+		SharedLinesPins, SpiControl, SpiError,
+	},
 	charset::CharsetWithFallback,
+	display_size::DisplaySize,
 	entry_mode::EntryMode,
 	error::{Error, Result},
+	instruction::{DataLength, Lines},
+	lines_after_command,
 	memory_map::DisplayMemoryMap,
+	profile::{ControllerProfile, ExecutionTimes, InitStep},
 	sealed::Internal,
 	DisplayMode, HD44780,
 };
 
-use super::{DisplayOptions4Bit, DisplayOptions8Bit, DisplayOptionsI2C};
+use super::{
+	DisplayOptions4Bit, DisplayOptions8Bit, DisplayOptionsI2C, DisplayOptionsPort, DisplayOptionsSerialI2C,
+	DisplayOptionsShared, DisplayOptionsSpi,
+};
 
 pub(crate) mod sealed {
 	use embedded_hal::delay::DelayNs;
@@ -39,6 +50,10 @@ pub(crate) mod sealed {
 /// - [`DisplayOptionsI2C`]
 /// - [`DisplayOptions4Bit`]
 /// - [`DisplayOptions8Bit`]
+/// - [`DisplayOptionsPort`]
+/// - [`DisplayOptionsSerialI2C`]
+/// - [`DisplayOptionsSpi`]
+/// - [`DisplayOptionsShared`]
 pub trait DisplayOptions: sealed::SealedDisplayOptions {}
 
 type HD44780FromOptions<Options> = HD44780<
@@ -52,203 +67,356 @@ type DisplayOptionsResult<Options> =
 impl<
 		M: DisplayMemoryMap,
 		C: CharsetWithFallback,
-		RS: OutputPin<Error = E>,
-		EN: OutputPin<Error = E>,
-		D0: OutputPin<Error = E>,
-		D1: OutputPin<Error = E>,
-		D2: OutputPin<Error = E>,
-		D3: OutputPin<Error = E>,
-		D4: OutputPin<Error = E>,
-		D5: OutputPin<Error = E>,
-		D6: OutputPin<Error = E>,
-		D7: OutputPin<Error = E>,
-		E: digital::Error,
-	> DisplayOptions for DisplayOptions8Bit<M, C, RS, EN, D0, D1, D2, D3, D4, D5, D6, D7>
+		CP: ControllerProfile,
+		RS: OutputPin,
+		EN: OutputPin,
+		D0: OutputPin,
+		D1: OutputPin,
+		D2: OutputPin,
+		D3: OutputPin,
+		D4: OutputPin,
+		D5: OutputPin,
+		D6: OutputPin,
+		D7: OutputPin,
+	> DisplayOptions for DisplayOptions8Bit<M, C, RS, EN, D0, D1, D2, D3, D4, D5, D6, D7, CP>
 {
 }
 
 impl<
 		M: DisplayMemoryMap,
 		C: CharsetWithFallback,
-		RS: OutputPin<Error = E>,
-		EN: OutputPin<Error = E>,
-		D0: OutputPin<Error = E>,
-		D1: OutputPin<Error = E>,
-		D2: OutputPin<Error = E>,
-		D3: OutputPin<Error = E>,
-		D4: OutputPin<Error = E>,
-		D5: OutputPin<Error = E>,
-		D6: OutputPin<Error = E>,
-		D7: OutputPin<Error = E>,
-		E: digital::Error,
-	> SealedDisplayOptions for DisplayOptions8Bit<M, C, RS, EN, D0, D1, D2, D3, D4, D5, D6, D7>
+		CP: ControllerProfile,
+		RS: OutputPin,
+		EN: OutputPin,
+		D0: OutputPin,
+		D1: OutputPin,
+		D2: OutputPin,
+		D3: OutputPin,
+		D4: OutputPin,
+		D5: OutputPin,
+		D6: OutputPin,
+		D7: OutputPin,
+	> SealedDisplayOptions for DisplayOptions8Bit<M, C, RS, EN, D0, D1, D2, D3, D4, D5, D6, D7, CP>
 {
 	type Bus = EightBitBus<RS, EN, D0, D1, D2, D3, D4, D5, D6, D7>;
 	type MemoryMap = M;
 	type Charset = C;
-	type IoError = E;
+	type IoError = ErrorKind;
 
 	fn new_display<D: DelayNs>(mut self, delay: &mut D, _: Internal) -> DisplayOptionsResult<Self> {
 		let mut bus = EightBitBus::from_pins(self.pins);
 
-		if let Err(error) = init_8bit(&mut bus, &self.entry_mode, delay) {
-			self.pins = bus.destroy();
-			return Err((self, error));
-		}
-
-		Ok(HD44780::new_raw(bus, self.memory_map, self.charset, self.entry_mode, DisplayMode::default()))
+		let lines = match init(
+			&mut bus,
+			&self.profile,
+			DataLength::EightBit,
+			self.entry_mode,
+			self.memory_map.display_size(),
+			delay,
+		) {
+			Ok(lines) => lines,
+			Err(error) => {
+				self.pins = bus.destroy();
+				return Err((self, error));
+			}
+		};
+
+		Ok(HD44780::new_raw(bus, self.memory_map, self.charset, self.entry_mode, DisplayMode::default())
+			.with_lines(lines)
+			.with_execution_times(ExecutionTimes::of(&self.profile)))
 	}
 }
 
 impl<
 		M: DisplayMemoryMap,
 		C: CharsetWithFallback,
-		RS: OutputPin<Error = E>,
-		EN: OutputPin<Error = E>,
-		D4: OutputPin<Error = E>,
-		D5: OutputPin<Error = E>,
-		D6: OutputPin<Error = E>,
-		D7: OutputPin<Error = E>,
-		E: digital::Error,
-	> DisplayOptions for DisplayOptions4Bit<M, C, RS, EN, D4, D5, D6, D7>
+		CP: ControllerProfile,
+		RS: OutputPin,
+		EN: OutputPin,
+		D4: OutputPin,
+		D5: OutputPin,
+		D6: OutputPin,
+		D7: OutputPin,
+	> DisplayOptions for DisplayOptions4Bit<M, C, RS, EN, D4, D5, D6, D7, CP>
 {
 }
 
 impl<
 		M: DisplayMemoryMap,
 		C: CharsetWithFallback,
-		RS: OutputPin<Error = E>,
-		EN: OutputPin<Error = E>,
-		D4: OutputPin<Error = E>,
-		D5: OutputPin<Error = E>,
-		D6: OutputPin<Error = E>,
-		D7: OutputPin<Error = E>,
-		E: digital::Error,
-	> SealedDisplayOptions for DisplayOptions4Bit<M, C, RS, EN, D4, D5, D6, D7>
+		CP: ControllerProfile,
+		RS: OutputPin,
+		EN: OutputPin,
+		D4: OutputPin,
+		D5: OutputPin,
+		D6: OutputPin,
+		D7: OutputPin,
+	> SealedDisplayOptions for DisplayOptions4Bit<M, C, RS, EN, D4, D5, D6, D7, CP>
 {
 	type Bus = FourBitBus<RS, EN, D4, D5, D6, D7>;
 	type MemoryMap = M;
 	type Charset = C;
-	type IoError = E;
+	type IoError = ErrorKind;
 
 	fn new_display<D: DelayNs>(mut self, delay: &mut D, _: Internal) -> DisplayOptionsResult<Self> {
 		let mut bus = FourBitBus::from_pins(self.pins);
 
-		if let Err(error) = init_4bit(&mut bus, &self.entry_mode, delay) {
-			self.pins = bus.destroy();
-			return Err((self, error));
-		}
-
-		Ok(HD44780::new_raw(bus, self.memory_map, self.charset, self.entry_mode, DisplayMode::default()))
+		let lines = match init(
+			&mut bus,
+			&self.profile,
+			DataLength::FourBit,
+			self.entry_mode,
+			self.memory_map.display_size(),
+			delay,
+		) {
+			Ok(lines) => lines,
+			Err(error) => {
+				self.pins = bus.destroy();
+				return Err((self, error));
+			}
+		};
+
+		Ok(HD44780::new_raw(bus, self.memory_map, self.charset, self.entry_mode, DisplayMode::default())
+			.with_lines(lines)
+			.with_execution_times(ExecutionTimes::of(&self.profile)))
 	}
 }
 
-impl<M: DisplayMemoryMap, C: CharsetWithFallback, I2C: I2c> DisplayOptions for DisplayOptionsI2C<M, C, I2C> {}
+impl<
+		M: DisplayMemoryMap,
+		C: CharsetWithFallback,
+		CP: ControllerProfile,
+		P: ParallelPort,
+		RS: OutputPin,
+		EN: OutputPin,
+	> DisplayOptions for DisplayOptionsPort<M, C, P, RS, EN, CP>
+{
+}
 
-impl<M: DisplayMemoryMap, C: CharsetWithFallback, I2C: I2c> SealedDisplayOptions for DisplayOptionsI2C<M, C, I2C> {
-	type Bus = I2CBus<I2C>;
+impl<
+		M: DisplayMemoryMap,
+		C: CharsetWithFallback,
+		CP: ControllerProfile,
+		P: ParallelPort,
+		RS: OutputPin,
+		EN: OutputPin,
+	> SealedDisplayOptions for DisplayOptionsPort<M, C, P, RS, EN, CP>
+{
+	type Bus = PortBus<P, RS, EN>;
 	type MemoryMap = M;
 	type Charset = C;
-	type IoError = I2C::Error;
+	type IoError = ErrorKind;
 
 	fn new_display<D: DelayNs>(mut self, delay: &mut D, _: Internal) -> DisplayOptionsResult<Self> {
-		let mut bus = I2CBus::new(self.i2c_bus, self.address);
-
-		if let Err(error) = init_4bit(&mut bus, &self.entry_mode, delay) {
-			self.i2c_bus = bus.destroy();
-			return Err((self, error));
-		}
-
-		Ok(HD44780::new_raw(bus, self.memory_map, self.charset, self.entry_mode, DisplayMode::default()))
+		let mut bus = PortBus::from_pins(self.pins, self.data_length);
+
+		let lines = match init(
+			&mut bus,
+			&self.profile,
+			self.data_length,
+			self.entry_mode,
+			self.memory_map.display_size(),
+			delay,
+		) {
+			Ok(lines) => lines,
+			Err(error) => {
+				self.pins = bus.destroy();
+				return Err((self, error));
+			}
+		};
+
+		Ok(HD44780::new_raw(bus, self.memory_map, self.charset, self.entry_mode, DisplayMode::default())
+			.with_lines(lines)
+			.with_execution_times(ExecutionTimes::of(&self.profile)))
 	}
 }
 
-// Follow the 8-bit setup procedure as specified in the HD44780 datasheet
-fn init_8bit<B: DataBus, D: DelayNs>(bus: &mut B, entry_mode: &EntryMode, delay: &mut D) -> Result<(), B::Error> {
-	// Wait for the LCD to wakeup if it was off
-	delay.delay_ms(15u32);
-
-	// Initialize Lcd in 8-bit mode
-	bus.write(0b0011_0000, false, delay)?;
-
-	// Wait for the command to be processed
-	delay.delay_ms(5u32);
-
-	// Sets 8-bit operation and enables 5x7 mode for chars
-	bus.write(0b0011_1000, false, delay)?;
-
-	// Wait for the command to be processed
-	delay.delay_us(100);
-
-	bus.write(0b0000_1110, false, delay)?;
-
-	// Wait for the command to be processed
-	delay.delay_us(100);
-
-	// Clear Display
-	bus.write(0b0000_0001, false, delay)?;
-
-	// Wait for the command to be processed
-	delay.delay_us(100);
-
-	// Move the cursor to beginning of first line
-	bus.write(0b000_0111, false, delay)?;
-
-	// Wait for the command to be processed
-	delay.delay_us(100);
-
-	// Set entry mode
-	bus.write(entry_mode.as_byte(), false, delay)?;
-
-	// Wait for the command to be processed
-	delay.delay_us(100);
-
-	Ok(())
+impl<M, C, CP, L, RS, D4, D5, D6, D7, EN> DisplayOptions for DisplayOptionsShared<M, C, SharedBus<L, EN>, CP>
+where
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+	CP: ControllerProfile,
+	L: Lock<Pins = SharedLinesPins<RS, D4, D5, D6, D7>>,
+	RS: OutputPin,
+	D4: OutputPin,
+	D5: OutputPin,
+	D6: OutputPin,
+	D7: OutputPin,
+	EN: OutputPin,
+{
 }
 
-fn init_4bit<B: DataBus, D: DelayNs>(bus: &mut B, entry_mode: &EntryMode, delay: &mut D) -> Result<(), B::Error> {
-	// Wait for the LCD to wakeup if it was off
-	delay.delay_ms(15u32);
-
-	// Initialize Lcd in 4-bit mode
-	bus.write(0x33, false, delay)?;
-
-	// Wait for the command to be processed
-	delay.delay_ms(5u32);
-
-	// Sets 4-bit operation and enables 5x7 mode for chars
-	bus.write(0x32, false, delay)?;
+impl<M, C, CP, L, RS, D4, D5, D6, D7, EN> SealedDisplayOptions for DisplayOptionsShared<M, C, SharedBus<L, EN>, CP>
+where
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+	CP: ControllerProfile,
+	L: Lock<Pins = SharedLinesPins<RS, D4, D5, D6, D7>>,
+	RS: OutputPin,
+	D4: OutputPin,
+	D5: OutputPin,
+	D6: OutputPin,
+	D7: OutputPin,
+	EN: OutputPin,
+{
+	type Bus = SharedBus<L, EN>;
+	type MemoryMap = M;
+	type Charset = C;
+	type IoError = ErrorKind;
 
-	// Wait for the command to be processed
-	delay.delay_us(100);
+	fn new_display<D: DelayNs>(mut self, delay: &mut D, _: Internal) -> DisplayOptionsResult<Self> {
+		let lines = match init(
+			&mut self.bus,
+			&self.profile,
+			DataLength::FourBit,
+			self.entry_mode,
+			self.memory_map.display_size(),
+			delay,
+		) {
+			Ok(lines) => lines,
+			Err(error) => {
+				return Err((self, error));
+			}
+		};
+
+		Ok(HD44780::new_raw(self.bus, self.memory_map, self.charset, self.entry_mode, DisplayMode::default())
+			.with_lines(lines)
+			.with_execution_times(ExecutionTimes::of(&self.profile)))
+	}
+}
 
-	bus.write(0x28, false, delay)?;
+impl<M: DisplayMemoryMap, C: CharsetWithFallback, CP: ControllerProfile, I2C: I2c> DisplayOptions
+	for DisplayOptionsI2C<M, C, I2C, CP>
+{
+}
 
-	// Wait for the command to be processed
-	delay.delay_us(100);
+impl<M: DisplayMemoryMap, C: CharsetWithFallback, CP: ControllerProfile, I2C: I2c> SealedDisplayOptions
+	for DisplayOptionsI2C<M, C, I2C, CP>
+{
+	type Bus = I2CBus<I2C>;
+	type MemoryMap = M;
+	type Charset = C;
+	type IoError = I2C::Error;
 
-	// Clear Display
-	bus.write(0x0E, false, delay)?;
+	fn new_display<D: DelayNs>(mut self, delay: &mut D, _: Internal) -> DisplayOptionsResult<Self> {
+		let mut bus = I2CBus::new(self.i2c_bus, self.address).with_max_chunk_size(self.max_chunk_size);
+
+		let lines = match init(
+			&mut bus,
+			&self.profile,
+			DataLength::FourBit,
+			self.entry_mode,
+			self.memory_map.display_size(),
+			delay,
+		) {
+			Ok(lines) => lines,
+			Err(error) => {
+				self.i2c_bus = bus.destroy();
+				return Err((self, error));
+			}
+		};
+
+		Ok(HD44780::new_raw(bus, self.memory_map, self.charset, self.entry_mode, DisplayMode::default())
+			.with_lines(lines)
+			.with_execution_times(ExecutionTimes::of(&self.profile)))
+	}
+}
 
-	// Wait for the command to be processed
-	delay.delay_us(100);
+impl<M: DisplayMemoryMap, C: CharsetWithFallback, CP: ControllerProfile, I2C: I2c> DisplayOptions
+	for DisplayOptionsSerialI2C<M, C, I2C, CP>
+{
+}
 
-	// Move the cursor to beginning of first line
-	bus.write(0x01, false, delay)?;
+impl<M: DisplayMemoryMap, C: CharsetWithFallback, CP: ControllerProfile, I2C: I2c> SealedDisplayOptions
+	for DisplayOptionsSerialI2C<M, C, I2C, CP>
+{
+	type Bus = SerialControlBus<I2cControl<I2C>>;
+	type MemoryMap = M;
+	type Charset = C;
+	type IoError = I2C::Error;
 
-	// Wait for the command to be processed
-	delay.delay_us(100);
+	fn new_display<D: DelayNs>(mut self, delay: &mut D, _: Internal) -> DisplayOptionsResult<Self> {
+		let mut bus = SerialControlBus::i2c(self.i2c_bus, self.address).with_max_chunk_size(self.max_chunk_size);
+
+		let lines = match init(
+			&mut bus,
+			&self.profile,
+			DataLength::EightBit,
+			self.entry_mode,
+			self.memory_map.display_size(),
+			delay,
+		) {
+			Ok(lines) => lines,
+			Err(error) => {
+				self.i2c_bus = bus.destroy();
+				return Err((self, error));
+			}
+		};
+
+		Ok(HD44780::new_raw(bus, self.memory_map, self.charset, self.entry_mode, DisplayMode::default())
+			.with_lines(lines)
+			.with_execution_times(ExecutionTimes::of(&self.profile)))
+	}
+}
 
-	// Set entry mode
-	bus.write(entry_mode.as_byte(), false, delay)?;
+impl<M: DisplayMemoryMap, C: CharsetWithFallback, CP: ControllerProfile, SPI: SpiDevice, RS: OutputPin> DisplayOptions
+	for DisplayOptionsSpi<M, C, SPI, RS, CP>
+{
+}
 
-	// Wait for the command to be processed
-	delay.delay_us(100);
+impl<M: DisplayMemoryMap, C: CharsetWithFallback, CP: ControllerProfile, SPI: SpiDevice, RS: OutputPin>
+	SealedDisplayOptions for DisplayOptionsSpi<M, C, SPI, RS, CP>
+{
+	type Bus = SerialControlBus<SpiControl<SPI, RS>>;
+	type MemoryMap = M;
+	type Charset = C;
+	type IoError = SpiError<SPI::Error, RS::Error>;
 
-	bus.write(0x80, false, delay)?;
+	fn new_display<D: DelayNs>(mut self, delay: &mut D, _: Internal) -> DisplayOptionsResult<Self> {
+		let mut bus = SerialControlBus::spi(self.spi, self.rs);
+
+		let lines = match init(
+			&mut bus,
+			&self.profile,
+			DataLength::EightBit,
+			self.entry_mode,
+			self.memory_map.display_size(),
+			delay,
+		) {
+			Ok(lines) => lines,
+			Err(error) => {
+				(self.spi, self.rs) = bus.destroy();
+				return Err((self, error));
+			}
+		};
+
+		Ok(HD44780::new_raw(bus, self.memory_map, self.charset, self.entry_mode, DisplayMode::default())
+			.with_lines(lines)
+			.with_execution_times(ExecutionTimes::of(&self.profile)))
+	}
+}
 
-	// Wait for the command to be processed
-	delay.delay_us(100);
+fn init<B: DataBus, CP: ControllerProfile, D: DelayNs>(
+	bus: &mut B,
+	profile: &CP,
+	data_length: DataLength,
+	entry_mode: EntryMode,
+	size: DisplaySize,
+	delay: &mut D,
+) -> Result<Lines, B::Error> {
+	// The controller resets to one line, until a function set says otherwise
+	let mut lines = Lines::One;
+
+	for step in profile.init_sequence(data_length, entry_mode, size).steps() {
+		match *step {
+			InitStep::Write(byte) => {
+				bus.write(byte, false, delay)?;
+				lines = lines_after_command(lines, byte);
+			}
+			InitStep::Data(byte) => bus.write(byte, true, delay)?,
+			InitStep::Wait { us } => delay.delay_us(us),
+		}
+	}
 
-	Ok(())
+	Ok(lines)
 }
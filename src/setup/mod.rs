@@ -1,8 +1,11 @@
 use crate::{
-	bus::{EightBitBusPins, FourBitBusPins},
+	bus::{EightBitBusPins, FourBitBusPins, PortBusPins, DEFAULT_CHUNK_SIZE, DEFAULT_I2C_ADDRESS},
 	charset::{CharsetUniversal, CharsetWithFallback, EmptyFallback},
 	entry_mode::EntryMode,
+	instruction::{DataLength, Instruction},
 	memory_map::DisplayMemoryMap,
+	profile::{ControllerProfile, Hd44780},
+	Cursor, CursorBlink, Display, DisplayMode,
 };
 
 pub(crate) mod blocking;
@@ -10,31 +13,94 @@ pub(crate) mod blocking;
 #[cfg(feature = "async")]
 pub(crate) mod non_blocking;
 
+/// Display on with a steady cursor, as left behind by the init sequences.
+pub(crate) const INIT_DISPLAY_CONTROL: Instruction = Instruction::DisplayControl(DisplayMode {
+	cursor_visibility: Cursor::Visible,
+	cursor_blink: CursorBlink::Off,
+	display: Display::On,
+});
+
 /// Placeholder until the pin/bus is specified.
 #[derive(Debug, Clone, Copy)]
 pub struct Unspecified;
 
 #[derive(Debug, Clone, Copy)]
-pub struct DisplayOptions8Bit<M: DisplayMemoryMap, C: CharsetWithFallback, RS, EN, D0, D1, D2, D3, D4, D5, D6, D7> {
+pub struct DisplayOptions8Bit<
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+	RS,
+	EN,
+	D0,
+	D1,
+	D2,
+	D3,
+	D4,
+	D5,
+	D6,
+	D7,
+	CP: ControllerProfile = Hd44780,
+> {
 	/// Memory map used for mapping 2D coordinates to the display.
 	pub memory_map: M,
 	/// The character set this display uses.
 	pub charset: C,
 	pub entry_mode: EntryMode,
 	pub pins: EightBitBusPins<RS, EN, D0, D1, D2, D3, D4, D5, D6, D7>,
+	/// Set with [`with_profile`](Self::with_profile).
+	profile: CP,
 }
 
 #[derive(Debug, Clone, Copy)]
-pub struct DisplayOptions4Bit<M: DisplayMemoryMap, C: CharsetWithFallback, RS, EN, D4, D5, D6, D7> {
+pub struct DisplayOptions4Bit<
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+	RS,
+	EN,
+	D4,
+	D5,
+	D6,
+	D7,
+	CP: ControllerProfile = Hd44780,
+> {
 	/// Memory map used for mapping 2D coordinates to the display.
 	pub memory_map: M,
 	/// The character set this display uses.
 	pub charset: C,
 	pub entry_mode: EntryMode,
 	pub pins: FourBitBusPins<RS, EN, D4, D5, D6, D7>,
+	/// Set with [`with_profile`](Self::with_profile).
+	profile: CP,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayOptionsPort<M: DisplayMemoryMap, C: CharsetWithFallback, P, RS, EN, CP: ControllerProfile = Hd44780> {
+	/// Memory map used for mapping 2D coordinates to the display.
+	pub memory_map: M,
+	/// The character set this display uses.
+	pub charset: C,
+	pub entry_mode: EntryMode,
+	pub pins: PortBusPins<P, RS, EN>,
+	/// Whether the display is wired with `D0..D7` or only `D4..D7`.
+	pub data_length: DataLength,
+	/// Set with [`with_profile`](Self::with_profile).
+	profile: CP,
+}
+
+/// Options of a display on [`SharedLines`](crate::bus::SharedLines), see
+/// [`SharedBus`](crate::bus::SharedBus).
+#[derive(Debug)]
+pub struct DisplayOptionsShared<M: DisplayMemoryMap, C: CharsetWithFallback, B, CP: ControllerProfile = Hd44780> {
+	/// Memory map used for mapping 2D coordinates to the display.
+	pub memory_map: M,
+	/// The character set this display uses.
+	pub charset: C,
+	pub entry_mode: EntryMode,
+	pub bus: B,
+	/// Set with [`with_profile`](Self::with_profile).
+	profile: CP,
 }
 
-pub struct DisplayOptionsI2C<M: DisplayMemoryMap, C: CharsetWithFallback, I2C> {
+pub struct DisplayOptionsI2C<M: DisplayMemoryMap, C: CharsetWithFallback, I2C, CP: ControllerProfile = Hd44780> {
 	/// Memory map used for mapping 2D coordinates to the display.
 	pub memory_map: M,
 	/// The character set this display uses.
@@ -42,6 +108,40 @@ pub struct DisplayOptionsI2C<M: DisplayMemoryMap, C: CharsetWithFallback, I2C> {
 	pub entry_mode: EntryMode,
 	pub i2c_bus: I2C,
 	pub address: u8,
+	/// Set with [`with_max_chunk_size`](Self::with_max_chunk_size).
+	max_chunk_size: usize,
+	/// Set with [`with_profile`](Self::with_profile).
+	profile: CP,
+}
+
+/// Options of a controller speaking I2C itself, like the `ST7032i` or `AiP31068`, see
+/// [`SerialControlBus`](crate::bus::SerialControlBus).
+pub struct DisplayOptionsSerialI2C<M: DisplayMemoryMap, C: CharsetWithFallback, I2C, CP: ControllerProfile = Hd44780> {
+	/// Memory map used for mapping 2D coordinates to the display.
+	pub memory_map: M,
+	/// The character set this display uses.
+	pub charset: C,
+	pub entry_mode: EntryMode,
+	pub i2c_bus: I2C,
+	pub address: u8,
+	/// Set with [`with_max_chunk_size`](Self::with_max_chunk_size).
+	max_chunk_size: usize,
+	/// Set with [`with_profile`](Self::with_profile).
+	profile: CP,
+}
+
+/// Options of a controller on a 4-wire SPI bus, like the `ST7036`, see
+/// [`SerialControlBus`](crate::bus::SerialControlBus).
+pub struct DisplayOptionsSpi<M: DisplayMemoryMap, C: CharsetWithFallback, SPI, RS, CP: ControllerProfile = Hd44780> {
+	/// Memory map used for mapping 2D coordinates to the display.
+	pub memory_map: M,
+	/// The character set this display uses.
+	pub charset: C,
+	pub entry_mode: EntryMode,
+	pub spi: SPI,
+	pub rs: RS,
+	/// Set with [`with_profile`](Self::with_profile).
+	profile: CP,
 }
 
 impl<M: DisplayMemoryMap>
@@ -65,6 +165,7 @@ impl<M: DisplayMemoryMap>
 			memory_map,
 			charset: CharsetUniversal::EMPTY_FALLBACK,
 			entry_mode: EntryMode::default(),
+			profile: Hd44780,
 			pins: EightBitBusPins {
 				rs: Unspecified,
 				en: Unspecified,
@@ -98,6 +199,7 @@ impl<M: DisplayMemoryMap>
 			memory_map,
 			charset: CharsetUniversal::EMPTY_FALLBACK,
 			entry_mode: EntryMode::default(),
+			profile: Hd44780,
 			pins: FourBitBusPins {
 				rs: Unspecified,
 				en: Unspecified,
@@ -110,14 +212,70 @@ impl<M: DisplayMemoryMap>
 	}
 }
 
+impl<M: DisplayMemoryMap>
+	DisplayOptionsPort<M, EmptyFallback<CharsetUniversal>, Unspecified, Unspecified, Unspecified>
+{
+	pub fn new(memory_map: M) -> Self {
+		Self {
+			memory_map,
+			charset: CharsetUniversal::EMPTY_FALLBACK,
+			entry_mode: EntryMode::default(),
+			profile: Hd44780,
+			pins: PortBusPins { port: Unspecified, rs: Unspecified, en: Unspecified },
+			data_length: DataLength::EightBit,
+		}
+	}
+}
+
+impl<M: DisplayMemoryMap> DisplayOptionsShared<M, EmptyFallback<CharsetUniversal>, Unspecified> {
+	pub fn new(memory_map: M) -> Self {
+		Self {
+			memory_map,
+			charset: CharsetUniversal::EMPTY_FALLBACK,
+			entry_mode: EntryMode::default(),
+			profile: Hd44780,
+			bus: Unspecified,
+		}
+	}
+}
+
 impl<M: DisplayMemoryMap> DisplayOptionsI2C<M, EmptyFallback<CharsetUniversal>, Unspecified> {
 	pub fn new(memory_map: M) -> Self {
 		Self {
 			memory_map,
 			charset: CharsetUniversal::EMPTY_FALLBACK,
 			entry_mode: EntryMode::default(),
+			profile: Hd44780,
 			i2c_bus: Unspecified,
 			address: 0,
+			max_chunk_size: DEFAULT_CHUNK_SIZE,
+		}
+	}
+}
+
+impl<M: DisplayMemoryMap> DisplayOptionsSerialI2C<M, EmptyFallback<CharsetUniversal>, Unspecified> {
+	pub fn new(memory_map: M) -> Self {
+		Self {
+			memory_map,
+			charset: CharsetUniversal::EMPTY_FALLBACK,
+			entry_mode: EntryMode::default(),
+			profile: Hd44780,
+			i2c_bus: Unspecified,
+			address: DEFAULT_I2C_ADDRESS,
+			max_chunk_size: 1,
+		}
+	}
+}
+
+impl<M: DisplayMemoryMap> DisplayOptionsSpi<M, EmptyFallback<CharsetUniversal>, Unspecified, Unspecified> {
+	pub fn new(memory_map: M) -> Self {
+		Self {
+			memory_map,
+			charset: CharsetUniversal::EMPTY_FALLBACK,
+			entry_mode: EntryMode::default(),
+			profile: Hd44780,
+			spi: Unspecified,
+			rs: Unspecified,
 		}
 	}
 }
@@ -126,21 +284,23 @@ macro_rules! builder_functions {
 	(
 		$Options:ident < $($Gn:ident$(: $Gt:tt)?),* > { $($fn:ident),* }
 	) => {
-		impl<M: DisplayMemoryMap, C: CharsetWithFallback, $($Gn$(: $Gt)?),*> $Options<M, C, $($Gn),*> {
-			pub fn with_memory_map<M2: DisplayMemoryMap>(self, memory_map: M2) -> $Options<M2, C, $($Gn),*> {
+		impl<M: DisplayMemoryMap, C: CharsetWithFallback, $($Gn$(: $Gt)?,)* CP: ControllerProfile> $Options<M, C, $($Gn,)* CP> {
+			pub fn with_memory_map<M2: DisplayMemoryMap>(self, memory_map: M2) -> $Options<M2, C, $($Gn,)* CP> {
 				$Options {
 					memory_map,
 					charset: self.charset,
 					entry_mode: self.entry_mode,
+					profile: self.profile,
 					$($fn: self.$fn),*
 				}
 			}
 
-			pub fn with_charset<C2: CharsetWithFallback>(self, charset: C2) -> $Options<M, C2, $($Gn),*> {
+			pub fn with_charset<C2: CharsetWithFallback>(self, charset: C2) -> $Options<M, C2, $($Gn,)* CP> {
 				$Options {
 					memory_map: self.memory_map,
 					charset,
 					entry_mode: self.entry_mode,
+					profile: self.profile,
 					$($fn: self.$fn),*
 				}
 			}
@@ -149,16 +309,32 @@ macro_rules! builder_functions {
 				self.entry_mode = entry_mode;
 				self
 			}
+
+			/// Init sequence and timing of the controller, [`Hd44780`] unless a clone or an OLED
+			/// needs something else.
+			pub fn with_profile<CP2: ControllerProfile>(self, profile: CP2) -> $Options<M, C, $($Gn,)* CP2> {
+				$Options {
+					memory_map: self.memory_map,
+					charset: self.charset,
+					entry_mode: self.entry_mode,
+					profile,
+					$($fn: self.$fn),*
+				}
+			}
 		}
 	};
 }
 
 builder_functions!(DisplayOptions8Bit < RS, EN, D0, D1, D2, D3, D4, D5, D6, D7 > { pins });
 builder_functions!(DisplayOptions4Bit < RS, EN, D4, D5, D6, D7 > { pins });
-builder_functions!(DisplayOptionsI2C<I2C> { i2c_bus, address });
+builder_functions!(DisplayOptionsPort<P, RS, EN> { pins, data_length });
+builder_functions!(DisplayOptionsShared<B> { bus });
+builder_functions!(DisplayOptionsI2C<I2C> { i2c_bus, address, max_chunk_size });
+builder_functions!(DisplayOptionsSerialI2C<I2C> { i2c_bus, address, max_chunk_size });
+builder_functions!(DisplayOptionsSpi<SPI, RS> { spi, rs });
 
-impl<M: DisplayMemoryMap, C: CharsetWithFallback, RS, EN, D0, D1, D2, D3, D4, D5, D6, D7>
-	DisplayOptions8Bit<M, C, RS, EN, D0, D1, D2, D3, D4, D5, D6, D7>
+impl<M: DisplayMemoryMap, C: CharsetWithFallback, RS, EN, D0, D1, D2, D3, D4, D5, D6, D7, CP: ControllerProfile>
+	DisplayOptions8Bit<M, C, RS, EN, D0, D1, D2, D3, D4, D5, D6, D7, CP>
 {
 	/// The eight d0..d7 pins are used to send and recieve with
 	/// the `HD44780`.
@@ -166,16 +342,23 @@ impl<M: DisplayMemoryMap, C: CharsetWithFallback, RS, EN, D0, D1, D2, D3, D4, D5
 	/// if incoming data is a command or data.
 	/// The enable pin `en` is used to tell the `HD44780` that there
 	/// is data on the 8 data pins and that it should read them in.
+	#[allow(clippy::type_complexity)]
 	pub fn with_pins<RS2, EN2, D02, D12, D22, D32, D42, D52, D62, D72>(
 		self,
 		pins: EightBitBusPins<RS2, EN2, D02, D12, D22, D32, D42, D52, D62, D72>,
-	) -> DisplayOptions8Bit<M, C, RS2, EN2, D02, D12, D22, D32, D42, D52, D62, D72> {
-		DisplayOptions8Bit { memory_map: self.memory_map, charset: self.charset, entry_mode: self.entry_mode, pins }
+	) -> DisplayOptions8Bit<M, C, RS2, EN2, D02, D12, D22, D32, D42, D52, D62, D72, CP> {
+		DisplayOptions8Bit {
+			memory_map: self.memory_map,
+			charset: self.charset,
+			entry_mode: self.entry_mode,
+			profile: self.profile,
+			pins,
+		}
 	}
 }
 
-impl<M: DisplayMemoryMap, C: CharsetWithFallback, RS, EN, D4, D5, D6, D7>
-	DisplayOptions4Bit<M, C, RS, EN, D4, D5, D6, D7>
+impl<M: DisplayMemoryMap, C: CharsetWithFallback, RS, EN, D4, D5, D6, D7, CP: ControllerProfile>
+	DisplayOptions4Bit<M, C, RS, EN, D4, D5, D6, D7, CP>
 {
 	/// The four d4..d7 pins are used to send and recieve with
 	/// the `HD44780`.
@@ -186,19 +369,109 @@ impl<M: DisplayMemoryMap, C: CharsetWithFallback, RS, EN, D4, D5, D6, D7>
 	pub fn with_pins<RS2, EN2, D42, D52, D62, D72>(
 		self,
 		pins: FourBitBusPins<RS2, EN2, D42, D52, D62, D72>,
-	) -> DisplayOptions4Bit<M, C, RS2, EN2, D42, D52, D62, D72> {
-		DisplayOptions4Bit { memory_map: self.memory_map, charset: self.charset, entry_mode: self.entry_mode, pins }
+	) -> DisplayOptions4Bit<M, C, RS2, EN2, D42, D52, D62, D72, CP> {
+		DisplayOptions4Bit {
+			memory_map: self.memory_map,
+			charset: self.charset,
+			entry_mode: self.entry_mode,
+			profile: self.profile,
+			pins,
+		}
+	}
+}
+
+impl<M: DisplayMemoryMap, C: CharsetWithFallback, P, RS, EN, CP: ControllerProfile>
+	DisplayOptionsPort<M, C, P, RS, EN, CP>
+{
+	/// The `port` drives the data lines of the `HD44780` in a single write, see
+	/// [`ParallelPort`](crate::bus::ParallelPort). With [`DataLength::FourBit`] only `D4..D7`
+	/// are connected, on bits 4 to 7 of the port.
+	/// The register select pin `rs` is used to tell the `HD44780`
+	/// if incoming data is a command or data.
+	/// The enable pin `en` is used to tell the `HD44780` that there
+	/// is data on the data lines and that it should read them in.
+	pub fn with_port<P2, RS2, EN2>(
+		self,
+		pins: PortBusPins<P2, RS2, EN2>,
+		data_length: DataLength,
+	) -> DisplayOptionsPort<M, C, P2, RS2, EN2, CP> {
+		DisplayOptionsPort {
+			memory_map: self.memory_map,
+			charset: self.charset,
+			entry_mode: self.entry_mode,
+			profile: self.profile,
+			pins,
+			data_length,
+		}
 	}
 }
 
-impl<M: DisplayMemoryMap, C: CharsetWithFallback, I2C> DisplayOptionsI2C<M, C, I2C> {
-	pub fn with_i2c_bus<I2C2>(self, i2c_bus: I2C2, address: u8) -> DisplayOptionsI2C<M, C, I2C2> {
+impl<M: DisplayMemoryMap, C: CharsetWithFallback, B, CP: ControllerProfile> DisplayOptionsShared<M, C, B, CP> {
+	/// The bus of this display, created with [`SharedLines::bus`](crate::bus::SharedLines::bus)
+	/// from its enable pin.
+	pub fn with_bus<B2>(self, bus: B2) -> DisplayOptionsShared<M, C, B2, CP> {
+		DisplayOptionsShared {
+			memory_map: self.memory_map,
+			charset: self.charset,
+			entry_mode: self.entry_mode,
+			profile: self.profile,
+			bus,
+		}
+	}
+}
+
+impl<M: DisplayMemoryMap, C: CharsetWithFallback, I2C, CP: ControllerProfile> DisplayOptionsI2C<M, C, I2C, CP> {
+	pub fn with_i2c_bus<I2C2>(self, i2c_bus: I2C2, address: u8) -> DisplayOptionsI2C<M, C, I2C2, CP> {
 		DisplayOptionsI2C {
 			memory_map: self.memory_map,
 			charset: self.charset,
 			entry_mode: EntryMode::default(),
+			profile: self.profile,
+			i2c_bus,
+			address,
+			max_chunk_size: self.max_chunk_size,
+		}
+	}
+
+	/// Largest I2C write used to send text, see [`I2CBus::with_max_chunk_size`](crate::bus::I2CBus::with_max_chunk_size).
+	pub fn with_max_chunk_size(mut self, max_chunk_size: usize) -> Self {
+		self.max_chunk_size = max_chunk_size;
+		self
+	}
+}
+
+impl<M: DisplayMemoryMap, C: CharsetWithFallback, I2C, CP: ControllerProfile> DisplayOptionsSerialI2C<M, C, I2C, CP> {
+	/// Most controllers listen on [`DEFAULT_I2C_ADDRESS`](crate::bus::DEFAULT_I2C_ADDRESS).
+	pub fn with_i2c_bus<I2C2>(self, i2c_bus: I2C2, address: u8) -> DisplayOptionsSerialI2C<M, C, I2C2, CP> {
+		DisplayOptionsSerialI2C {
+			memory_map: self.memory_map,
+			charset: self.charset,
+			entry_mode: self.entry_mode,
+			profile: self.profile,
 			i2c_bus,
 			address,
+			max_chunk_size: self.max_chunk_size,
+		}
+	}
+
+	/// Data bytes per I2C write, see [`SerialControlBus::with_max_chunk_size`](crate::bus::SerialControlBus::with_max_chunk_size).
+	pub fn with_max_chunk_size(mut self, max_chunk_size: usize) -> Self {
+		self.max_chunk_size = max_chunk_size;
+		self
+	}
+}
+
+impl<M: DisplayMemoryMap, C: CharsetWithFallback, SPI, RS, CP: ControllerProfile> DisplayOptionsSpi<M, C, SPI, RS, CP> {
+	/// The `spi` device handles chip select, the register select pin `rs` tells the
+	/// controller if a byte is a command or data.
+	pub fn with_spi<SPI2, RS2>(self, spi: SPI2, rs: RS2) -> DisplayOptionsSpi<M, C, SPI2, RS2, CP> {
+		DisplayOptionsSpi {
+			memory_map: self.memory_map,
+			charset: self.charset,
+			entry_mode: self.entry_mode,
+			profile: self.profile,
+			spi,
+			rs,
 		}
 	}
 }
@@ -1,25 +1,28 @@
-use core::future::Future;
-
-use embedded_hal::digital::{self, OutputPin};
-use embedded_hal_async::{delay::DelayNs, i2c::I2c};
+use embedded_hal::digital::{ErrorKind, OutputPin};
+use embedded_hal_async::{delay::DelayNs, i2c::I2c, spi::SpiDevice};
 use sealed::SealedDisplayOptions;
 
 use crate::{
-	bus::{EightBitBus, FourBitBus, I2CBus},
+	bus::{EightBitBus, FourBitBus, I2CBus, I2cControl, ParallelPort, PortBus, SerialControlBus, SpiControl, SpiError},
 	charset::CharsetWithFallback,
+	display_size::DisplaySize,
 	entry_mode::EntryMode,
 	error::{Error, Result},
+	instruction::{DataLength, Lines},
+	lines_after_command,
 	memory_map::DisplayMemoryMap,
 	non_blocking::{bus::DataBus, HD44780},
+	profile::{ControllerProfile, ExecutionTimes, InitStep},
 	sealed::Internal,
 	DisplayMode,
 };
 
-use super::{DisplayOptions4Bit, DisplayOptions8Bit, DisplayOptionsI2C};
+use super::{
+	DisplayOptions4Bit, DisplayOptions8Bit, DisplayOptionsI2C, DisplayOptionsPort, DisplayOptionsSerialI2C,
+	DisplayOptionsSpi,
+};
 
 pub(crate) mod sealed {
-	use core::future::Future;
-
 	use embedded_hal_async::delay::DelayNs;
 
 	use crate::{
@@ -35,9 +38,8 @@ pub(crate) mod sealed {
 		type Charset: CharsetWithFallback;
 		type IoError: core::fmt::Debug;
 
-		type Future<'d, D: 'd + DelayNs>: Future<Output = DisplayOptionsResult<Self>>;
-
-		fn new_display<D: DelayNs>(self, delay: &mut D, _: Internal) -> Self::Future<'_, D>;
+		#[allow(async_fn_in_trait)]
+		async fn new_display<D: DelayNs>(self, delay: &mut D, _: Internal) -> DisplayOptionsResult<Self>;
 	}
 }
 
@@ -45,6 +47,9 @@ pub(crate) mod sealed {
 /// - [`DisplayOptionsI2C`]
 /// - [`DisplayOptions4Bit`]
 /// - [`DisplayOptions8Bit`]
+/// - [`DisplayOptionsPort`]
+/// - [`DisplayOptionsSerialI2C`]
+/// - [`DisplayOptionsSpi`]
 pub trait DisplayOptions: sealed::SealedDisplayOptions {}
 
 type HD44780FromOptions<Options> = HD44780<
@@ -56,222 +61,316 @@ type DisplayOptionsResult<Options> =
 	core::result::Result<HD44780FromOptions<Options>, (Options, Error<<Options as SealedDisplayOptions>::IoError>)>;
 
 impl<
-		M: DisplayMemoryMap + 'static,
-		C: CharsetWithFallback + 'static,
-		RS: OutputPin<Error = E> + 'static,
-		EN: OutputPin<Error = E> + 'static,
-		D0: OutputPin<Error = E> + 'static,
-		D1: OutputPin<Error = E> + 'static,
-		D2: OutputPin<Error = E> + 'static,
-		D3: OutputPin<Error = E> + 'static,
-		D4: OutputPin<Error = E> + 'static,
-		D5: OutputPin<Error = E> + 'static,
-		D6: OutputPin<Error = E> + 'static,
-		D7: OutputPin<Error = E> + 'static,
-		E: digital::Error,
-	> DisplayOptions for DisplayOptions8Bit<M, C, RS, EN, D0, D1, D2, D3, D4, D5, D6, D7>
+		M: DisplayMemoryMap,
+		C: CharsetWithFallback,
+		CP: ControllerProfile,
+		RS: OutputPin,
+		EN: OutputPin,
+		D0: OutputPin,
+		D1: OutputPin,
+		D2: OutputPin,
+		D3: OutputPin,
+		D4: OutputPin,
+		D5: OutputPin,
+		D6: OutputPin,
+		D7: OutputPin,
+	> DisplayOptions for DisplayOptions8Bit<M, C, RS, EN, D0, D1, D2, D3, D4, D5, D6, D7, CP>
 {
 }
 
 impl<
-		M: DisplayMemoryMap + 'static,
-		C: CharsetWithFallback + 'static,
-		RS: OutputPin<Error = E> + 'static,
-		EN: OutputPin<Error = E> + 'static,
-		D0: OutputPin<Error = E> + 'static,
-		D1: OutputPin<Error = E> + 'static,
-		D2: OutputPin<Error = E> + 'static,
-		D3: OutputPin<Error = E> + 'static,
-		D4: OutputPin<Error = E> + 'static,
-		D5: OutputPin<Error = E> + 'static,
-		D6: OutputPin<Error = E> + 'static,
-		D7: OutputPin<Error = E> + 'static,
-		E: digital::Error,
-	> SealedDisplayOptions for DisplayOptions8Bit<M, C, RS, EN, D0, D1, D2, D3, D4, D5, D6, D7>
+		M: DisplayMemoryMap,
+		C: CharsetWithFallback,
+		CP: ControllerProfile,
+		RS: OutputPin,
+		EN: OutputPin,
+		D0: OutputPin,
+		D1: OutputPin,
+		D2: OutputPin,
+		D3: OutputPin,
+		D4: OutputPin,
+		D5: OutputPin,
+		D6: OutputPin,
+		D7: OutputPin,
+	> SealedDisplayOptions for DisplayOptions8Bit<M, C, RS, EN, D0, D1, D2, D3, D4, D5, D6, D7, CP>
 {
 	type Bus = EightBitBus<RS, EN, D0, D1, D2, D3, D4, D5, D6, D7>;
 	type MemoryMap = M;
 	type Charset = C;
-	type IoError = E;
-
-	type Future<'d, D: 'd + DelayNs> = impl Future<Output = DisplayOptionsResult<Self>> + 'd;
-
-	fn new_display<D: DelayNs>(mut self, delay: &mut D, _: Internal) -> Self::Future<'_, D> {
-		async move {
-			let mut bus = EightBitBus::from_pins(self.pins);
-
-			if let Err(error) = init_8bit(&mut bus, &self.entry_mode, delay).await {
+	type IoError = ErrorKind;
+
+	async fn new_display<D: DelayNs>(mut self, delay: &mut D, _: Internal) -> DisplayOptionsResult<Self> {
+		let mut bus = EightBitBus::from_pins(self.pins);
+
+		let lines = match init(
+			&mut bus,
+			&self.profile,
+			DataLength::EightBit,
+			self.entry_mode,
+			self.memory_map.display_size(),
+			delay,
+		)
+		.await
+		{
+			Ok(lines) => lines,
+			Err(error) => {
 				self.pins = bus.destroy();
 				return Err((self, error));
 			}
+		};
 
-			Ok(HD44780::new_raw(bus, self.memory_map, self.charset, self.entry_mode, DisplayMode::default()))
-		}
+		Ok(HD44780::new_raw(bus, self.memory_map, self.charset, self.entry_mode, DisplayMode::default())
+			.with_lines(lines)
+			.with_execution_times(ExecutionTimes::of(&self.profile)))
 	}
 }
 
 impl<
-		M: DisplayMemoryMap + 'static,
-		C: CharsetWithFallback + 'static,
-		RS: OutputPin<Error = E> + 'static,
-		EN: OutputPin<Error = E> + 'static,
-		D4: OutputPin<Error = E> + 'static,
-		D5: OutputPin<Error = E> + 'static,
-		D6: OutputPin<Error = E> + 'static,
-		D7: OutputPin<Error = E> + 'static,
-		E: digital::Error,
-	> DisplayOptions for DisplayOptions4Bit<M, C, RS, EN, D4, D5, D6, D7>
+		M: DisplayMemoryMap,
+		C: CharsetWithFallback,
+		CP: ControllerProfile,
+		RS: OutputPin,
+		EN: OutputPin,
+		D4: OutputPin,
+		D5: OutputPin,
+		D6: OutputPin,
+		D7: OutputPin,
+	> DisplayOptions for DisplayOptions4Bit<M, C, RS, EN, D4, D5, D6, D7, CP>
 {
 }
 
 impl<
-		M: DisplayMemoryMap + 'static,
-		C: CharsetWithFallback + 'static,
-		RS: OutputPin<Error = E> + 'static,
-		EN: OutputPin<Error = E> + 'static,
-		D4: OutputPin<Error = E> + 'static,
-		D5: OutputPin<Error = E> + 'static,
-		D6: OutputPin<Error = E> + 'static,
-		D7: OutputPin<Error = E> + 'static,
-		E: digital::Error,
-	> SealedDisplayOptions for DisplayOptions4Bit<M, C, RS, EN, D4, D5, D6, D7>
+		M: DisplayMemoryMap,
+		C: CharsetWithFallback,
+		CP: ControllerProfile,
+		RS: OutputPin,
+		EN: OutputPin,
+		D4: OutputPin,
+		D5: OutputPin,
+		D6: OutputPin,
+		D7: OutputPin,
+	> SealedDisplayOptions for DisplayOptions4Bit<M, C, RS, EN, D4, D5, D6, D7, CP>
 {
 	type Bus = FourBitBus<RS, EN, D4, D5, D6, D7>;
 	type MemoryMap = M;
 	type Charset = C;
-	type IoError = E;
+	type IoError = ErrorKind;
+
+	async fn new_display<D: DelayNs>(mut self, delay: &mut D, _: Internal) -> DisplayOptionsResult<Self> {
+		let mut bus = FourBitBus::from_pins(self.pins);
+
+		let lines = match init(
+			&mut bus,
+			&self.profile,
+			DataLength::FourBit,
+			self.entry_mode,
+			self.memory_map.display_size(),
+			delay,
+		)
+		.await
+		{
+			Ok(lines) => lines,
+			Err(error) => {
+				self.pins = bus.destroy();
+				return Err((self, error));
+			}
+		};
 
-	type Future<'d, D: 'd + DelayNs> = impl Future<Output = DisplayOptionsResult<Self>> + 'd;
+		Ok(HD44780::new_raw(bus, self.memory_map, self.charset, self.entry_mode, DisplayMode::default())
+			.with_lines(lines)
+			.with_execution_times(ExecutionTimes::of(&self.profile)))
+	}
+}
 
-	fn new_display<D: DelayNs>(mut self, delay: &mut D, _: Internal) -> Self::Future<'_, D> {
-		async move {
-			let mut bus = FourBitBus::from_pins(self.pins);
+impl<
+		M: DisplayMemoryMap,
+		C: CharsetWithFallback,
+		CP: ControllerProfile,
+		P: ParallelPort,
+		RS: OutputPin,
+		EN: OutputPin,
+	> DisplayOptions for DisplayOptionsPort<M, C, P, RS, EN, CP>
+{
+}
 
-			if let Err(error) = init_4bit(&mut bus, &self.entry_mode, delay).await {
+impl<
+		M: DisplayMemoryMap,
+		C: CharsetWithFallback,
+		CP: ControllerProfile,
+		P: ParallelPort,
+		RS: OutputPin,
+		EN: OutputPin,
+	> SealedDisplayOptions for DisplayOptionsPort<M, C, P, RS, EN, CP>
+{
+	type Bus = PortBus<P, RS, EN>;
+	type MemoryMap = M;
+	type Charset = C;
+	type IoError = ErrorKind;
+
+	async fn new_display<D: DelayNs>(mut self, delay: &mut D, _: Internal) -> DisplayOptionsResult<Self> {
+		let mut bus = PortBus::from_pins(self.pins, self.data_length);
+
+		let lines = match init(
+			&mut bus,
+			&self.profile,
+			self.data_length,
+			self.entry_mode,
+			self.memory_map.display_size(),
+			delay,
+		)
+		.await
+		{
+			Ok(lines) => lines,
+			Err(error) => {
 				self.pins = bus.destroy();
 				return Err((self, error));
 			}
+		};
 
-			Ok(HD44780::new_raw(bus, self.memory_map, self.charset, self.entry_mode, DisplayMode::default()))
-		}
+		Ok(HD44780::new_raw(bus, self.memory_map, self.charset, self.entry_mode, DisplayMode::default())
+			.with_lines(lines)
+			.with_execution_times(ExecutionTimes::of(&self.profile)))
 	}
 }
 
-impl<M: DisplayMemoryMap + 'static, C: CharsetWithFallback + 'static, I2C: I2c + 'static> DisplayOptions
-	for DisplayOptionsI2C<M, C, I2C>
+impl<M: DisplayMemoryMap, C: CharsetWithFallback, CP: ControllerProfile, I2C: I2c> DisplayOptions
+	for DisplayOptionsI2C<M, C, I2C, CP>
 {
 }
 
-impl<M: DisplayMemoryMap + 'static, C: CharsetWithFallback + 'static, I2C: I2c + 'static> SealedDisplayOptions
-	for DisplayOptionsI2C<M, C, I2C>
+impl<M: DisplayMemoryMap, C: CharsetWithFallback, CP: ControllerProfile, I2C: I2c> SealedDisplayOptions
+	for DisplayOptionsI2C<M, C, I2C, CP>
 {
 	type Bus = I2CBus<I2C>;
 	type MemoryMap = M;
 	type Charset = C;
 	type IoError = I2C::Error;
 
-	type Future<'d, D: 'd + DelayNs> = impl Future<Output = DisplayOptionsResult<Self>> + 'd;
-
-	fn new_display<D: DelayNs>(mut self, delay: &mut D, _: Internal) -> Self::Future<'_, D> {
-		async move {
-			let mut bus = I2CBus::new(self.i2c_bus, self.address);
-
-			if let Err(error) = init_4bit(&mut bus, &self.entry_mode, delay).await {
+	async fn new_display<D: DelayNs>(mut self, delay: &mut D, _: Internal) -> DisplayOptionsResult<Self> {
+		let mut bus = I2CBus::new(self.i2c_bus, self.address).with_max_chunk_size(self.max_chunk_size);
+
+		let lines = match init(
+			&mut bus,
+			&self.profile,
+			DataLength::FourBit,
+			self.entry_mode,
+			self.memory_map.display_size(),
+			delay,
+		)
+		.await
+		{
+			Ok(lines) => lines,
+			Err(error) => {
 				self.i2c_bus = bus.destroy();
 				return Err((self, error));
 			}
+		};
 
-			Ok(HD44780::new_raw(bus, self.memory_map, self.charset, self.entry_mode, DisplayMode::default()))
-		}
+		Ok(HD44780::new_raw(bus, self.memory_map, self.charset, self.entry_mode, DisplayMode::default())
+			.with_lines(lines)
+			.with_execution_times(ExecutionTimes::of(&self.profile)))
 	}
 }
 
-// Follow the 8-bit setup procedure as specified in the HD44780 datasheet
-async fn init_8bit<B: DataBus, D: DelayNs>(bus: &mut B, entry_mode: &EntryMode, delay: &mut D) -> Result<(), B::Error> {
-	// Wait for the LCD to wakeup if it was off
-	delay.delay_ms(15).await;
-
-	// Initialize Lcd in 8-bit mode
-	bus.write(0b0011_0000, false, delay).await?;
-
-	// Wait for the command to be processed
-	delay.delay_ms(5).await;
-
-	// Sets 8-bit operation and enables 5x7 mode for chars
-	bus.write(0b0011_1000, false, delay).await?;
-
-	// Wait for the command to be processed
-	delay.delay_us(100).await;
-
-	bus.write(0b0000_1110, false, delay).await?;
-
-	// Wait for the command to be processed
-	delay.delay_us(100).await;
-
-	// Clear Display
-	bus.write(0b0000_0001, false, delay).await?;
-
-	// Wait for the command to be processed
-	delay.delay_us(100).await;
-
-	// Move the cursor to beginning of first line
-	bus.write(0b000_0111, false, delay).await?;
-
-	// Wait for the command to be processed
-	delay.delay_us(100).await;
-
-	// Set entry mode
-	bus.write(entry_mode.as_byte(), false, delay).await?;
-
-	// Wait for the command to be processed
-	delay.delay_us(100).await;
-
-	Ok(())
+impl<M: DisplayMemoryMap, C: CharsetWithFallback, CP: ControllerProfile, I2C: I2c> DisplayOptions
+	for DisplayOptionsSerialI2C<M, C, I2C, CP>
+{
 }
 
-async fn init_4bit<B: DataBus, D: DelayNs>(bus: &mut B, entry_mode: &EntryMode, delay: &mut D) -> Result<(), B::Error> {
-	// Wait for the LCD to wakeup if it was off
-	delay.delay_ms(15).await;
-
-	// Initialize Lcd in 4-bit mode
-	bus.write(0x33, false, delay).await?;
-
-	// Wait for the command to be processed
-	delay.delay_ms(5).await;
-
-	// Sets 4-bit operation and enables 5x7 mode for chars
-	bus.write(0x32, false, delay).await?;
-
-	// Wait for the command to be processed
-	delay.delay_us(100).await;
-
-	bus.write(0x28, false, delay).await?;
-
-	// Wait for the command to be processed
-	delay.delay_us(100).await;
-
-	// Clear Display
-	bus.write(0x0E, false, delay).await?;
-
-	// Wait for the command to be processed
-	delay.delay_us(100).await;
+impl<M: DisplayMemoryMap, C: CharsetWithFallback, CP: ControllerProfile, I2C: I2c> SealedDisplayOptions
+	for DisplayOptionsSerialI2C<M, C, I2C, CP>
+{
+	type Bus = SerialControlBus<I2cControl<I2C>>;
+	type MemoryMap = M;
+	type Charset = C;
+	type IoError = I2C::Error;
 
-	// Move the cursor to beginning of first line
-	bus.write(0x01, false, delay).await?;
+	async fn new_display<D: DelayNs>(mut self, delay: &mut D, _: Internal) -> DisplayOptionsResult<Self> {
+		let mut bus = SerialControlBus::i2c(self.i2c_bus, self.address).with_max_chunk_size(self.max_chunk_size);
+
+		let lines = match init(
+			&mut bus,
+			&self.profile,
+			DataLength::EightBit,
+			self.entry_mode,
+			self.memory_map.display_size(),
+			delay,
+		)
+		.await
+		{
+			Ok(lines) => lines,
+			Err(error) => {
+				self.i2c_bus = bus.destroy();
+				return Err((self, error));
+			}
+		};
 
-	// Wait for the command to be processed
-	delay.delay_us(100).await;
+		Ok(HD44780::new_raw(bus, self.memory_map, self.charset, self.entry_mode, DisplayMode::default())
+			.with_lines(lines)
+			.with_execution_times(ExecutionTimes::of(&self.profile)))
+	}
+}
 
-	// Set entry mode
-	bus.write(entry_mode.as_byte(), false, delay).await?;
+impl<M: DisplayMemoryMap, C: CharsetWithFallback, CP: ControllerProfile, SPI: SpiDevice, RS: OutputPin> DisplayOptions
+	for DisplayOptionsSpi<M, C, SPI, RS, CP>
+{
+}
 
-	// Wait for the command to be processed
-	delay.delay_us(100).await;
+impl<M: DisplayMemoryMap, C: CharsetWithFallback, CP: ControllerProfile, SPI: SpiDevice, RS: OutputPin>
+	SealedDisplayOptions for DisplayOptionsSpi<M, C, SPI, RS, CP>
+{
+	type Bus = SerialControlBus<SpiControl<SPI, RS>>;
+	type MemoryMap = M;
+	type Charset = C;
+	type IoError = SpiError<SPI::Error, RS::Error>;
+
+	async fn new_display<D: DelayNs>(mut self, delay: &mut D, _: Internal) -> DisplayOptionsResult<Self> {
+		let mut bus = SerialControlBus::spi(self.spi, self.rs);
+
+		let lines = match init(
+			&mut bus,
+			&self.profile,
+			DataLength::EightBit,
+			self.entry_mode,
+			self.memory_map.display_size(),
+			delay,
+		)
+		.await
+		{
+			Ok(lines) => lines,
+			Err(error) => {
+				(self.spi, self.rs) = bus.destroy();
+				return Err((self, error));
+			}
+		};
 
-	bus.write(0x80, false, delay).await?;
+		Ok(HD44780::new_raw(bus, self.memory_map, self.charset, self.entry_mode, DisplayMode::default())
+			.with_lines(lines)
+			.with_execution_times(ExecutionTimes::of(&self.profile)))
+	}
+}
 
-	// Wait for the command to be processed
-	delay.delay_us(100).await;
+async fn init<B: DataBus, CP: ControllerProfile, D: DelayNs>(
+	bus: &mut B,
+	profile: &CP,
+	data_length: DataLength,
+	entry_mode: EntryMode,
+	size: DisplaySize,
+	delay: &mut D,
+) -> Result<Lines, B::Error> {
+	// The controller resets to one line, until a function set says otherwise
+	let mut lines = Lines::One;
+
+	for step in profile.init_sequence(data_length, entry_mode, size).steps() {
+		match *step {
+			InitStep::Write(byte) => {
+				bus.write(byte, false, delay).await?;
+				lines = lines_after_command(lines, byte);
+			}
+			InitStep::Data(byte) => bus.write(byte, true, delay).await?,
+			InitStep::Wait { us } => delay.delay_us(us).await,
+		}
+	}
 
-	Ok(())
+	Ok(lines)
 }
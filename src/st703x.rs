@@ -0,0 +1,418 @@
+use core::ops::{Deref, DerefMut};
+
+use embedded_hal::delay::DelayNs;
+
+use crate::{
+	bus::DataBus,
+	charset::CharsetWithFallback,
+	display_size::DisplaySize,
+	entry_mode::EntryMode,
+	error::Result,
+	instruction::{DataLength, Font, Instruction, Lines},
+	memory_map::DisplayMemoryMap,
+	profile::{ControllerProfile, ExecutionTimes, InitSequence, InitStep},
+	setup::INIT_DISPLAY_CONTROL,
+	HD44780,
+};
+
+/// Which of the two controllers drives the display. They share the extended instruction set,
+/// except for the double-height position select of the `ST7036`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub enum Controller {
+	St7032,
+	St7036,
+}
+
+/// Bias of the LCD drive voltage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub enum Bias {
+	OneFifth,
+	OneFourth,
+}
+
+/// Lines shown in double height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub enum DoubleHeight {
+	Off,
+	/// The upper line is doubled. On an `ST7036` with three lines, the upper two are merged.
+	Top,
+	/// The lower two lines of an `ST7036` with three lines are merged. The `ST7032` has no
+	/// position select, it treats this like [`DoubleHeight::Top`].
+	Bottom,
+}
+
+/// Settings of the internal LCD power supply, applied by the init sequence of [`St703xProfile`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct St703xConfig {
+	pub controller: Controller,
+	/// Contrast, `0..=63`.
+	pub contrast: u8,
+	/// Voltage booster, needed when running from 3.3V. Turn it off for 5V.
+	pub booster: bool,
+	/// Amplification ratio of the voltage follower, `0..=7`.
+	pub follower_ratio: u8,
+	pub bias: Bias,
+	/// Internal oscillator frequency adjustment, `0..=7`.
+	pub oscillator: u8,
+	/// Show the icons stored in the icon RAM.
+	pub icons: bool,
+}
+
+impl St703xConfig {
+	/// Settings from the datasheet example for 3.3V.
+	pub const fn new(controller: Controller) -> Self {
+		Self {
+			controller,
+			contrast: 32,
+			booster: true,
+			follower_ratio: 4,
+			bias: Bias::OneFifth,
+			oscillator: 4,
+			icons: false,
+		}
+	}
+}
+
+/// Instruction table selected by the `IS` bits of function set.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Table {
+	Normal = 0b00,
+	Extended = 0b01,
+	/// Only exists on the `ST7036`.
+	DoubleHeight = 0b10,
+}
+
+/// Encodes the extended instructions for the current settings.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct State {
+	pub(crate) config: St703xConfig,
+	pub(crate) double_height: DoubleHeight,
+	pub(crate) data_length: DataLength,
+	pub(crate) lines: Lines,
+}
+
+impl State {
+	/// Settings after the extended init sequence, for a display of `size`.
+	pub(crate) fn new(config: St703xConfig, data_length: DataLength, size: DisplaySize) -> Self {
+		// Three line `ST7036` displays also use the two line mode, see `DoubleHeight`
+		let lines = if size.get().1 > 1 { Lines::Two } else { Lines::One };
+
+		Self { config, double_height: DoubleHeight::Off, data_length, lines }
+	}
+
+	pub(crate) const fn function_set(&self, table: Table) -> u8 {
+		// Neither controller has a 5x10 font, the font bit selects double height instead
+		let function_set =
+			Instruction::FunctionSet { data_length: self.data_length, lines: self.lines, font: Font::Dots5x8 };
+		let double_height = match self.double_height {
+			DoubleHeight::Off => 0,
+			DoubleHeight::Top | DoubleHeight::Bottom => 0b100,
+		};
+
+		function_set.to_byte() | double_height | table as u8
+	}
+
+	pub(crate) const fn oscillator(&self) -> u8 {
+		let bias = match self.config.bias {
+			Bias::OneFifth => 0,
+			Bias::OneFourth => 0b1000,
+		};
+
+		0b0001_0000 | bias | (self.config.oscillator & 0b111)
+	}
+
+	/// Icon display, booster and upper two bits of the contrast.
+	pub(crate) const fn power(&self) -> u8 {
+		let icons = if self.config.icons { 0b1000 } else { 0 };
+		let booster = if self.config.booster { 0b0100 } else { 0 };
+
+		0b0101_0000 | icons | booster | ((self.config.contrast >> 4) & 0b11)
+	}
+
+	pub(crate) const fn follower(&self) -> u8 {
+		0b0110_1000 | (self.config.follower_ratio & 0b111)
+	}
+
+	/// Lower four bits of the contrast.
+	pub(crate) const fn contrast(&self) -> u8 {
+		0b0111_0000 | (self.config.contrast & 0b1111)
+	}
+
+	/// Double height position select, if the controller has one.
+	pub(crate) const fn double_height_position(&self) -> Option<u8> {
+		match (self.config.controller, self.double_height) {
+			(Controller::St7036, DoubleHeight::Top) => Some(0b0001_1000),
+			(Controller::St7036, DoubleHeight::Bottom) => Some(0b0001_0000),
+			_ => None,
+		}
+	}
+
+	pub(crate) const fn icon_address(address: u8) -> u8 {
+		0b0100_0000 | (address & 0b1111)
+	}
+}
+
+/// Init sequence of an `ST7032` or `ST7036`, selected with `with_profile` on the
+/// [`setup`](crate::setup) options in place of the `HD44780` one. Between the function sets it
+/// runs the extended instructions, powering up the LCD voltage generator.
+///
+/// ```rust,ignore
+/// let config = St703xConfig::new(Controller::St7032);
+/// let options = DisplayOptionsSerialI2C::new(MemoryMap1602::new())
+///     .with_i2c_bus(i2c, 0x3E)
+///     .with_profile(St703xProfile::new(config));
+/// let mut lcd = St703x::new(HD44780::new(options, &mut delay)?, config);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct St703xProfile {
+	config: St703xConfig,
+}
+
+impl St703xProfile {
+	pub const fn new(config: St703xConfig) -> Self {
+		Self { config }
+	}
+}
+
+impl ControllerProfile for St703xProfile {
+	fn power_on_delay_ms(&self) -> u32 {
+		40
+	}
+
+	fn init_sequence(&self, data_length: DataLength, entry_mode: EntryMode, size: DisplaySize) -> InitSequence {
+		let state = State::new(self.config, data_length, size);
+		let execution_times = ExecutionTimes::of(self);
+
+		let mut sequence = InitSequence::new();
+		sequence.push(InitStep::Wait { us: self.power_on_delay_ms() * 1000 });
+		sequence.synchronise(data_length);
+
+		for byte in
+			[state.function_set(Table::Extended), state.oscillator(), state.contrast(), state.power(), state.follower()]
+		{
+			sequence.push(InitStep::Write(byte));
+			sequence.push(InitStep::Wait { us: execution_times.command(byte) });
+		}
+
+		// Wait for the power to stabilise
+		sequence.push(InitStep::Wait { us: 200_000 });
+		let function_set = state.function_set(Table::Normal);
+		sequence.push(InitStep::Write(function_set));
+		sequence.push(InitStep::Wait { us: execution_times.command(function_set) });
+
+		for instruction in [
+			INIT_DISPLAY_CONTROL,
+			Instruction::ClearDisplay,
+			Instruction::EntryModeSet(entry_mode),
+			Instruction::SetDdramAddr(0),
+		] {
+			sequence.instruction(instruction, self.execution_time_us(instruction));
+		}
+
+		sequence
+	}
+}
+
+/// [`HD44780`] on an `ST7032` or `ST7036` controller, as used by EA DOGM, Newhaven and Midas
+/// displays. Adds software contrast, the voltage booster, double height lines and icons.
+///
+/// All methods of [`HD44780`] are available through `Deref`:
+///
+/// ```rust,ignore
+/// let config = St703xConfig::new(Controller::St7032);
+/// let mut lcd = St703x::new(HD44780::new(options.with_profile(St703xProfile::new(config)), &mut delay)?, config);
+///
+/// lcd.set_contrast(40, &mut delay)?;
+/// lcd.write_str("Hello", &mut delay)?;
+/// ```
+pub struct St703x<B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback> {
+	lcd: HD44780<B, M, C>,
+	state: State,
+}
+
+impl<B, M, C> St703x<B, M, C>
+where
+	B: DataBus,
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+{
+	/// Wrap a display set up with [`HD44780::new`] and an [`St703xProfile`] of the same `config`.
+	pub fn new(lcd: HD44780<B, M, C>, config: St703xConfig) -> Self {
+		let state = State::new(config, lcd.bus.data_length(), lcd.display_size());
+
+		Self { lcd, state }
+	}
+
+	pub fn into_inner(self) -> HD44780<B, M, C> {
+		self.lcd
+	}
+
+	pub fn config(&self) -> &St703xConfig {
+		&self.state.config
+	}
+
+	/// Set the contrast, values above 63 are clamped.
+	pub fn set_contrast<D: DelayNs>(&mut self, contrast: u8, delay: &mut D) -> Result<(), B::Error> {
+		self.state.config.contrast = contrast.min(63);
+
+		self.command(self.state.function_set(Table::Extended), delay)?;
+		self.command(self.state.contrast(), delay)?;
+		self.command(self.state.power(), delay)?;
+		self.command(self.state.function_set(Table::Normal), delay)
+	}
+
+	pub fn set_booster<D: DelayNs>(&mut self, on: bool, delay: &mut D) -> Result<(), B::Error> {
+		self.state.config.booster = on;
+
+		self.extended(Table::Extended, self.state.power(), delay)
+	}
+
+	/// Show or hide the icons, see [write_icon](#method.write_icon).
+	pub fn set_icons<D: DelayNs>(&mut self, on: bool, delay: &mut D) -> Result<(), B::Error> {
+		self.state.config.icons = on;
+
+		self.extended(Table::Extended, self.state.power(), delay)
+	}
+
+	pub fn set_double_height<D: DelayNs>(
+		&mut self,
+		double_height: DoubleHeight,
+		delay: &mut D,
+	) -> Result<(), B::Error> {
+		self.state.double_height = double_height;
+
+		match self.state.double_height_position() {
+			Some(position) => self.extended(Table::DoubleHeight, position, delay),
+			None => self.command(self.state.function_set(Table::Normal), delay),
+		}
+	}
+
+	/// Store the segments of one of the 16 icon RAM addresses, bit 4 being the leftmost one.
+	/// Which segments exist depends on the glass.
+	pub fn write_icon<D: DelayNs>(&mut self, address: u8, segments: u8, delay: &mut D) -> Result<(), B::Error> {
+		self.command(self.state.function_set(Table::Extended), delay)?;
+		self.command(State::icon_address(address), delay)?;
+		self.lcd.bus.write(segments & 0b1_1111, true, delay)?;
+		delay.delay_us(self.lcd.execution_times.data());
+		self.command(self.state.function_set(Table::Normal), delay)?;
+
+		// Text has to go to the DDRAM again
+		self.command(Instruction::SetDdramAddr(self.lcd.ddram_address).to_byte(), delay)
+	}
+
+	/// Send an instruction from `table` and switch back to the normal instructions.
+	fn extended<D: DelayNs>(&mut self, table: Table, byte: u8, delay: &mut D) -> Result<(), B::Error> {
+		self.command(self.state.function_set(table), delay)?;
+		self.command(byte, delay)?;
+		self.command(self.state.function_set(Table::Normal), delay)
+	}
+
+	/// Extended instructions share their encoding with the normal ones, so they bypass the
+	/// address tracking of [`HD44780::write_command`].
+	fn command<D: DelayNs>(&mut self, byte: u8, delay: &mut D) -> Result<(), B::Error> {
+		self.lcd.bus.write(byte, false, delay)?;
+
+		// Wait for the command to be processed
+		delay.delay_us(self.lcd.execution_times.command(byte));
+		Ok(())
+	}
+}
+
+impl<B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback> Deref for St703x<B, M, C> {
+	type Target = HD44780<B, M, C>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.lcd
+	}
+}
+
+impl<B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback> DerefMut for St703x<B, M, C> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.lcd
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		charset::CharsetUniversal,
+		entry_mode::EntryMode,
+		memory_map::MemoryMap1602,
+		test_util::{NoDelay, Recorder},
+		DisplayMode,
+	};
+
+	fn display(controller: Controller) -> St703x<Recorder, MemoryMap1602, impl CharsetWithFallback> {
+		let lcd = HD44780::new_raw(
+			Recorder::default(),
+			MemoryMap1602::new(),
+			CharsetUniversal::QUESTION_FALLBACK,
+			EntryMode::default(),
+			DisplayMode::default(),
+		);
+
+		St703x::new(lcd, St703xConfig::new(controller))
+	}
+
+	fn commands(lcd: St703x<Recorder, MemoryMap1602, impl CharsetWithFallback>) -> ([u8; 32], usize) {
+		let bus = lcd.into_inner().destroy();
+		let mut bytes = [0; 32];
+		for (byte, &(write, _)) in bytes.iter_mut().zip(bus.writes()) {
+			*byte = write;
+		}
+
+		(bytes, bus.writes().len())
+	}
+
+	#[test]
+	fn extended_init() {
+		let profile = St703xProfile::new(St703xConfig::new(Controller::St7032));
+		let sequence = profile.init_sequence(DataLength::FourBit, EntryMode::default(), DisplaySize::new(16, 2));
+		let writes = sequence.steps().iter().filter_map(|step| match *step {
+			InitStep::Write(byte) => Some(byte),
+			InitStep::Data(_) | InitStep::Wait { .. } => None,
+		});
+
+		// Datasheet example for a 3.3V 4-bit interface, between synchronisation and the usual end
+		assert!(writes.eq([0x33, 0x32, 0x29, 0x14, 0x70, 0x56, 0x6C, 0x28, 0x0E, 0x01, 0x06, 0x80]));
+		assert!(sequence.steps().contains(&InitStep::Wait { us: 200_000 }));
+	}
+
+	#[test]
+	fn one_line_function_set() {
+		let state = State::new(St703xConfig::new(Controller::St7032), DataLength::FourBit, DisplaySize::new(16, 1));
+		assert_eq!(state.function_set(Table::Extended), 0x21);
+		assert_eq!(state.function_set(Table::Normal), 0x20);
+	}
+
+	#[test]
+	fn contrast_and_double_height() {
+		let mut lcd = display(Controller::St7036);
+		lcd.set_contrast(0x3F + 1, &mut NoDelay).unwrap();
+		lcd.set_double_height(DoubleHeight::Bottom, &mut NoDelay).unwrap();
+
+		let (bytes, len) = commands(lcd);
+		assert_eq!(bytes[..len], [0x29, 0x7F, 0x57, 0x28, 0x2E, 0x10, 0x2C]);
+	}
+
+	#[test]
+	fn icons_leave_ddram_selected() {
+		let mut lcd = display(Controller::St7032);
+		lcd.set_cursor_pos(0x41, &mut NoDelay).unwrap();
+		lcd.write_icon(3, 0xFF, &mut NoDelay).unwrap();
+
+		let bus = lcd.into_inner().destroy();
+		assert_eq!(&bus.writes()[1..], &[(0x29, false), (0x43, false), (0x1F, true), (0x28, false), (0xC1, false)]);
+	}
+}
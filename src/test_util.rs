@@ -0,0 +1,153 @@
+use core::convert::Infallible;
+
+use embedded_hal::digital::{ErrorType, OutputPin};
+use embedded_hal::i2c::{self, I2c, Operation};
+
+use crate::{bus::DataBus, error::Result};
+
+/// Records every write on the bus.
+pub(crate) struct Recorder {
+	writes: [(u8, bool); 256],
+	len: usize,
+}
+
+impl Default for Recorder {
+	fn default() -> Self {
+		Self { writes: [(0, false); 256], len: 0 }
+	}
+}
+
+impl Recorder {
+	pub(crate) fn writes(&self) -> &[(u8, bool)] {
+		&self.writes[..self.len]
+	}
+
+	pub(crate) fn commands(&self) -> impl Iterator<Item = u8> + '_ {
+		self.writes().iter().filter(|(_, data)| !data).map(|&(byte, _)| byte)
+	}
+
+	pub(crate) fn data(&self) -> impl Iterator<Item = u8> + '_ {
+		self.writes().iter().filter(|(_, data)| *data).map(|&(byte, _)| byte)
+	}
+
+	fn record(&mut self, byte: u8, data: bool) {
+		self.writes[self.len] = (byte, data);
+		self.len += 1;
+	}
+}
+
+impl DataBus for Recorder {
+	type Error = Infallible;
+
+	fn write<D: embedded_hal::delay::DelayNs>(
+		&mut self,
+		byte: u8,
+		data: bool,
+		_delay: &mut D,
+	) -> Result<(), Infallible> {
+		self.record(byte, data);
+		Ok(())
+	}
+}
+
+#[cfg(feature = "async")]
+impl crate::non_blocking::bus::DataBus for Recorder {
+	type Error = Infallible;
+
+	async fn write<D: embedded_hal_async::delay::DelayNs>(
+		&mut self,
+		byte: u8,
+		data: bool,
+		_delay: &mut D,
+	) -> Result<(), Infallible> {
+		self.record(byte, data);
+		Ok(())
+	}
+}
+
+/// Records the I2C writes.
+pub(crate) struct I2cRecorder {
+	bytes: [u8; 512],
+	ends: [usize; 16],
+	len: usize,
+	pub(crate) address: Option<u8>,
+}
+
+impl Default for I2cRecorder {
+	fn default() -> Self {
+		Self { bytes: [0; 512], ends: [0; 16], len: 0, address: None }
+	}
+}
+
+impl I2cRecorder {
+	pub(crate) fn writes(&self) -> impl Iterator<Item = &[u8]> + '_ {
+		(0..self.len).map(|i| {
+			let start = if i == 0 { 0 } else { self.ends[i - 1] };
+			&self.bytes[start..self.ends[i]]
+		})
+	}
+}
+
+impl i2c::ErrorType for I2cRecorder {
+	type Error = Infallible;
+}
+
+impl I2c for I2cRecorder {
+	fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> core::result::Result<(), Infallible> {
+		self.address = Some(address);
+
+		for operation in operations {
+			if let Operation::Write(write) = operation {
+				let start = if self.len == 0 { 0 } else { self.ends[self.len - 1] };
+				self.bytes[start..][..write.len()].copy_from_slice(write);
+				self.ends[self.len] = start + write.len();
+				self.len += 1;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Output pin remembering its level.
+#[derive(Default)]
+pub(crate) struct Pin(pub(crate) bool);
+
+impl ErrorType for Pin {
+	type Error = Infallible;
+}
+
+impl OutputPin for Pin {
+	fn set_low(&mut self) -> core::result::Result<(), Infallible> {
+		self.0 = false;
+		Ok(())
+	}
+
+	fn set_high(&mut self) -> core::result::Result<(), Infallible> {
+		self.0 = true;
+		Ok(())
+	}
+}
+
+pub(crate) struct NoDelay;
+
+impl embedded_hal::delay::DelayNs for NoDelay {
+	fn delay_ns(&mut self, _ns: u32) {}
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::delay::DelayNs for NoDelay {
+	async fn delay_ns(&mut self, _ns: u32) {}
+}
+
+/// Runs `future`, which must not wait for anything, to completion.
+#[cfg(feature = "async")]
+pub(crate) fn block_on<F: core::future::Future>(future: F) -> F::Output {
+	let mut future = core::pin::pin!(future);
+	let mut context = core::task::Context::from_waker(core::task::Waker::noop());
+	loop {
+		if let core::task::Poll::Ready(output) = future.as_mut().poll(&mut context) {
+			return output;
+		}
+	}
+}
@@ -0,0 +1,270 @@
+use embedded_hal::delay::DelayNs;
+
+use crate::{
+	bus::DataBus,
+	charset::CharsetWithFallback,
+	display_size::DisplaySize,
+	entry_mode::CursorMode,
+	error::{Error, Result},
+	glyph::{Glyph, Glyph5x8, GlyphBank},
+	memory_map::DisplayMemoryMap,
+	setup::blocking::{sealed::SealedDisplayOptions, DisplayOptions},
+	Cursor, CursorBlink, Direction, Display, DisplayMode, Instruction, HD44780,
+};
+
+/// A [`HD44780`] which owns its delay, so none of the methods take one.
+///
+/// Create it with [HD44780::new_with_delay] or [HD44780::with_delay]. As it implements
+/// [`core::fmt::Write`], it works with `write!`:
+///
+/// ```rust,ignore
+/// let mut lcd = HD44780::new_with_delay(options, delay)?;
+///
+/// lcd.clear()?;
+/// write!(lcd, "{} C", temperature)?;
+/// ```
+pub struct HD44780WithDelay<B: DataBus, M: DisplayMemoryMap, C: CharsetWithFallback, D: DelayNs> {
+	lcd: HD44780<B, M, C>,
+	delay: D,
+}
+
+type NewWithDelayResult<Options, D> = core::result::Result<
+	HD44780WithDelay<
+		<Options as SealedDisplayOptions>::Bus,
+		<Options as SealedDisplayOptions>::MemoryMap,
+		<Options as SealedDisplayOptions>::Charset,
+		D,
+	>,
+	(Options, D, Error<<Options as SealedDisplayOptions>::IoError>),
+>;
+
+impl<B, M, C> HD44780<B, M, C>
+where
+	B: DataBus,
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+{
+	/// Like [new](#method.new), but the delay is moved into the driver.
+	///
+	/// If there was an error when setting up the display, the settings and the delay
+	/// are returned together with the error.
+	pub fn new_with_delay<Opt, D: DelayNs>(options: Opt, mut delay: D) -> NewWithDelayResult<Opt, D>
+	where
+		Opt: DisplayOptions<Bus = B, MemoryMap = M, Charset = C>,
+	{
+		match Self::new(options, &mut delay) {
+			Ok(lcd) => Ok(lcd.with_delay(delay)),
+			Err((options, error)) => Err((options, delay, error)),
+		}
+	}
+
+	/// Move a delay into the driver.
+	pub fn with_delay<D: DelayNs>(self, delay: D) -> HD44780WithDelay<B, M, C, D> {
+		HD44780WithDelay { lcd: self, delay }
+	}
+}
+
+impl<B, M, C, D> HD44780WithDelay<B, M, C, D>
+where
+	B: DataBus,
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+	D: DelayNs,
+{
+	/// Get back the driver and the delay.
+	pub fn into_parts(self) -> (HD44780<B, M, C>, D) {
+		(self.lcd, self.delay)
+	}
+
+	pub fn destroy(self) -> (B, D) {
+		(self.lcd.destroy(), self.delay)
+	}
+
+	/// Get the memory map information for this display.
+	pub fn memory_map(&self) -> &M {
+		self.lcd.memory_map()
+	}
+
+	/// Get the display size.
+	pub fn display_size(&self) -> DisplaySize {
+		self.lcd.display_size()
+	}
+
+	/// Number of cells `text` takes up when written with [write_str](#method.write_str).
+	pub fn text_width(&self, text: &str) -> usize {
+		self.lcd.text_width(text)
+	}
+
+	/// Unshifts the display and sets the cursor position to 0. See [reset](HD44780::reset).
+	pub fn reset(&mut self) -> Result<(), B::Error> {
+		self.lcd.reset(&mut self.delay)
+	}
+
+	/// Set if the display should be on, if the cursor should be
+	/// visible, and if the cursor should blink. See [set_display_mode](HD44780::set_display_mode).
+	pub fn set_display_mode(&mut self, display_mode: DisplayMode) -> Result<(), B::Error> {
+		self.lcd.set_display_mode(display_mode, &mut self.delay)
+	}
+
+	/// Clear the entire display. See [clear](HD44780::clear).
+	pub fn clear(&mut self) -> Result<(), B::Error> {
+		self.lcd.clear(&mut self.delay)
+	}
+
+	/// Turn the backlight on or off. See [set_backlight](HD44780::set_backlight).
+	pub fn set_backlight(&mut self, on: bool) -> Result<(), B::Error> {
+		self.lcd.set_backlight(on, &mut self.delay)
+	}
+
+	/// If enabled, automatically scroll the display when a new
+	/// character is written to the display. See [set_autoscroll](HD44780::set_autoscroll).
+	pub fn set_autoscroll(&mut self, enabled: bool) -> Result<(), B::Error> {
+		self.lcd.set_autoscroll(enabled, &mut self.delay)
+	}
+
+	/// Set if the cursor should be visible. See [set_cursor_visibility](HD44780::set_cursor_visibility).
+	pub fn set_cursor_visibility(&mut self, visibility: Cursor) -> Result<(), B::Error> {
+		self.lcd.set_cursor_visibility(visibility, &mut self.delay)
+	}
+
+	/// Set if the characters on the display should be visible. See [set_display](HD44780::set_display).
+	pub fn set_display(&mut self, display: Display) -> Result<(), B::Error> {
+		self.lcd.set_display(display, &mut self.delay)
+	}
+
+	/// Set if the cursor should blink. See [set_cursor_blink](HD44780::set_cursor_blink).
+	pub fn set_cursor_blink(&mut self, blink: CursorBlink) -> Result<(), B::Error> {
+		self.lcd.set_cursor_blink(blink, &mut self.delay)
+	}
+
+	/// Set which way the cursor will move when a new character is written. See [set_cursor_mode](HD44780::set_cursor_mode).
+	pub fn set_cursor_mode(&mut self, mode: CursorMode) -> Result<(), B::Error> {
+		self.lcd.set_cursor_mode(mode, &mut self.delay)
+	}
+
+	/// Set the cursor position. See [set_cursor_pos](HD44780::set_cursor_pos).
+	pub fn set_cursor_pos(&mut self, position: u8) -> Result<(), B::Error> {
+		self.lcd.set_cursor_pos(position, &mut self.delay)
+	}
+
+	/// Set the cursor position. See [set_cursor_xy](HD44780::set_cursor_xy).
+	pub fn set_cursor_xy(&mut self, position: (u8, u8)) -> Result<(), B::Error> {
+		self.lcd.set_cursor_xy(position, &mut self.delay)
+	}
+
+	/// Shift just the cursor to the left or the right. See [shift_cursor](HD44780::shift_cursor).
+	pub fn shift_cursor(&mut self, dir: Direction) -> Result<(), B::Error> {
+		self.lcd.shift_cursor(dir, &mut self.delay)
+	}
+
+	/// Shift the entire display to the left or the right. See [shift_display](HD44780::shift_display).
+	pub fn shift_display(&mut self, dir: Direction) -> Result<(), B::Error> {
+		self.lcd.shift_display(dir, &mut self.delay)
+	}
+
+	/// Send an instruction to the `HD44780`. See [send](HD44780::send).
+	pub fn send(&mut self, instruction: Instruction) -> Result<(), B::Error> {
+		self.lcd.send(instruction, &mut self.delay)
+	}
+
+	/// Store a 5x8 custom character in one of the eight CGRAM slots (`0..=7`). See [set_custom_char](HD44780::set_custom_char).
+	pub fn set_custom_char(&mut self, slot: u8, pattern: &[u8; 8]) -> Result<(), B::Error> {
+		self.lcd.set_custom_char(slot, pattern, &mut self.delay)
+	}
+
+	/// Store a [`Glyph5x8`](crate::glyph::Glyph5x8) or [`Glyph5x10`](crate::glyph::Glyph5x10) in CGRAM. See [upload_glyph](HD44780::upload_glyph).
+	pub fn upload_glyph<G: Glyph>(&mut self, slot: u8, glyph: &G) -> Result<(), B::Error> {
+		self.lcd.upload_glyph(slot, glyph, &mut self.delay)
+	}
+
+	/// Load glyphs into CGRAM, skipping the slots `bank` knows to hold them already. See
+	/// [activate_glyphs](HD44780::activate_glyphs).
+	pub fn activate_glyphs(&mut self, bank: &mut GlyphBank, glyphs: &[Glyph5x8]) -> Result<(), B::Error> {
+		self.lcd.activate_glyphs(bank, glyphs, &mut self.delay)
+	}
+
+	/// Write a single character to the `HD44780`. See [write_char](HD44780::write_char).
+	pub fn write_char(&mut self, data: char) -> Result<(), B::Error> {
+		self.lcd.write_char(data, &mut self.delay)
+	}
+
+	/// Writes a string to the `HD44780`. See [write_str](HD44780::write_str).
+	pub fn write_str(&mut self, string: &str) -> Result<(), B::Error> {
+		self.lcd.write_str(string, &mut self.delay)
+	}
+
+	/// Writes a sequence of bytes to the `HD44780`. See [write_bytes](HD44780::write_bytes).
+	pub fn write_bytes(&mut self, string: &[u8]) -> Result<(), B::Error> {
+		self.lcd.write_bytes(string, &mut self.delay)
+	}
+
+	/// Writes a single byte to the `HD44780`. See [write_byte](HD44780::write_byte).
+	pub fn write_byte(&mut self, data: u8) -> Result<(), B::Error> {
+		self.lcd.write_byte(data, &mut self.delay)
+	}
+}
+
+impl<B, M, C, D> core::fmt::Write for HD44780WithDelay<B, M, C, D>
+where
+	B: DataBus,
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+	D: DelayNs,
+{
+	fn write_str(&mut self, s: &str) -> core::fmt::Result {
+		HD44780WithDelay::write_str(self, s).map_err(|_| core::fmt::Error)
+	}
+
+	fn write_char(&mut self, c: char) -> core::fmt::Result {
+		HD44780WithDelay::write_char(self, c).map_err(|_| core::fmt::Error)
+	}
+}
+
+#[cfg(feature = "ufmt")]
+impl<B, M, C, D> ufmt::uWrite for HD44780WithDelay<B, M, C, D>
+where
+	B: DataBus,
+	M: DisplayMemoryMap,
+	C: CharsetWithFallback,
+	D: DelayNs,
+{
+	type Error = Error<B::Error>;
+
+	fn write_str(&mut self, s: &str) -> Result<(), B::Error> {
+		HD44780WithDelay::write_str(self, s)
+	}
+
+	fn write_char(&mut self, c: char) -> Result<(), B::Error> {
+		HD44780WithDelay::write_char(self, c)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use core::fmt::Write;
+
+	use super::*;
+	use crate::{
+		charset::CharsetA02,
+		entry_mode::EntryMode,
+		memory_map::MemoryMap1602,
+		test_util::{NoDelay, Recorder},
+	};
+
+	#[test]
+	fn formats_text() {
+		let mut lcd = HD44780::new_raw(
+			Recorder::default(),
+			MemoryMap1602::new(),
+			CharsetA02::QUESTION_FALLBACK,
+			EntryMode::default(),
+			DisplayMode::default(),
+		)
+		.with_delay(NoDelay);
+
+		write!(lcd, "{}°C", 21).unwrap();
+
+		let (bus, _) = lcd.destroy();
+		assert!(bus.data().eq(*b"21\xB0C"));
+	}
+}